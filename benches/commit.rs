@@ -0,0 +1,101 @@
+//! Timings for the commitment scheme and the accumulator across vector sizes.
+//! `pcs/commit_precomputed` is `commit` through a `PrecomputedKey`.
+//!
+//! `msm_vs_fold` times the multi-scalar multiplication `commit`, `open` and
+//! `batch_open` use against folding one scalar multiplication per term, and
+//! `batch_check` does the same for verifying a batch opening.
+
+use ark_bls12_377::{Fr, G1Affine, G1Projective};
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::UniformRand;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use oblivious_syncing_service::accumulator::insert;
+use oblivious_syncing_service::curve::Bls12_377;
+use oblivious_syncing_service::key::CommitmentKey;
+use oblivious_syncing_service::pcs::{batch_check, batch_open, commit, fold_terms};
+use oblivious_syncing_service::precompute::{PrecomputedKey, DEFAULT_WINDOW};
+use rand::thread_rng;
+
+const SIZES: [usize; 5] = [4, 8, 16, 64, 256];
+
+fn random_vector(n: usize) -> Vec<Fr> {
+    let mut rng = thread_rng();
+    (0..n).map(|_| Fr::rand(&mut rng)).collect()
+}
+
+fn bench_pcs(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pcs");
+    let r = Fr::rand(&mut thread_rng());
+    for n in SIZES {
+        let key = CommitmentKey::with_len(n).unwrap();
+        let precomputed = PrecomputedKey::with_generators(n + 1, DEFAULT_WINDOW).unwrap();
+        let v = random_vector(n);
+        let half: Vec<usize> = (0..n).step_by(2).collect();
+
+        group.bench_with_input(BenchmarkId::new("commit", n), &v, |b, v| b.iter(|| key.commit(v, r).unwrap()));
+        group.bench_with_input(BenchmarkId::new("commit_precomputed", n), &v, |b, v| {
+            b.iter(|| precomputed.commit(v, r).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("open", n), &v, |b, v| b.iter(|| key.open(v, r, n / 2).unwrap()));
+        group.bench_with_input(BenchmarkId::new("open_all", n), &v, |b, v| b.iter(|| key.open_all(v, r).unwrap()));
+        group.bench_with_input(BenchmarkId::new("batch_open", n), &v, |b, v| {
+            b.iter(|| key.batch_open(v, r, half.iter().copied()).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_msm_vs_fold(c: &mut Criterion) {
+    let mut group = c.benchmark_group("msm_vs_fold");
+    let key = CommitmentKey::with_len(32).unwrap();
+    for n in [2, 4, 6, 8, 12, 16, 32] {
+        let points: &[G1Affine] = &key.points()[1..=n];
+        let v = random_vector(n);
+
+        group.bench_with_input(BenchmarkId::new("msm", n), &v, |b, v| {
+            b.iter(|| G1Projective::msm_unchecked(points, v).into_affine())
+        });
+        group.bench_with_input(BenchmarkId::new("fold", n), &v, |b, v| {
+            b.iter(|| fold_terms::<Bls12_377>(points, v).into_affine())
+        });
+    }
+    group.finish();
+}
+
+fn bench_batch_check(c: &mut Criterion) {
+    let mut group = c.benchmark_group("batch_check");
+    let r = Fr::rand(&mut thread_rng());
+    let v = random_vector(20);
+    let key = CommitmentKey::with_len(v.len()).unwrap();
+    let commitment = commit(&v, r).unwrap();
+    for n in [4, 19] {
+        let indices: Vec<usize> = (0..n).collect();
+        let opening = batch_open(&v, r, indices.iter().copied()).unwrap();
+        let bases: Vec<G1Affine> = indices.iter().map(|&j| key.points()[j + 1]).collect();
+
+        group.bench_with_input(BenchmarkId::new("msm", n), &opening, |b, o| {
+            b.iter(|| assert!(batch_check(commitment, &o.values, o.witness, &indices, v.len()).unwrap()))
+        });
+        group.bench_with_input(BenchmarkId::new("fold", n), &opening, |b, o| {
+            b.iter(|| assert_eq!((fold_terms::<Bls12_377>(&bases, &o.values) + o.witness).into_affine(), commitment))
+        });
+    }
+    group.finish();
+}
+
+fn bench_insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("accumulator");
+    let r = Fr::rand(&mut thread_rng());
+    // `insert` commits to the polynomial with these roots, so at most
+    // `default_vector_len() - 1` of them
+    for n in [1, 4, 8, 16, 19] {
+        let roots = random_vector(n);
+        group.bench_with_input(BenchmarkId::new("insert", n), &roots, |b, roots| {
+            b.iter(|| insert(roots, G1Affine::default(), r).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_pcs, bench_msm_vs_fold, bench_batch_check, bench_insert);
+criterion_main!(benches);
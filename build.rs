@@ -9,13 +9,55 @@ fn main() {
     let mut f = File::create(&dest_path).expect("Failed to create output file");
 
     let file = File::open("trusted_setup.txt").expect("Failed to open trusted_setup.txt");
-    let lines = io::BufReader::new(file).lines();
+    let lines: Vec<String> = io::BufReader::new(file)
+        .lines()
+        .take(8257)
+        .collect::<io::Result<_>>()
+        .expect("Failed to read line");
     
     writeln!(f, "pub const TRUSTED_SETUP: [&str; 8257] = [").expect("Failed to write");
-    for line in lines.take(8257) {
-        writeln!(f, "    \"{}\",", line.expect("Failed to read line")).expect("Failed to write");
+    for line in &lines {
+        writeln!(f, "    \"{}\",", line).expect("Failed to write");
     }
     writeln!(f, "];").expect("Failed to write");
     
     println!("cargo:rerun-if-changed=trusted_setup.txt");
-} 
\ No newline at end of file
+
+    #[cfg(feature = "verify-only")]
+    write_generators(&out_dir, &lines);
+
+    #[cfg(feature = "server")]
+    {
+        tonic_build::compile_protos("proto/sync.proto").expect("Failed to compile protos");
+        println!("cargo:rerun-if-changed=proto/sync.proto");
+    }
+}
+
+/// Derives `POINTS` ahead of time and writes their compressed encodings to
+/// `generators.rs`, so a verifier decodes them instead of hashing the setup.
+#[cfg(feature = "verify-only")]
+fn write_generators(out_dir: &str, lines: &[String]) {
+    use ark_bls12_377::g1::Config;
+    use ark_ec::hashing::{curve_maps::wb::WBMap, map_to_curve_hasher::MapToCurveBasedHasher, HashToCurve};
+    use ark_ec::short_weierstrass::Projective;
+    use ark_ff::field_hashers::DefaultFieldHasher;
+    use ark_serialize::CanonicalSerialize;
+    use sha2::Sha256;
+
+    // Must match `pcs::NUM_POINTS` and `Bls12_377::DST`; a test compares the
+    // embedded points with the runtime derivation
+    const NUM_POINTS: usize = 21;
+    const DST: &[u8] = b"BLS12377G1_XMD:SHA-256_SSWU_RO_";
+
+    let hasher = MapToCurveBasedHasher::<Projective<Config>, DefaultFieldHasher<Sha256>, WBMap<Config>>::new(DST)
+        .expect("Failed to build hasher");
+    let mut f = File::create(Path::new(out_dir).join("generators.rs")).expect("Failed to create output file");
+    writeln!(f, "pub(crate) const GENERATORS: [[u8; 48]; {}] = [", NUM_POINTS).expect("Failed to write");
+    for line in &lines[..NUM_POINTS] {
+        let p = hasher.hash(line.as_bytes()).expect("Failed to hash to curve");
+        let mut buf = Vec::new();
+        p.serialize_compressed(&mut buf).expect("Failed to serialize");
+        writeln!(f, "    {:?},", buf).expect("Failed to write");
+    }
+    writeln!(f, "];").expect("Failed to write");
+}
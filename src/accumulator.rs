@@ -2,17 +2,27 @@ use ark_bls12_377::{Fr, G1Affine, G1Projective};
 use ark_ff::{Field, PrimeField, Zero};
 use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial, Polynomial};
 use ark_ec::CurveGroup;
-use sha2::{Digest, Sha256};
+use core::marker::PhantomData;
+use sha2::Digest;
 use anyhow::Result;
+use ark_ff::UniformRand;
 use ark_serialize::CanonicalSerialize;
+use rand::{thread_rng, CryptoRng, RngCore};
 
-use crate::pcs::{commit, POINTS, open, check};
+use crate::curve::{Bls12_377, PcsCurve};
+use crate::error::Error;
+use crate::lazy::Lazy;
+use crate::pcs::{commit, commit_padded, validate_point, POINTS, open, check};
+use crate::sigma::challenge;
+use crate::transcript::Transcript;
 
 /// Evaluate poly at v
 fn evaluate_poly(coeffs: &[Fr], v: Fr) -> Fr {
     DensePolynomial::from_coefficients_vec(coeffs.to_vec()).evaluate(&v)
 }
 
+/// `Π (x - root)` over `roots`; the empty set gives the constant `1`, which
+/// has no roots.
 pub fn poly_from_roots(roots: &[Fr]) -> DensePolynomial<Fr> {
     let one = Fr::ONE;
     let mut poly = DensePolynomial::from_coefficients_vec(vec![one]); // constant 1
@@ -27,14 +37,70 @@ pub fn poly_from_roots(roots: &[Fr]) -> DensePolynomial<Fr> {
     poly
 }
 
-fn hash_points_to_fr(p1: &G1Affine, p2: &G1Affine) -> Fr {
-    let mut hasher = Sha256::new();
-    let mut buf = [0u8; 96]; // 2 * 48-byte compressed points
-    p1.serialize_compressed(&mut buf[..48]).unwrap();
-    p2.serialize_compressed(&mut buf[48..]).unwrap();
-    hasher.update(&buf);
-    let hash_bytes = hasher.finalize();
-    Fr::from_le_bytes_mod_order(&hash_bytes)
+/// Domain separation tag for `EPOCH_BASE`, distinct from the setup DST.
+pub const EPOCH_DST: &[u8] = b"OBLIVIOUS-SYNC-V01_EPOCH_XMD:SHA-256_SSWU_RO_";
+
+/// Base the epoch term of an epoch-bound step is added under, independent of
+/// `POINTS`.
+static EPOCH_BASE: Lazy<G1Affine> = Lazy::new(|| Bls12_377::hash_to_curve(EPOCH_DST, b"epoch").expect("EPOCH_DST is valid"));
+
+/// Derives the chain scalar `h = H([epoch], A_i, P_i)` of a step.
+trait ChainHash {
+    fn chain_scalar(epoch: Option<u64>, a: &G1Affine, p: &G1Affine) -> Fr;
+}
+
+/// Default chain hash: a merlin transcript labelled `accumulator-chain`.
+struct TranscriptHash;
+
+impl ChainHash for TranscriptHash {
+    fn chain_scalar(epoch: Option<u64>, a: &G1Affine, p: &G1Affine) -> Fr {
+        let mut transcript = Transcript::new(b"accumulator-chain");
+        if let Some(epoch) = epoch {
+            transcript.append_u64(b"epoch", epoch);
+        }
+        transcript.append_point(b"A", a);
+        transcript.append_point(b"P", p);
+        transcript.challenge_scalar(b"h")
+    }
+}
+
+/// Pre-transcript chain hash: digest `D` over `[epoch LE ||] A || P`, reached
+/// through the `*_with::<D>` functions. `::<Sha256>` reproduces chains built
+/// before the transcript became the default.
+struct DigestHash<D>(PhantomData<D>);
+
+impl<D: Digest> ChainHash for DigestHash<D> {
+    fn chain_scalar(epoch: Option<u64>, a: &G1Affine, p: &G1Affine) -> Fr {
+        let mut hasher = D::new();
+        if let Some(epoch) = epoch {
+            hasher.update(epoch.to_le_bytes());
+        }
+        let mut buf = [0u8; 96]; // 2 * 48-byte compressed points
+        a.serialize_compressed(&mut buf[..48]).unwrap();
+        p.serialize_compressed(&mut buf[48..]).unwrap();
+        hasher.update(buf);
+        Fr::from_le_bytes_mod_order(&hasher.finalize())
+    }
+}
+
+/// Coefficients of `poly_from_roots(roots)`, checked to fit the key: a set of
+/// `n` roots has `n + 1` coefficients, one per value generator.
+fn set_coeffs(roots: &[Fr]) -> Result<Vec<Fr>> {
+    let max = POINTS.len() - 2;
+    if roots.len() > max {
+        return Err(Error::TooManyRoots { max }.into());
+    }
+    let coeffs = poly_from_roots(roots).coeffs;
+    // Monic of degree `roots.len()`, so the count above is the coefficient count
+    debug_assert_eq!(coeffs.len(), roots.len() + 1);
+    debug_assert_eq!(coeffs.last(), Some(&Fr::ONE));
+    Ok(coeffs)
+}
+
+/// Commits to a polynomial's coefficients, zero-padded up to the key length
+/// (zero high coefficients leave the polynomial unchanged).
+fn commit_poly(coeffs: &[Fr], r: Fr) -> Result<G1Affine> {
+    Ok(commit_padded(coeffs, r)?)
 }
 
 pub struct State {
@@ -42,59 +108,497 @@ pub struct State {
     pub Commitment: G1Affine,
 }
 
+/// Chains a commitment to the set `roots`.
+///
+/// An empty `roots` is the empty set: it commits to the constant polynomial
+/// `1`, so every value is a non-member of that step (and `remove` of every
+/// root lands on the same polynomial).
 pub fn insert(roots: &[Fr], a_prev: G1Affine, r: Fr) -> Result<State> {
+    insert_step::<TranscriptHash>(None, roots, a_prev, r)
+}
+
+/// `insert` with the chain hash `H` computed by digest `D` over the two points
+/// instead of the transcript; `::<Sha256>` is the pre-transcript behaviour.
+pub fn insert_with<D: Digest>(roots: &[Fr], a_prev: G1Affine, r: Fr) -> Result<State> {
+    insert_step::<DigestHash<D>>(None, roots, a_prev, r)
+}
+
+fn insert_step<H: ChainHash>(epoch: Option<u64>, roots: &[Fr], a_prev: G1Affine, r: Fr) -> Result<State> {
     // Build polynomial with given roots
-    let poly = poly_from_roots(roots);
-    let coeffs = &poly.coeffs;
+    let coeffs = set_coeffs(roots)?;
 
     // Commit to polynomial
-    let p_i = commit(coeffs, r)?;
-
-    // Compute h = H(A_i, P_i)
-    let h = hash_points_to_fr(&a_prev, &p_i);
+    let p_i = commit_poly(&coeffs, r)?;
 
-    // Compute A_{i+1} = [h] A_i + P_i
-    let next = a_prev * h + p_i;
+    // Compute A_{i+1} = [H(A_i, P_i)] A_i + P_i
     Ok(State {
-        Accumulator: next.into_affine(),
+        Accumulator: chain_step_at::<H>(epoch, a_prev, p_i),
         Commitment: p_i,
     })
 }
 
-pub fn check_non_membership(roots: &[Fr], v: Fr, r: Fr, s_prev: G1Affine) -> Result<State> {
+/// Inserts several root sets in one call, committing `root_sets[k]` with
+/// blinding `r[k]`.
+///
+/// The sets are chained in slice order, exactly as repeated `insert` calls
+/// would: `A_{k+1} = [H(A_k, P_k)] A_k + P_k` with `A_0 = a_prev`. Returns the
+/// final state (whose `Commitment` is the last set's) together with every
+/// intermediate commitment `P_0, P_1, ...`, which `verify_insert` replays.
+pub fn insert_many(root_sets: &[Vec<Fr>], a_prev: G1Affine, r: &[Fr]) -> Result<(State, Vec<G1Affine>)> {
+    insert_many_step(None, root_sets, a_prev, r)
+}
+
+fn insert_many_step(epoch: Option<u64>, root_sets: &[Vec<Fr>], a_prev: G1Affine, r: &[Fr]) -> Result<(State, Vec<G1Affine>)> {
+    if root_sets.is_empty() {
+        return Err(anyhow::anyhow!("no root sets to insert"));
+    }
+    if root_sets.len() != r.len() {
+        return Err(anyhow::anyhow!(
+            "expected {} blinding factors, got {}",
+            root_sets.len(),
+            r.len()
+        ));
+    }
+
+    let mut state = State { Accumulator: a_prev, Commitment: G1Affine::default() };
+    let mut commitments = Vec::with_capacity(root_sets.len());
+    for (roots, r_k) in root_sets.iter().zip(r) {
+        state = insert_step::<TranscriptHash>(epoch, roots, state.Accumulator, *r_k)?;
+        commitments.push(state.Commitment);
+    }
+    Ok((state, commitments))
+}
+
+/// Replays the chain of an `insert_many` call from `a_prev` and checks it ends at `a_final`.
+pub fn verify_insert(a_prev: G1Affine, a_final: G1Affine, commitments: &[G1Affine]) -> bool {
+    verify_insert_step(None, a_prev, a_final, commitments)
+}
+
+fn verify_insert_step(epoch: Option<u64>, a_prev: G1Affine, a_final: G1Affine, commitments: &[G1Affine]) -> bool {
+    !commitments.is_empty()
+        && commitments.iter().fold(a_prev, |acc, p| chain_step_at::<TranscriptHash>(epoch, acc, *p)) == a_final
+}
+
+/// Removes `roots_to_remove` from the set `roots` and chains the result.
+///
+/// The accumulator only stores a hash chain of commitments, so the caller
+/// supplies the current set. The step commits to the quotient
+/// `p(x) / Π (x - d)` over the removed roots `d` (i.e. the polynomial of the
+/// remaining roots) and folds it in exactly like `insert`, so the newest
+/// commitment no longer vanishes at any removed root. Each removed root must
+/// occur in `roots`; repeated roots are removed once per occurrence.
+pub fn remove(roots: &[Fr], roots_to_remove: &[Fr], a_prev: G1Affine, r: Fr) -> Result<State> {
+    remove_step::<TranscriptHash>(roots, roots_to_remove, a_prev, r)
+}
+
+pub fn remove_with<D: Digest>(roots: &[Fr], roots_to_remove: &[Fr], a_prev: G1Affine, r: Fr) -> Result<State> {
+    remove_step::<DigestHash<D>>(roots, roots_to_remove, a_prev, r)
+}
+
+fn remove_step<H: ChainHash>(roots: &[Fr], roots_to_remove: &[Fr], a_prev: G1Affine, r: Fr) -> Result<State> {
+    let mut remaining = roots.to_vec();
+    for root in roots_to_remove {
+        let pos = remaining
+            .iter()
+            .position(|x| x == root)
+            .ok_or_else(|| anyhow::anyhow!("root to remove is not in the set"))?;
+        remaining.swap_remove(pos);
+    }
+
+    insert_step::<H>(None, &remaining, a_prev, r)
+}
+
+/// Checks that `state` is a valid hash-chain step from `a_prev`, i.e.
+/// `A_{i+1} = [H(A_i, P_i)] A_i + P_i`. Applies to both `insert` and `remove`.
+pub fn verify_transition(a_prev: G1Affine, state: &State) -> bool {
+    state.Accumulator == chain_step(a_prev, state.Commitment)
+}
+
+pub fn verify_transition_with<D: Digest>(a_prev: G1Affine, state: &State) -> bool {
+    state.Accumulator == chain_step_at::<DigestHash<D>>(None, a_prev, state.Commitment)
+}
+
+/// One hash-chain step: `[H(A_i, P_i)] A_i + P_i`.
+pub(crate) fn chain_step(a_prev: G1Affine, p_i: G1Affine) -> G1Affine {
+    chain_step_at::<TranscriptHash>(None, a_prev, p_i)
+}
+
+fn chain_step_at<H: ChainHash>(epoch: Option<u64>, a_prev: G1Affine, p_i: G1Affine) -> G1Affine {
+    let h = H::chain_scalar(epoch, &a_prev, &p_i);
+    let next = a_prev * h + p_i;
+    match epoch {
+        // `[h]A_i` vanishes for an identity `A_i`, so the epoch also gets a
+        // term of its own
+        Some(epoch) => (next + *EPOCH_BASE * epoch_scalar(epoch)).into_affine(),
+        None => next.into_affine(),
+    }
+}
+
+/// `H'(epoch)`: a transcript labelled `accumulator-epoch` over the epoch.
+fn epoch_scalar(epoch: u64) -> Fr {
+    let mut transcript = Transcript::new(b"accumulator-epoch");
+    transcript.append_u64(b"epoch", epoch);
+    transcript.challenge_scalar(b"e")
+}
+
+/// Joins the heads of two independently maintained chains into one:
+/// `[H_merge(A, B)] A + B`, with `H_merge` a transcript labelled
+/// `accumulator-merge` so a merge never verifies as an `insert` step.
+///
+/// This is a chain step that folds in `b` where a set commitment would go; it
+/// does not re-derive or interleave the two shards' steps. The challenge
+/// depends only on the two heads, so checking a merge needs neither shard's
+/// intermediate commitments. Each shard's own steps, and the membership and
+/// non-membership proofs made against their commitments, stay valid: prove a
+/// step is in a shard (e.g. `history::verify_prefix` up to `a`) and then that
+/// `a` and `b` merged into the new head. `merge(a, b)` and `merge(b, a)`
+/// differ; the merged head can be extended with `insert` like any other.
+pub fn merge(a: G1Affine, b: G1Affine) -> G1Affine {
+    let mut transcript = Transcript::new(b"accumulator-merge");
+    transcript.append_point(b"A", &a);
+    transcript.append_point(b"B", &b);
+    let h = transcript.challenge_scalar(b"h");
+    (a * h + b).into_affine()
+}
+
+/// Checks that `merged` is `merge(a, b)`.
+pub fn verify_merge(a: G1Affine, b: G1Affine, merged: G1Affine) -> bool {
+    merge(a, b) == merged
+}
+
+/// Result of a non-membership step: the new chain state plus the value `v`
+/// and `alpha = p(v)` a verifier needs to rebuild `P'_i = P_i - [alpha]G_0`.
+///
+/// `verify_non_membership` only checks the chain step and `alpha != 0`; tying
+/// `alpha` to the committed polynomial takes an evaluation proof at `v`
+/// (see `prove_evaluation`, or `sigma::open_eval` given the coefficients).
+pub struct NonMembershipProof {
+    pub state: State,
+    pub v: Fr,
+    pub alpha: Fr,
+}
+
+/// Proves `v` is not in `roots` and chains the step from `s_prev`. Every `v`
+/// is outside the empty set, with `alpha = 1`.
+pub fn check_non_membership(roots: &[Fr], v: Fr, r: Fr, s_prev: G1Affine) -> Result<NonMembershipProof> {
+    non_membership_step::<TranscriptHash>(None, roots, v, r, s_prev)
+}
+
+pub fn check_non_membership_with<D: Digest>(roots: &[Fr], v: Fr, r: Fr, s_prev: G1Affine) -> Result<NonMembershipProof> {
+    non_membership_step::<DigestHash<D>>(None, roots, v, r, s_prev)
+}
+
+fn non_membership_step<H: ChainHash>(epoch: Option<u64>, roots: &[Fr], v: Fr, r: Fr, s_prev: G1Affine) -> Result<NonMembershipProof> {
     // Build polynomial
-    let poly = poly_from_roots(roots);
-    let coeffs = &poly.coeffs;
+    let coeffs = set_coeffs(roots)?;
 
     // Evaluate poly at v
-    let alpha = evaluate_poly(coeffs, v);
+    let alpha = evaluate_poly(&coeffs, v);
     if alpha.is_zero() {
         return Err(anyhow::anyhow!("v is in the root set; cannot prove non-membership"));
     }
 
     // Commit to poly
-    let p_i = commit(coeffs, r)?;
+    let p_i = commit_poly(&coeffs, r)?;
 
     // P'_i = P_i - [α]G₀
-    let p_i_prime = p_i - POINTS[0] * alpha;
-
-    // Hash to get h'
-    let h_prime = hash_points_to_fr(&s_prev, &p_i_prime.into_affine());
+    let p_i_prime = (p_i - POINTS[0] * alpha).into_affine();
 
-    // s_{i+1} = [h'] s_prev + P'_i
-    let next = s_prev * h_prime + p_i_prime;
-    Ok(State {
-        Accumulator: next.into_affine(),
-        Commitment: p_i,
+    // s_{i+1} = [H(s_prev, P'_i)] s_prev + P'_i
+    Ok(NonMembershipProof {
+        state: State {
+            Accumulator: chain_step_at::<H>(epoch, s_prev, p_i_prime),
+            Commitment: p_i,
+        },
+        v,
+        alpha,
     })
 }
 
+/// Checks that `proof` is a valid non-membership step from `s_prev`:
+/// `s_{i+1} = [H(s_i, P'_i)] s_i + P'_i` with `P'_i = P_i - [alpha]G_0` and `alpha != 0`.
+pub fn verify_non_membership(s_prev: G1Affine, proof: &NonMembershipProof) -> bool {
+    verify_non_membership_step::<TranscriptHash>(None, s_prev, proof)
+}
+
+pub fn verify_non_membership_with<D: Digest>(s_prev: G1Affine, proof: &NonMembershipProof) -> bool {
+    verify_non_membership_step::<DigestHash<D>>(None, s_prev, proof)
+}
+
+fn verify_non_membership_step<H: ChainHash>(epoch: Option<u64>, s_prev: G1Affine, proof: &NonMembershipProof) -> bool {
+    if proof.alpha.is_zero() {
+        return false;
+    }
+    let p_i_prime = (proof.state.Commitment - POINTS[0] * proof.alpha).into_affine();
+    proof.state.Accumulator == chain_step_at::<H>(epoch, s_prev, p_i_prime)
+}
+
+/// Checks non-membership steps that all start from `s_prev` against one
+/// published commitment `p_i`, each given as `(v, alpha, s_next)`.
+///
+/// Every step is rebuilt from `p_i` itself rather than from a commitment the
+/// prover sends along, so a step made against any other set fails and a batch
+/// cannot mix proofs from different sets. As with `verify_non_membership`,
+/// `alpha = p(v)` is only as strong as the chain step; pair with
+/// `prove_evaluation` to bind it to the polynomial. Errors on an invalid `p_i`
+/// or an `alpha` of zero, which would claim `v` is a root.
+pub fn verify_non_membership_batch(s_prev: G1Affine, p_i: G1Affine, proofs: &[(Fr, Fr, G1Affine)]) -> Result<bool> {
+    validate_point(&p_i)?;
+    if proofs.iter().any(|(_, alpha, _)| alpha.is_zero()) {
+        return Err(anyhow::anyhow!("alpha is zero; v is in the root set"));
+    }
+    Ok(proofs.iter().all(|(_, alpha, s_next)| {
+        let p_i_prime = (p_i - POINTS[0] * alpha).into_affine();
+        *s_next == chain_step(s_prev, p_i_prime)
+    }))
+}
+
+/// A chain state bound to an epoch.
+///
+/// Epoch-bound steps absorb the epoch into the chain hash and add a term of
+/// its own, `A_{i+1} = [H(epoch, A_i, P_i)] A_i + P_i + [H'(epoch)] G_e` with
+/// `G_e = EPOCH_BASE`. The second term keeps the epoch in the step when `A_i`
+/// is the identity (e.g. the default genesis), so a step produced in one epoch
+/// does not verify in any other and cannot be replayed across epochs.
+pub struct EpochedState {
+    pub epoch: u64,
+    pub state: State,
+}
+
+pub fn insert_at_epoch(epoch: u64, roots: &[Fr], a_prev: G1Affine, r: Fr) -> Result<EpochedState> {
+    let state = insert_step::<TranscriptHash>(Some(epoch), roots, a_prev, r)?;
+    Ok(EpochedState { epoch, state })
+}
+
+/// Checks an `insert_at_epoch` step against the epoch it claims.
+pub fn verify_transition_at_epoch(a_prev: G1Affine, state: &EpochedState) -> bool {
+    state.state.Accumulator == chain_step_at::<TranscriptHash>(Some(state.epoch), a_prev, state.state.Commitment)
+}
+
+/// `insert_many` with every step bound to `epoch`.
+pub fn insert_many_at_epoch(epoch: u64, root_sets: &[Vec<Fr>], a_prev: G1Affine, r: &[Fr]) -> Result<(EpochedState, Vec<G1Affine>)> {
+    let (state, commitments) = insert_many_step(Some(epoch), root_sets, a_prev, r)?;
+    Ok((EpochedState { epoch, state }, commitments))
+}
+
+pub fn verify_insert_at_epoch(epoch: u64, a_prev: G1Affine, a_final: G1Affine, commitments: &[G1Affine]) -> bool {
+    verify_insert_step(Some(epoch), a_prev, a_final, commitments)
+}
+
+pub fn check_non_membership_at_epoch(epoch: u64, roots: &[Fr], v: Fr, r: Fr, s_prev: G1Affine) -> Result<NonMembershipProof> {
+    non_membership_step::<TranscriptHash>(Some(epoch), roots, v, r, s_prev)
+}
+
+pub fn verify_non_membership_at_epoch(epoch: u64, s_prev: G1Affine, proof: &NonMembershipProof) -> bool {
+    verify_non_membership_step::<TranscriptHash>(Some(epoch), s_prev, proof)
+}
+
+/// Stateful wrapper over `insert` and `check_non_membership` for long-running
+/// callers: it threads both chain heads between calls.
+///
+/// The root set of the latest `insert` is kept so that non-membership checks
+/// run against the current set, along with that insert's blinding.
+#[derive(Clone, Debug)]
+pub struct Accumulator {
+    head: G1Affine,
+    non_membership: G1Affine,
+    roots: Vec<Fr>,
+    blinding: Fr,
+}
+
+impl Default for Accumulator {
+    fn default() -> Self {
+        Self::new(G1Affine::default())
+    }
+}
+
+impl Accumulator {
+    /// Starts both chains at `genesis` with an empty root set.
+    pub fn new(genesis: G1Affine) -> Self {
+        Accumulator { head: genesis, non_membership: genesis, roots: Vec::new(), blinding: Fr::zero() }
+    }
+
+    pub fn head(&self) -> G1Affine {
+        self.head
+    }
+
+    pub fn non_membership_head(&self) -> G1Affine {
+        self.non_membership
+    }
+
+    pub fn roots(&self) -> &[Fr] {
+        &self.roots
+    }
+
+    /// Blinding of the latest insert (zero before the first one).
+    pub fn blinding(&self) -> Fr {
+        self.blinding
+    }
+
+    /// Commits to `roots` as the new current set and advances the chain.
+    /// On error the state is left unchanged.
+    pub fn insert(&mut self, roots: &[Fr], r: Fr) -> Result<State> {
+        let state = insert(roots, self.head, r)?;
+        self.head = state.Accumulator;
+        self.roots = roots.to_vec();
+        self.blinding = r;
+        Ok(state)
+    }
+
+    /// Proves `v` is not in the current set and advances the non-membership chain.
+    /// On error (e.g. `v` is a member) the state is left unchanged.
+    pub fn check_non_membership(&mut self, v: Fr, r: Fr) -> Result<NonMembershipProof> {
+        let proof = check_non_membership(&self.roots, v, r, self.non_membership)?;
+        self.non_membership = proof.state.Accumulator;
+        Ok(proof)
+    }
+}
+
+/// Proof that the polynomial committed in an accumulator step, `P_i`, evaluates
+/// to a claimed value at a public point.
+///
+/// KZG would commit to the quotient `(p(x) - p(z)) / (x - z)` and check it with
+/// a pairing against `[τ - z]G2`. That needs a structured setup (`[τ^k]G1` and
+/// `[τ]G2`), but `POINTS` are independent hash-to-curve generators with no G2
+/// counterpart, so a quotient commitment cannot be tied back to `P_i`. This
+/// instead stays in the Pedersen setting: a sigma proof that the committed
+/// coefficients `c` satisfy `<c, (1, z, z^2, ...)> = y`. It needs no pairings
+/// and hides `p`, but its size is linear in the number of coefficients.
+pub struct EvaluationProof {
+    /// Commitment to the masking coefficients.
+    pub t: G1Affine,
+    /// Evaluation of the masking polynomial at the point.
+    pub t_eval: Fr,
+    pub s: Vec<Fr>,
+    pub s_r: Fr,
+}
+
+/// `(1, z, z^2, ...)` up to the key length, i.e. evaluation at `z` as an inner product.
+fn powers(z: Fr) -> Vec<Fr> {
+    core::iter::successors(Some(Fr::ONE), |x| Some(*x * z))
+        .take(POINTS.len() - 1)
+        .collect()
+}
+
+fn inner_product(a: &[Fr], b: &[Fr]) -> Fr {
+    a.iter().zip(b).map(|(x, y)| *x * y).sum()
+}
+
+/// Returns `p(point)` for the polynomial with the given roots, with a proof
+/// that it is the evaluation of the polynomial committed by `insert(roots, _, r)`.
+pub fn prove_evaluation(roots: &[Fr], point: Fr, r: Fr) -> Result<(Fr, EvaluationProof)> {
+    prove_evaluation_with_rng(roots, point, r, &mut thread_rng())
+}
+
+/// `prove_evaluation` drawing its masks from `rng`.
+pub fn prove_evaluation_with_rng<R: RngCore + CryptoRng>(roots: &[Fr], point: Fr, r: Fr, rng: &mut R) -> Result<(Fr, EvaluationProof)> {
+    let mut coeffs = set_coeffs(roots)?;
+    coeffs.resize(POINTS.len() - 1, Fr::zero());
+    let b = powers(point);
+    let value = inner_product(&coeffs, &b);
+    let c = commit(&coeffs, r)?;
+
+    let a: Vec<Fr> = (0..coeffs.len()).map(|_| Fr::rand(rng)).collect();
+    let r_a = Fr::rand(rng);
+    let t = commit(&a, r_a)?;
+    let t_eval = inner_product(&a, &b);
+    let e = challenge(b"evaluation", &[c, t], &[point, value, t_eval]);
+
+    let s = a.iter().zip(&coeffs).map(|(a_i, c_i)| *a_i + e * c_i).collect();
+    Ok((value, EvaluationProof { t, t_eval, s, s_r: r_a + e * r }))
+}
+
+/// Checks that the polynomial committed in `commitment` evaluates to `value` at `point`.
+pub fn verify_evaluation(commitment: G1Affine, point: Fr, value: Fr, proof: &EvaluationProof) -> bool {
+    if proof.s.len() != POINTS.len() - 1 {
+        return false;
+    }
+    let e = challenge(b"evaluation", &[commitment, proof.t], &[point, value, proof.t_eval]);
+    let Ok(lhs) = commit(&proof.s, proof.s_r) else {
+        return false;
+    };
+    lhs == proof.t + commitment * e && inner_product(&proof.s, &powers(point)) == proof.t_eval + e * value
+}
+
+/// Proof that `v` is a root of the polynomial committed in `P_i`.
+///
+/// The prover commits to the quotient `q = p / (x - v)` as `quotient` and shows,
+/// with a sigma proof over both commitments, that `P_i` commits to the
+/// coefficients of `q(x) * (x - v)`. Then `(x - v)` divides `p`, so `p(v) = 0`.
+/// The top coefficient of `q` is forced to zero so the product fits the key.
+/// As with `EvaluationProof` the check stays in the Pedersen setting and needs
+/// no pairings, and neither `p` nor `q` is revealed.
+pub struct MembershipProof {
+    pub v: Fr,
+    pub quotient: G1Affine,
+    pub t_q: G1Affine,
+    pub t_p: G1Affine,
+    pub s: Vec<Fr>,
+    pub s_q: Fr,
+    pub s_p: Fr,
+}
+
+/// Coefficients of `q(x) * (x - v)`, truncated to `q.len()` terms.
+fn mul_linear(q: &[Fr], v: Fr) -> Vec<Fr> {
+    (0..q.len())
+        .map(|i| if i == 0 { -v * q[0] } else { q[i - 1] - v * q[i] })
+        .collect()
+}
+
+/// Proves `v` is in the set committed by `insert(roots, _, r)`.
+/// Errors if `v` is not one of `roots`.
+pub fn prove_membership(roots: &[Fr], v: Fr, r: Fr) -> Result<MembershipProof> {
+    prove_membership_with_rng(roots, v, r, &mut thread_rng())
+}
+
+/// `prove_membership` drawing the quotient blinding and masks from `rng`.
+pub fn prove_membership_with_rng<R: RngCore + CryptoRng>(roots: &[Fr], v: Fr, r: Fr, rng: &mut R) -> Result<MembershipProof> {
+    let n = POINTS.len() - 1;
+    let mut coeffs = set_coeffs(roots)?;
+    coeffs.resize(n, Fr::zero());
+    if !evaluate_poly(&coeffs, v).is_zero() {
+        return Err(anyhow::anyhow!("v is not in the root set; cannot prove membership"));
+    }
+
+    // Synthetic division by (x - v); the remainder is p(v) = 0
+    let mut q = vec![Fr::zero(); n];
+    for i in (1..n).rev() {
+        q[i - 1] = coeffs[i] + v * q[i];
+    }
+    let c = commit(&coeffs, r)?;
+
+    let r_q = Fr::rand(rng);
+    let quotient = commit(&q, r_q)?;
+
+    let mut a: Vec<Fr> = (0..n).map(|_| Fr::rand(rng)).collect();
+    a[n - 1] = Fr::zero();
+    let (ra_q, ra_p) = (Fr::rand(rng), Fr::rand(rng));
+    let t_q = commit(&a, ra_q)?;
+    let t_p = commit(&mul_linear(&a, v), ra_p)?;
+    let e = challenge(b"membership", &[c, quotient, t_q, t_p], &[v]);
+
+    let s = a.iter().zip(&q).map(|(a_i, q_i)| *a_i + e * q_i).collect();
+    Ok(MembershipProof { v, quotient, t_q, t_p, s, s_q: ra_q + e * r_q, s_p: ra_p + e * r })
+}
+
+/// Checks that `proof.v` is a root of the polynomial committed in `commitment`.
+pub fn verify_membership(commitment: G1Affine, proof: &MembershipProof) -> bool {
+    let n = POINTS.len() - 1;
+    if proof.s.len() != n || !proof.s[n - 1].is_zero() {
+        return false;
+    }
+    let e = challenge(b"membership", &[commitment, proof.quotient, proof.t_q, proof.t_p], &[proof.v]);
+    let (Ok(lhs_q), Ok(lhs_p)) = (commit(&proof.s, proof.s_q), commit(&mul_linear(&proof.s, proof.v), proof.s_p)) else {
+        return false;
+    };
+    lhs_q == proof.t_q + proof.quotient * e && lhs_p == proof.t_p + commitment * e
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ark_ff::UniformRand;
-    use rand::thread_rng;
-    use crate::pcs::{open, check};
+    use crate::pcs::{open, check, Opening};
+    use sha2::Sha256;
 
     #[test]
     fn test_accumulator_operations() {
@@ -116,10 +620,10 @@ mod tests {
         
         // Open commitment at a random index
         let test_index = 5;
-        let (v_j, r_j, witness) = open(coeffs, r, test_index).unwrap();
+        let Opening { value: v_j, witness, .. } = open(coeffs, r, test_index).unwrap();
         
         // Verify the opening
-        assert!(check(state.Commitment, v_j, witness, POINTS[test_index + 1]));
+        assert!(check(state.Commitment, test_index, v_j, witness).unwrap());
         
         // Test non-membership for a value not in roots
         let v = Fr::rand(&mut rng);
@@ -131,9 +635,342 @@ mod tests {
         assert!(!alpha.is_zero(), "Non-root value should not evaluate to zero");
         
         // Verify the non-membership commitment matches the polynomial commitment
-        assert_eq!(non_mem_state.Commitment, state.Commitment, "Non-membership commitment should match the original commitment");
+        assert_eq!(non_mem_state.state.Commitment, state.Commitment, "Non-membership commitment should match the original commitment");
         
         // Test that a root value fails non-membership check
         assert!(check_non_membership(&roots, roots[0], r, s_0).is_err());
     }
+
+    #[test]
+    fn test_non_membership_alpha() {
+        let mut rng = thread_rng();
+        let roots = (1..20).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let (v, r) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let s_0 = G1Affine::default();
+
+        let proof = check_non_membership(&roots, v, r, s_0).unwrap();
+        assert_eq!(proof.v, v);
+        assert_eq!(proof.alpha, poly_from_roots(&roots).evaluate(&v));
+        assert!(verify_non_membership(s_0, &proof));
+
+        // A wrong alpha or starting point breaks the chain step
+        let forged = NonMembershipProof { state: State { ..proof.state }, v, alpha: proof.alpha + Fr::ONE };
+        assert!(!verify_non_membership(s_0, &forged));
+        assert!(!verify_non_membership(proof.state.Accumulator, &proof));
+        let zero = NonMembershipProof { state: State { ..proof.state }, v, alpha: Fr::zero() };
+        assert!(!verify_non_membership(s_0, &zero));
+    }
+
+    #[test]
+    fn test_non_membership_batch() {
+        let mut rng = thread_rng();
+        let roots = (1..20).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let r = Fr::rand(&mut rng);
+        let s_0 = G1Affine::default();
+
+        let proofs: Vec<NonMembershipProof> =
+            (0..4).map(|_| check_non_membership(&roots, Fr::rand(&mut rng), r, s_0).unwrap()).collect();
+        let p_i = proofs[0].state.Commitment;
+        let mut batch: Vec<(Fr, Fr, G1Affine)> = proofs.iter().map(|p| (p.v, p.alpha, p.state.Accumulator)).collect();
+        assert!(verify_non_membership_batch(s_0, p_i, &batch).unwrap());
+
+        // A step against another set fails, even though it verifies on its own
+        let other = check_non_membership(&roots[1..], Fr::rand(&mut rng), r, s_0).unwrap();
+        assert!(verify_non_membership(s_0, &other));
+        batch.push((other.v, other.alpha, other.state.Accumulator));
+        assert!(!verify_non_membership_batch(s_0, p_i, &batch).unwrap());
+        assert!(!verify_non_membership_batch(s_0, other.state.Commitment, &batch[..4]).unwrap());
+
+        batch[4] = (roots[0], Fr::zero(), batch[0].2);
+        assert!(verify_non_membership_batch(s_0, p_i, &batch).is_err());
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut rng = thread_rng();
+        let roots = (1..10).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let a_0 = G1Affine::default();
+        let r = Fr::rand(&mut rng);
+
+        let inserted = insert(&roots, a_0, r).unwrap();
+        assert!(verify_transition(a_0, &inserted));
+
+        let removed = remove(&roots, &roots[..3], inserted.Accumulator, r).unwrap();
+        assert!(verify_transition(inserted.Accumulator, &removed));
+        assert!(!verify_transition(a_0, &removed));
+        assert_eq!(removed.Commitment, insert(&roots[3..], inserted.Accumulator, r).unwrap().Commitment);
+
+        // Removed roots are now non-members of the remaining set, others stay members
+        let remaining = &roots[3..];
+        let s_0 = G1Affine::default();
+        for root in &roots[..3] {
+            assert!(check_non_membership(remaining, *root, r, s_0).is_ok());
+        }
+        assert!(check_non_membership(remaining, roots[5], r, s_0).is_err());
+
+        // A value that was never inserted remains a non-member
+        let v = Fr::rand(&mut rng);
+        assert!(check_non_membership(remaining, v, r, s_0).is_ok());
+
+        // Removing a root that was never inserted errors
+        assert!(remove(&roots, &[v], inserted.Accumulator, r).is_err());
+        assert!(remove(&roots, &[roots[0], roots[0]], inserted.Accumulator, r).is_err());
+    }
+
+    #[test]
+    fn test_accumulator_struct() {
+        let mut rng = thread_rng();
+        let mut acc = Accumulator::default();
+        let (mut a, mut s) = (G1Affine::default(), G1Affine::default());
+
+        for n in [3, 8, 5] {
+            let roots = (0..n).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+            let r = Fr::rand(&mut rng);
+
+            let state = acc.insert(&roots, r).unwrap();
+            let expected = insert(&roots, a, r).unwrap();
+            assert_eq!(state.Accumulator, expected.Accumulator);
+            assert!(verify_transition(a, &state));
+            a = state.Accumulator;
+            assert_eq!(acc.head(), a);
+            assert_eq!(acc.roots(), &roots[..]);
+            assert_eq!(acc.blinding(), r);
+
+            let v = Fr::rand(&mut rng);
+            let proof = acc.check_non_membership(v, r).unwrap();
+            assert_eq!(proof.state.Accumulator, check_non_membership(&roots, v, r, s).unwrap().state.Accumulator);
+            assert!(verify_non_membership(s, &proof));
+            s = proof.state.Accumulator;
+            assert_eq!(acc.non_membership_head(), s);
+
+            // A member is rejected and leaves the state untouched
+            assert!(acc.check_non_membership(roots[0], r).is_err());
+            assert_eq!(acc.non_membership_head(), s);
+        }
+    }
+
+    #[test]
+    fn test_insert_many() {
+        let mut rng = thread_rng();
+        let root_sets: Vec<Vec<Fr>> = (1..5)
+            .map(|n| (0..n * 3).map(|_| Fr::rand(&mut rng)).collect())
+            .collect();
+        let r: Vec<Fr> = root_sets.iter().map(|_| Fr::rand(&mut rng)).collect();
+        let a_0 = G1Affine::default();
+
+        let (state, commitments) = insert_many(&root_sets, a_0, &r).unwrap();
+
+        let mut a = a_0;
+        for (k, (roots, r_k)) in root_sets.iter().zip(&r).enumerate() {
+            let step = insert(roots, a, *r_k).unwrap();
+            assert_eq!(step.Commitment, commitments[k]);
+            a = step.Accumulator;
+        }
+        assert_eq!(state.Accumulator, a);
+        assert_eq!(state.Commitment, *commitments.last().unwrap());
+
+        assert!(verify_insert(a_0, state.Accumulator, &commitments));
+        let mut swapped = commitments.clone();
+        swapped.swap(0, 1);
+        assert!(!verify_insert(a_0, state.Accumulator, &swapped));
+        assert!(!verify_insert(a_0, state.Accumulator, &commitments[1..]));
+
+        assert!(insert_many(&root_sets, a_0, &r[1..]).is_err());
+        assert!(insert_many(&[], a_0, &[]).is_err());
+    }
+
+    #[test]
+    fn test_epoch_binding() {
+        let mut rng = thread_rng();
+        let roots = (1..10).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let (v, r) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let n = 7;
+
+        // From the identity genesis `[h]A_0` vanishes; the epoch term alone
+        // must keep the first step from replaying into another epoch
+        for a_0 in [G1Affine::default(), insert(&roots[..2], G1Affine::default(), r).unwrap().Accumulator] {
+            let step = insert_at_epoch(n, &roots, a_0, r).unwrap();
+            assert!(verify_transition_at_epoch(a_0, &step));
+            assert!(!verify_transition(a_0, &step.state));
+            let replayed = EpochedState { epoch: n + 1, state: State { ..step.state } };
+            assert!(!verify_transition_at_epoch(a_0, &replayed));
+        }
+        let a_0 = G1Affine::default();
+
+        let sets = vec![roots[..3].to_vec(), roots[3..].to_vec()];
+        let (last, commitments) = insert_many_at_epoch(n, &sets, a_0, &[r, r]).unwrap();
+        assert!(verify_insert_at_epoch(n, a_0, last.state.Accumulator, &commitments));
+        assert!(!verify_insert_at_epoch(n + 1, a_0, last.state.Accumulator, &commitments));
+        assert!(!verify_insert(a_0, last.state.Accumulator, &commitments));
+
+        let proof = check_non_membership_at_epoch(n, &roots, v, r, a_0).unwrap();
+        assert!(verify_non_membership_at_epoch(n, a_0, &proof));
+        assert!(!verify_non_membership_at_epoch(n + 1, a_0, &proof));
+        assert!(!verify_non_membership(a_0, &proof));
+    }
+
+    #[test]
+    fn test_swappable_chain_hash() {
+        let mut rng = thread_rng();
+        let roots = (1..20).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let r = Fr::rand(&mut rng);
+        let v = Fr::rand(&mut rng);
+        // Non-identity, so the chain scalar actually scales `A_0`
+        let a_0 = insert(&roots[..2], G1Affine::default(), r).unwrap().Accumulator;
+
+        // The transcript is the default; `::<Sha256>` is the pre-transcript chain
+        let default = insert(&roots, a_0, r).unwrap();
+        let legacy = insert_with::<Sha256>(&roots, a_0, r).unwrap();
+        assert_eq!(default.Commitment, legacy.Commitment);
+        assert_ne!(default.Accumulator, legacy.Accumulator);
+        assert!(verify_transition_with::<Sha256>(a_0, &legacy));
+        assert!(!verify_transition(a_0, &legacy));
+        let legacy = check_non_membership_with::<Sha256>(&roots, v, r, a_0).unwrap();
+        assert!(verify_non_membership_with::<Sha256>(a_0, &legacy));
+        assert!(!verify_non_membership(a_0, &legacy));
+
+        // Another digest changes the chain but not the commitment, and is self-consistent
+        let blake = insert_with::<blake2::Blake2s256>(&roots, default.Accumulator, r).unwrap();
+        let sha3 = insert_with::<sha3::Sha3_256>(&roots, default.Accumulator, r).unwrap();
+        assert_eq!(blake.Commitment, sha3.Commitment);
+        assert_ne!(blake.Accumulator, sha3.Accumulator);
+        assert!(verify_transition_with::<blake2::Blake2s256>(default.Accumulator, &blake));
+        assert!(verify_transition_with::<sha3::Sha3_256>(default.Accumulator, &sha3));
+        assert!(!verify_transition(default.Accumulator, &blake));
+    }
+
+    #[test]
+    fn test_evaluation_proof() {
+        let mut rng = thread_rng();
+        let roots = (1..20).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let r = Fr::rand(&mut rng);
+        let state = insert(&roots, G1Affine::default(), r).unwrap();
+
+        let z = Fr::rand(&mut rng);
+        let (value, proof) = prove_evaluation(&roots, z, r).unwrap();
+        assert_eq!(value, poly_from_roots(&roots).evaluate(&z));
+        assert!(verify_evaluation(state.Commitment, z, value, &proof));
+
+        // Evaluating at a root proves membership
+        let (zero, proof_at_root) = prove_evaluation(&roots, roots[4], r).unwrap();
+        assert!(zero.is_zero());
+        assert!(verify_evaluation(state.Commitment, roots[4], zero, &proof_at_root));
+
+        // Wrong value, point, or commitment is rejected
+        assert!(!verify_evaluation(state.Commitment, z, value + Fr::ONE, &proof));
+        assert!(!verify_evaluation(state.Commitment, z + Fr::ONE, value, &proof));
+        let other = insert(&roots[1..], G1Affine::default(), r).unwrap();
+        assert!(!verify_evaluation(other.Commitment, z, value, &proof));
+
+        let too_many = (0..20).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        assert!(prove_evaluation(&too_many, z, r).is_err());
+    }
+
+    #[test]
+    fn test_membership_proof() {
+        let mut rng = thread_rng();
+        let roots = (1..20).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let r = Fr::rand(&mut rng);
+        let state = insert(&roots, G1Affine::default(), r).unwrap();
+
+        for root in [roots[0], roots[9], roots[18]] {
+            let proof = prove_membership(&roots, root, r).unwrap();
+            assert!(verify_membership(state.Commitment, &proof));
+        }
+
+        // A non-root cannot be proven, and a proof does not transfer to another value or set
+        let v = Fr::rand(&mut rng);
+        assert!(prove_membership(&roots, v, r).is_err());
+        let proof = prove_membership(&roots, roots[3], r).unwrap();
+        assert!(!verify_membership(state.Commitment, &MembershipProof { v, ..proof }));
+        let proof = prove_membership(&roots, roots[3], r).unwrap();
+        let other = insert(&roots[4..], G1Affine::default(), r).unwrap();
+        assert!(!verify_membership(other.Commitment, &proof));
+    }
+
+    #[test]
+    fn test_too_many_roots() {
+        let mut rng = thread_rng();
+        let r = Fr::rand(&mut rng);
+        let max = POINTS.len() - 2;
+        let roots = (0..max + 1).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+
+        let err = insert(&roots, G1Affine::default(), r).err().unwrap();
+        assert_eq!(err.downcast_ref::<Error>(), Some(&Error::TooManyRoots { max }));
+        let err = check_non_membership(&roots, Fr::rand(&mut rng), r, G1Affine::default()).err().unwrap();
+        assert_eq!(err.downcast_ref::<Error>(), Some(&Error::TooManyRoots { max }));
+        let err = prove_membership(&roots, roots[0], r).err().unwrap();
+        assert_eq!(err.downcast_ref::<Error>(), Some(&Error::TooManyRoots { max }));
+
+        // Exactly `max` roots fill every generator
+        let state = insert(&roots[..max], G1Affine::default(), r).unwrap();
+        assert!(verify_transition(G1Affine::default(), &state));
+    }
+
+    #[test]
+    fn test_empty_root_set() {
+        let mut rng = thread_rng();
+        let r = Fr::rand(&mut rng);
+        let a_0 = G1Affine::default();
+
+        // The empty set commits to the constant polynomial 1
+        let state = insert(&[], a_0, r).unwrap();
+        assert!(verify_transition(a_0, &state));
+        let mut one = vec![Fr::zero(); POINTS.len() - 1];
+        one[0] = Fr::ONE;
+        assert_eq!(state.Commitment, commit(&one, r).unwrap());
+
+        let roots = (1..5).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        assert_eq!(remove(&roots, &roots, a_0, r).unwrap().Commitment, state.Commitment);
+
+        // Nothing is a member, so every value is a non-member with alpha = 1
+        let v = Fr::rand(&mut rng);
+        let proof = check_non_membership(&[], v, r, a_0).unwrap();
+        assert_eq!(proof.alpha, Fr::ONE);
+        assert_eq!(proof.state.Commitment, state.Commitment);
+        assert!(verify_non_membership(a_0, &proof));
+        assert!(prove_membership(&[], v, r).is_err());
+    }
+
+    #[test]
+    fn test_merge() {
+        use crate::history::verify_prefix;
+
+        let mut rng = thread_rng();
+        let genesis = G1Affine::default();
+        let shard = |rng: &mut rand::rngs::ThreadRng| {
+            let sets: Vec<Vec<Fr>> = (0..3).map(|_| (1..20).map(|_| Fr::rand(rng)).collect()).collect();
+            let r: Vec<Fr> = (0..3).map(|_| Fr::rand(rng)).collect();
+            let (state, commitments) = insert_many(&sets, genesis, &r).unwrap();
+            (sets, r, state, commitments)
+        };
+        let (sets_a, r_a, state_a, commitments_a) = shard(&mut rng);
+        let (sets_b, r_b, state_b, commitments_b) = shard(&mut rng);
+
+        let merged = merge(state_a.Accumulator, state_b.Accumulator);
+        assert!(verify_merge(state_a.Accumulator, state_b.Accumulator, merged));
+        assert!(!verify_merge(state_b.Accumulator, state_a.Accumulator, merged));
+        assert!(!verify_merge(state_a.Accumulator, genesis, merged));
+        // Not an insert step of either head
+        let as_step = State { Accumulator: merged, Commitment: state_b.Accumulator };
+        assert!(!verify_transition(state_a.Accumulator, &as_step));
+
+        // Both shards' histories still lead to the heads that were merged
+        assert!(verify_prefix(genesis, state_a.Accumulator, &commitments_a));
+        assert!(verify_prefix(genesis, state_b.Accumulator, &commitments_b));
+
+        // Membership against a step commitment of either shard is unaffected
+        let proof = prove_membership(&sets_a[1], sets_a[1][5], r_a[1]).unwrap();
+        assert!(verify_membership(commitments_a[1], &proof));
+        let proof = prove_membership(&sets_b[2], sets_b[2][0], r_b[2]).unwrap();
+        assert!(verify_membership(commitments_b[2], &proof));
+
+        // Non-membership and further inserts continue from the merged head
+        let v = Fr::rand(&mut rng);
+        let proof = check_non_membership(&sets_b[0], v, r_b[0], merged).unwrap();
+        assert!(verify_non_membership(merged, &proof));
+        assert!(check_non_membership(&sets_b[0], sets_b[0][3], r_b[0], merged).is_err());
+        let next = insert(&sets_a[0], merged, Fr::rand(&mut rng)).unwrap();
+        assert!(verify_transition(merged, &next));
+    }
 }
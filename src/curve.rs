@@ -0,0 +1,143 @@
+use ark_ec::hashing::{
+    curve_maps::wb::{WBConfig, WBMap},
+    map_to_curve_hasher::MapToCurveBasedHasher,
+    HashToCurve,
+};
+use ark_ec::short_weierstrass::{Affine, Projective, SWCurveConfig};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::field_hashers::DefaultFieldHasher;
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+use sha2::digest::FixedOutputReset;
+use sha2::{Digest, Sha256};
+
+use crate::error::{Error, Result};
+use crate::lazy::OnceCell;
+use crate::pcs::{derive_points_on, NUM_POINTS, POINTS, TRUSTED_SETUP};
+
+/// A curve the commitment scheme can be instantiated over.
+pub trait PcsCurve: 'static {
+    type Scalar: PrimeField;
+    type Affine: AffineRepr<ScalarField = Self::Scalar, Group = Self::Projective>;
+    type Projective: CurveGroup<ScalarField = Self::Scalar, Affine = Self::Affine>;
+
+    /// Domain separation tag used to hash the trusted setup onto the curve.
+    const DST: &'static [u8];
+
+    /// Fails with `GeneratorDerivationFailed` for an empty `dst`, which
+    /// RFC 9380 disallows, or if the hasher itself fails.
+    fn hash_to_curve(dst: &[u8], message: &[u8]) -> Result<Self::Affine>;
+
+    /// Rejects the identity and points off the curve or outside the prime-order subgroup.
+    fn validate_point(p: &Self::Affine) -> Result<()>;
+
+    /// Generators derived from the trusted setup, blinding base first.
+    fn points() -> &'static [Self::Affine];
+}
+
+pub struct Bls12_377;
+pub struct Bls12_381;
+pub struct Bn254;
+
+fn derive_setup_points<C: PcsCurve>() -> Vec<C::Affine> {
+    derive_points_on::<C>(&TRUSTED_SETUP, C::DST, NUM_POINTS).expect("built-in DST and setup hash to the curve")
+}
+
+fn validate_sw_point<P: SWCurveConfig>(p: &Affine<P>) -> Result<()> {
+    if p.is_zero() {
+        return Err(Error::IdentityPoint);
+    }
+    if !p.is_on_curve() {
+        return Err(Error::NotOnCurve);
+    }
+    if !p.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(Error::NotInSubgroup);
+    }
+    Ok(())
+}
+
+/// Hashes to a WB curve with `H` as the `expand_message_xmd` digest.
+pub(crate) fn hash_to_wb_curve<P: WBConfig, H: FixedOutputReset + Default + Clone>(dst: &[u8], message: &[u8]) -> Result<Affine<P>> {
+    if dst.is_empty() {
+        return Err(Error::GeneratorDerivationFailed);
+    }
+    MapToCurveBasedHasher::<Projective<P>, DefaultFieldHasher<H>, WBMap<P>>::new(dst)
+        .and_then(|hasher| hasher.hash(message))
+        .map_err(|_| Error::GeneratorDerivationFailed)
+}
+
+impl PcsCurve for Bls12_377 {
+    type Scalar = ark_bls12_377::Fr;
+    type Affine = ark_bls12_377::G1Affine;
+    type Projective = ark_bls12_377::G1Projective;
+
+    const DST: &'static [u8] = b"BLS12377G1_XMD:SHA-256_SSWU_RO_";
+
+    fn hash_to_curve(dst: &[u8], message: &[u8]) -> Result<Self::Affine> {
+        hash_to_wb_curve::<ark_bls12_377::g1::Config, Sha256>(dst, message)
+    }
+
+    fn validate_point(p: &Self::Affine) -> Result<()> {
+        validate_sw_point(p)
+    }
+
+    fn points() -> &'static [Self::Affine] {
+        &POINTS[..]
+    }
+}
+
+impl PcsCurve for Bls12_381 {
+    type Scalar = ark_bls12_381::Fr;
+    type Affine = ark_bls12_381::G1Affine;
+    type Projective = ark_bls12_381::G1Projective;
+
+    const DST: &'static [u8] = b"BLS12381G1_XMD:SHA-256_SSWU_RO_";
+
+    fn hash_to_curve(dst: &[u8], message: &[u8]) -> Result<Self::Affine> {
+        hash_to_wb_curve::<ark_bls12_381::g1::Config, Sha256>(dst, message)
+    }
+
+    fn validate_point(p: &Self::Affine) -> Result<()> {
+        validate_sw_point(p)
+    }
+
+    fn points() -> &'static [Self::Affine] {
+        static POINTS_381: OnceCell<Vec<ark_bls12_381::G1Affine>> = OnceCell::new();
+        POINTS_381.get_or_init(derive_setup_points::<Self>)
+    }
+}
+
+impl PcsCurve for Bn254 {
+    type Scalar = ark_bn254::Fr;
+    type Affine = ark_bn254::G1Affine;
+    type Projective = ark_bn254::G1Projective;
+
+    const DST: &'static [u8] = b"BN254G1_XMD:SHA-256_TAI_RO_";
+
+    /// arkworks has no WB/SVDW map for BN254 G1, so use try-and-increment.
+    /// The cofactor is 1, so any point on the curve is in the right subgroup.
+    fn hash_to_curve(dst: &[u8], message: &[u8]) -> Result<Self::Affine> {
+        if dst.is_empty() {
+            return Err(Error::GeneratorDerivationFailed);
+        }
+        (0u32..)
+            .find_map(|counter| {
+                let mut hasher = Sha256::new();
+                hasher.update(dst);
+                hasher.update(message);
+                hasher.update(counter.to_le_bytes());
+                let x = ark_bn254::Fq::from_le_bytes_mod_order(&hasher.finalize());
+                ark_bn254::G1Affine::get_point_from_x_unchecked(x, false)
+            })
+            .ok_or(Error::GeneratorDerivationFailed)
+    }
+
+    fn validate_point(p: &Self::Affine) -> Result<()> {
+        validate_sw_point(p)
+    }
+
+    fn points() -> &'static [Self::Affine] {
+        static POINTS_BN254: OnceCell<Vec<ark_bn254::G1Affine>> = OnceCell::new();
+        POINTS_BN254.get_or_init(derive_setup_points::<Self>)
+    }
+}
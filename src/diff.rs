@@ -0,0 +1,227 @@
+//! Incremental sync: move a commitment from an old vector to a new one by
+//! sending only the changed cells.
+use ark_bls12_377::{Fr, G1Affine, G1Projective};
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::Zero;
+use ark_std::vec::Vec;
+
+use crate::error::{Error, Result};
+use crate::pcs::{commit, open_all, update_commitment, update_witness, validate_point, value_generator, Opening, BLINDING_INDEX, POINTS};
+
+/// The cells that changed between two committed vectors.
+///
+/// `deltas[k] = new[indices[k]] - old[indices[k]]` and
+/// `blinding_delta = r_new - r_old`, so that
+/// `c_new = c_old + Σ [deltas[k]] G_{indices[k]+1} + [blinding_delta] G_0`.
+/// Together with `values` a diff reveals the old values of the changed cells,
+/// but nothing about unchanged ones.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffProof {
+    pub indices: Vec<usize>,
+    pub values: Vec<Fr>,
+    pub deltas: Vec<Fr>,
+    pub blinding_delta: Fr,
+}
+
+/// Computes the diff from `(old, r_old)` to `(new, r_new)`, indices ascending.
+pub fn diff_proof(old: &[Fr], new: &[Fr], r_old: Fr, r_new: Fr) -> Result<DiffProof> {
+    if old.len() != new.len() {
+        return Err(Error::LengthMismatch { expected: old.len(), got: new.len() });
+    }
+
+    let mut diff = DiffProof {
+        indices: Vec::new(),
+        values: Vec::new(),
+        deltas: Vec::new(),
+        blinding_delta: r_new - r_old,
+    };
+    for (j, (o, n)) in old.iter().zip(new).enumerate() {
+        if o != n {
+            diff.indices.push(j);
+            diff.values.push(*n);
+            diff.deltas.push(*n - o);
+        }
+    }
+    Ok(diff)
+}
+
+/// Applies `diff` to `c_old`, yielding the commitment to the new vector.
+pub fn apply_diff(c_old: G1Affine, diff: &DiffProof) -> Result<G1Affine> {
+    if diff.deltas.len() != diff.indices.len() {
+        return Err(Error::ValuesIndicesMismatch);
+    }
+
+    let mut bases = Vec::with_capacity(diff.indices.len() + 1);
+    for &j in &diff.indices {
+        bases.push(value_generator(j)?);
+    }
+    bases.push(POINTS[BLINDING_INDEX]);
+    let mut scalars = diff.deltas.clone();
+    scalars.push(diff.blinding_delta);
+
+    Ok((c_old.into_group() + G1Projective::msm_unchecked(&bases, &scalars)).into_affine())
+}
+
+/// Checks that `diff` takes `c_old` to `c_new`. Malformed diffs fail.
+pub fn verify_diff(c_old: G1Affine, c_new: G1Affine, diff: &DiffProof) -> bool {
+    diff.values.len() == diff.indices.len()
+        && apply_diff(c_old, diff) == Ok(c_new)
+}
+
+/// Generators and deltas of `changes`, rejecting repeated indices: with one
+/// index listed twice a published change list would not say what happened.
+fn update_terms(changes: &[(usize, Fr)]) -> Result<(Vec<G1Affine>, Vec<Fr>)> {
+    let mut indices: Vec<usize> = changes.iter().map(|(j, _)| *j).collect();
+    indices.sort_unstable();
+    if indices.windows(2).any(|w| w[0] == w[1]) {
+        return Err(Error::DuplicateIndices);
+    }
+    let bases = changes.iter().map(|(j, _)| value_generator(*j)).collect::<Result<Vec<_>>>()?;
+    Ok((bases, changes.iter().map(|(_, delta)| *delta).collect()))
+}
+
+/// Commitment after adding each `(j, delta)` of `changes` to value `j`, with
+/// the blinding unchanged: what a service publishes for `verify_updates`.
+/// Errors on an index past `max_vector_len()` or a repeated index.
+pub fn apply_updates(c_old: G1Affine, changes: &[(usize, Fr)]) -> Result<G1Affine> {
+    let (bases, deltas) = update_terms(changes)?;
+    Ok((c_old.into_group() + G1Projective::msm_unchecked(&bases, &deltas)).into_affine())
+}
+
+/// Checks, without any opening, that `c_new` is `c_old` with `delta` added to
+/// value `j` and nothing else changed: `c_new - c_old = [delta]G_{j+1}`.
+pub fn verify_update(c_old: G1Affine, c_new: G1Affine, j: usize, delta: Fr) -> Result<bool> {
+    verify_updates(c_old, c_new, &[(j, delta)])
+}
+
+/// `verify_update` for several public changes, folded into one MSM of
+/// `c_old - c_new + Σ [delta_k]G_{j_k+1}`. Any change not listed, including
+/// to the blinding, makes it fail. Errors as `apply_updates` does; an invalid
+/// commitment is a failure.
+pub fn verify_updates(c_old: G1Affine, c_new: G1Affine, changes: &[(usize, Fr)]) -> Result<bool> {
+    let (mut bases, mut scalars) = update_terms(changes)?;
+    if validate_point(&c_old).is_err() || validate_point(&c_new).is_err() {
+        return Ok(false);
+    }
+    bases.extend([c_old, c_new]);
+    scalars.extend([Fr::from(1u64), -Fr::from(1u64)]);
+    Ok(G1Projective::msm_unchecked(&bases, &scalars).is_zero())
+}
+
+/// Every index's opening of one commitment, kept current across value changes.
+///
+/// Built once with `open_all`, after which `open_cached` is a lookup. For a
+/// server answering many single-index requests against a commitment that
+/// changes rarely, that replaces an `open` per request.
+#[derive(Clone, Debug)]
+pub struct WitnessCache {
+    commitment: G1Affine,
+    openings: Vec<Opening>,
+}
+
+impl WitnessCache {
+    pub fn new(v: &[Fr], r: Fr) -> Result<Self> {
+        Ok(WitnessCache { commitment: commit(v, r)?, openings: open_all(v, r)? })
+    }
+
+    /// The commitment the cached witnesses open.
+    pub fn commitment(&self) -> G1Affine {
+        self.commitment
+    }
+
+    /// The opening of `index`, as `open` would return it.
+    pub fn open_cached(&self, index: usize) -> Result<Opening> {
+        self.openings.get(index).cloned().ok_or(Error::IndexOutOfBounds)
+    }
+
+    /// Adds `delta` to value `index` and moves the commitment and every other
+    /// witness along with it.
+    pub fn invalidate(&mut self, index: usize, delta: Fr) -> Result<()> {
+        if index >= self.openings.len() {
+            return Err(Error::IndexOutOfBounds);
+        }
+        let len = self.openings.len();
+        let old = self.openings[index].value;
+        self.commitment = update_commitment(self.commitment, index, old, old + delta, len)?;
+        for (k, opening) in self.openings.iter_mut().enumerate() {
+            // The changed slot keeps its witness; only its value moves
+            if k == index {
+                opening.value += delta;
+            } else {
+                opening.witness = update_witness(opening.witness, k, index, old, old + delta, len)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcs::{commit, max_vector_len, open, NUM_POINTS};
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_diff_transforms_commitment() {
+        let mut rng = thread_rng();
+        let old: Vec<Fr> = (0..NUM_POINTS - 1).map(|_| Fr::rand(&mut rng)).collect();
+        let mut new = old.clone();
+        for j in [1, 7, 19] {
+            new[j] = Fr::rand(&mut rng);
+        }
+        let (r_old, r_new) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let c_old = commit(&old, r_old).unwrap();
+        let c_new = commit(&new, r_new).unwrap();
+
+        let diff = diff_proof(&old, &new, r_old, r_new).unwrap();
+        assert_eq!(diff.indices, vec![1, 7, 19]);
+        assert_eq!(diff.values, vec![new[1], new[7], new[19]]);
+        assert_eq!(apply_diff(c_old, &diff).unwrap(), c_new);
+        assert!(verify_diff(c_old, c_new, &diff));
+
+        // Tampered value, wrong base commitment, or bad index all fail
+        let mut tampered = diff.clone();
+        tampered.deltas[0] += Fr::from(1u64);
+        assert!(!verify_diff(c_old, c_new, &tampered));
+        assert!(!verify_diff(c_new, c_new, &diff));
+        let mut out_of_range = diff.clone();
+        out_of_range.indices[2] = max_vector_len();
+        assert_eq!(apply_diff(c_old, &out_of_range), Err(Error::IndexOutOfBounds));
+
+        // An unchanged vector with the same blinding is an empty diff
+        let same = diff_proof(&old, &old, r_old, r_old).unwrap();
+        assert!(same.indices.is_empty());
+        assert!(verify_diff(c_old, c_old, &same));
+
+        assert!(diff_proof(&old, &new[1..], r_old, r_new).is_err());
+    }
+
+    #[test]
+    fn test_witness_cache() {
+        let mut rng = thread_rng();
+        let mut v: Vec<Fr> = (0..NUM_POINTS - 1).map(|_| Fr::rand(&mut rng)).collect();
+        let r = Fr::rand(&mut rng);
+        let mut cache = WitnessCache::new(&v, r).unwrap();
+        assert_eq!(cache.commitment(), commit(&v, r).unwrap());
+        for j in 0..v.len() {
+            assert_eq!(cache.open_cached(j).unwrap(), open(&v, r, j).unwrap());
+        }
+
+        for j in [4, 4, 19] {
+            let delta = Fr::rand(&mut rng);
+            v[j] += delta;
+            cache.invalidate(j, delta).unwrap();
+            let c = commit(&v, r).unwrap();
+            assert_eq!(cache.commitment(), c);
+            for k in 0..v.len() {
+                let opening = open(&v, r, k).unwrap();
+                assert_eq!(cache.open_cached(k).unwrap(), opening);
+                assert!(opening.verify(&c).unwrap());
+            }
+        }
+
+        assert_eq!(cache.open_cached(NUM_POINTS - 1), Err(Error::IndexOutOfBounds));
+        assert_eq!(cache.invalidate(NUM_POINTS - 1, Fr::from(1u64)), Err(Error::IndexOutOfBounds));
+    }
+}
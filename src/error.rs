@@ -0,0 +1,86 @@
+use core::fmt;
+
+/// Errors returned by the commitment scheme.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The vector length doesn't match the number of value generators.
+    LengthMismatch { expected: usize, got: usize },
+    IndexOutOfBounds,
+    EmptyIndices,
+    DuplicateIndices,
+    UnsortedIndices,
+    /// `values` and `indices` passed to a batch verifier differ in length.
+    ValuesIndicesMismatch,
+    IdentityPoint,
+    NotOnCurve,
+    NotInSubgroup,
+    /// A trusted setup has fewer lines than generators required.
+    SetupTooShort { expected: usize, got: usize },
+    /// A root set's polynomial has more coefficients than the key has value
+    /// generators; at most `max` roots fit.
+    TooManyRoots { max: usize },
+    /// Two generators of a key are equal, e.g. from repeated setup lines.
+    DuplicateGenerator,
+    /// A proof names a different `CommitmentKey::id` than the verifying key.
+    KeyMismatch,
+    /// An encoded proof carries a version this build does not understand.
+    UnsupportedVersion(u8),
+    /// `BlindingTracker` saw this blinding factor before.
+    BlindingReused,
+    /// A string is not a decimal or `0x` hex integer below the scalar modulus.
+    InvalidScalar,
+    /// Encoded bytes are truncated, have trailing data, or hold an invalid field.
+    MalformedEncoding,
+    /// Hash-to-curve could not produce a generator, e.g. under an empty DST.
+    GeneratorDerivationFailed,
+    /// A witness update names the opened index itself; its witness stays the
+    /// same and the opened value changes instead.
+    OpenedIndexChanged,
+    /// An index would select the blinding generator, which carries no value.
+    BlindingGenerator,
+    /// An opening does not verify against its commitment.
+    InvalidOpening,
+    /// A scalar does not fit the integer type it is decoded to.
+    ValueOutOfRange,
+    /// The writer given to an export function returned an error.
+    WriteFailed,
+    /// A `PrecomputedKey` window outside `1..16`.
+    InvalidWindow(usize),
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::LengthMismatch { expected, got } => write!(f, "POINTS must have {} entries, got {}", expected, got),
+            Error::IndexOutOfBounds => write!(f, "Index out of bounds"),
+            Error::EmptyIndices => write!(f, "Empty indices"),
+            Error::DuplicateIndices => write!(f, "Duplicate indices not allowed"),
+            Error::UnsortedIndices => write!(f, "indices must be sorted and unique"),
+            Error::ValuesIndicesMismatch => write!(f, "values and indices must match"),
+            Error::IdentityPoint => write!(f, "Point is the identity"),
+            Error::NotOnCurve => write!(f, "Point is not on the curve"),
+            Error::NotInSubgroup => write!(f, "Point is not in the prime-order subgroup"),
+            Error::SetupTooShort { expected, got } => {
+                write!(f, "trusted setup must have at least {} lines, got {}", expected, got)
+            }
+            Error::TooManyRoots { max } => write!(f, "at most {} roots fit the commitment key", max),
+            Error::DuplicateGenerator => write!(f, "generators must be distinct"),
+            Error::KeyMismatch => write!(f, "proof was made under a different commitment key"),
+            Error::UnsupportedVersion(v) => write!(f, "unsupported wire format version {}", v),
+            Error::MalformedEncoding => write!(f, "malformed encoding"),
+            Error::InvalidScalar => write!(f, "invalid field element"),
+            Error::BlindingReused => write!(f, "blinding factor was already used"),
+            Error::GeneratorDerivationFailed => write!(f, "failed to derive a generator by hashing to the curve"),
+            Error::OpenedIndexChanged => write!(f, "the opened value changed; update it instead of the witness"),
+            Error::BlindingGenerator => write!(f, "index maps to the blinding generator"),
+            Error::InvalidOpening => write!(f, "opening does not verify against the commitment"),
+            Error::ValueOutOfRange => write!(f, "scalar is out of range for the integer type"),
+            Error::WriteFailed => write!(f, "failed to write output"),
+            Error::InvalidWindow(w) => write!(f, "window must be in 1..16, got {}", w),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
@@ -0,0 +1,234 @@
+//! Merkle log over the chain of accumulator states.
+//!
+//! Leaf `i` is `H(0x00 || A_{i+1} || P_i)` for the state produced by step `i`,
+//! inner nodes are `H(0x01 || left || right)`, and an unpaired node is promoted
+//! to the next level unchanged. The published root also binds the number of
+//! steps: `H(0x02 || n || merkle_root)`.
+use anyhow::{anyhow, Result};
+use ark_bls12_377::G1Affine;
+use ark_serialize::CanonicalSerialize;
+use sha2::{Digest, Sha256};
+
+use crate::accumulator::{chain_step, verify_transition, State};
+
+type Hash = [u8; 32];
+
+fn leaf_hash(accumulator: &G1Affine, commitment: &G1Affine) -> Hash {
+    let mut buf = [0u8; 96];
+    accumulator.serialize_compressed(&mut buf[..48]).unwrap();
+    commitment.serialize_compressed(&mut buf[48..]).unwrap();
+    Sha256::new().chain_update([0x00]).chain_update(buf).finalize().into()
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    Sha256::new().chain_update([0x01]).chain_update(left).chain_update(right).finalize().into()
+}
+
+fn bind_len(len: usize, merkle_root: &Hash) -> Hash {
+    Sha256::new()
+        .chain_update([0x02])
+        .chain_update((len as u64).to_le_bytes())
+        .chain_update(merkle_root)
+        .finalize()
+        .into()
+}
+
+/// Proof that step `index` of an `len`-step history is a given `(A_{i+1}, P_i)`.
+pub struct InclusionProof {
+    pub index: usize,
+    pub len: usize,
+    /// Sibling hashes from the leaf upwards; levels where the node is promoted are skipped.
+    pub siblings: Vec<Hash>,
+}
+
+pub struct History {
+    genesis: G1Affine,
+    accumulators: Vec<G1Affine>,
+    commitments: Vec<G1Affine>,
+}
+
+impl History {
+    pub fn new(genesis: G1Affine) -> Self {
+        History { genesis, accumulators: Vec::new(), commitments: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.commitments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commitments.is_empty()
+    }
+
+    /// The current accumulator, or the genesis value if nothing was recorded.
+    pub fn head(&self) -> G1Affine {
+        self.accumulators.last().copied().unwrap_or(self.genesis)
+    }
+
+    /// Appends a state; it must be a valid chain step from the current head.
+    pub fn record(&mut self, state: &State) -> Result<()> {
+        if !verify_transition(self.head(), state) {
+            return Err(anyhow!("state does not extend the current head"));
+        }
+        self.accumulators.push(state.Accumulator);
+        self.commitments.push(state.Commitment);
+        Ok(())
+    }
+
+    fn levels(&self) -> Vec<Vec<Hash>> {
+        let mut levels = vec![self
+            .accumulators
+            .iter()
+            .zip(&self.commitments)
+            .map(|(a, p)| leaf_hash(a, p))
+            .collect::<Vec<_>>()];
+        while levels.last().unwrap().len() > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| if pair.len() == 2 { node_hash(&pair[0], &pair[1]) } else { pair[0] })
+                .collect();
+            levels.push(next);
+        }
+        levels
+    }
+
+    pub fn root(&self) -> Hash {
+        let merkle_root = self.levels().last().and_then(|l| l.first().copied()).unwrap_or([0u8; 32]);
+        bind_len(self.len(), &merkle_root)
+    }
+
+    pub fn prove_inclusion(&self, i: usize) -> Result<InclusionProof> {
+        if i >= self.len() {
+            return Err(anyhow!("Index out of bounds"));
+        }
+        let mut siblings = Vec::new();
+        let mut index = i;
+        for level in &self.levels()[..] {
+            if let Some(sibling) = level.get(index ^ 1) {
+                siblings.push(*sibling);
+            }
+            index /= 2;
+        }
+        Ok(InclusionProof { index: i, len: self.len(), siblings })
+    }
+
+    /// Commitments `P_i..P_{n-1}` that take `A_i` to the current head.
+    pub fn prove_prefix(&self, i: usize) -> Result<Vec<G1Affine>> {
+        if i > self.len() {
+            return Err(anyhow!("Index out of bounds"));
+        }
+        Ok(self.commitments[i..].to_vec())
+    }
+}
+
+pub fn verify_inclusion(root: &Hash, accumulator: &G1Affine, commitment: &G1Affine, proof: &InclusionProof) -> bool {
+    if proof.index >= proof.len {
+        return false;
+    }
+    let mut hash = leaf_hash(accumulator, commitment);
+    let mut siblings = proof.siblings.iter();
+    let (mut index, mut width) = (proof.index, proof.len);
+    while width > 1 {
+        if index ^ 1 < width {
+            let Some(sibling) = siblings.next() else { return false };
+            hash = if index % 2 == 0 { node_hash(&hash, sibling) } else { node_hash(sibling, &hash) };
+        }
+        index /= 2;
+        width = width.div_ceil(2);
+    }
+    siblings.next().is_none() && bind_len(proof.len, &hash) == *root
+}
+
+/// Replays the hash chain from `a_i` over `commitments` and checks it reaches `a_n`.
+pub fn verify_prefix(a_i: G1Affine, a_n: G1Affine, commitments: &[G1Affine]) -> bool {
+    replay(a_i, commitments.iter().copied()) == a_n
+}
+
+/// Folds the chain step `A_{i+1} = [H(A_i, P_i)] A_i + P_i` over `commitments`
+/// from `genesis` and returns the final accumulator.
+///
+/// Commitments are consumed one at a time, so a light client can replay a
+/// chain straight from the network without buffering it.
+pub fn replay(genesis: G1Affine, commitments: impl Iterator<Item = G1Affine>) -> G1Affine {
+    commitments.fold(genesis, chain_step)
+}
+
+/// Whether replaying `commitments` from `genesis` ends at `expected_final`.
+pub fn replay_verify(genesis: G1Affine, commitments: impl Iterator<Item = G1Affine>, expected_final: G1Affine) -> bool {
+    replay(genesis, commitments) == expected_final
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accumulator::insert;
+    use ark_bls12_377::Fr;
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    fn build(steps: usize) -> History {
+        let mut rng = thread_rng();
+        let mut history = History::new(G1Affine::default());
+        for _ in 0..steps {
+            let roots = (1..20).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+            let state = insert(&roots, history.head(), Fr::rand(&mut rng)).unwrap();
+            history.record(&state).unwrap();
+        }
+        history
+    }
+
+    #[test]
+    fn test_inclusion_step_4() {
+        let history = build(10);
+        let root = history.root();
+
+        let proof = history.prove_inclusion(4).unwrap();
+        assert!(verify_inclusion(&root, &history.accumulators[4], &history.commitments[4], &proof));
+
+        // Wrong step data, wrong index and a truncated proof all fail
+        assert!(!verify_inclusion(&root, &history.accumulators[5], &history.commitments[5], &proof));
+        let moved = InclusionProof { index: 5, len: proof.len, siblings: proof.siblings.clone() };
+        assert!(!verify_inclusion(&root, &history.accumulators[4], &history.commitments[4], &moved));
+        let truncated = InclusionProof { index: 4, len: proof.len, siblings: proof.siblings[1..].to_vec() };
+        assert!(!verify_inclusion(&root, &history.accumulators[4], &history.commitments[4], &truncated));
+
+        // Every step, including the promoted last leaf, verifies
+        for i in 0..history.len() {
+            let proof = history.prove_inclusion(i).unwrap();
+            assert!(verify_inclusion(&root, &history.accumulators[i], &history.commitments[i], &proof));
+        }
+        assert!(history.prove_inclusion(10).is_err());
+    }
+
+    #[test]
+    fn test_prefix() {
+        let history = build(10);
+        let suffix = history.prove_prefix(4).unwrap();
+        assert_eq!(suffix.len(), 6);
+        // A_4 is the accumulator after step 3
+        assert!(verify_prefix(history.accumulators[3], history.head(), &suffix));
+        assert!(!verify_prefix(history.accumulators[2], history.head(), &suffix));
+        assert!(verify_prefix(G1Affine::default(), history.head(), &history.prove_prefix(0).unwrap()));
+    }
+
+    #[test]
+    fn test_replay() {
+        let history = build(100);
+        assert_eq!(replay(G1Affine::default(), history.commitments.iter().copied()), history.head());
+        assert!(replay_verify(G1Affine::default(), history.commitments.iter().copied(), history.head()));
+        assert!(!replay_verify(G1Affine::default(), history.commitments[1..].iter().copied(), history.head()));
+        assert!(!replay_verify(G1Affine::default(), history.commitments.iter().copied().rev(), history.head()));
+        assert_eq!(replay(history.head(), core::iter::empty()), history.head());
+    }
+
+    #[test]
+    fn test_record_rejects_foreign_state() {
+        let mut history = build(2);
+        let mut rng = thread_rng();
+        let roots = (1..20).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let state = insert(&roots, G1Affine::default(), Fr::rand(&mut rng)).unwrap();
+        assert!(history.record(&state).is_err());
+    }
+}
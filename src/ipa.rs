@@ -0,0 +1,253 @@
+//! Subset openings whose proof size depends only on the vector length.
+//!
+//! An inner-product argument (Bulletproofs' IPA with a public second vector)
+//! over `POINTS`, padded with further setup generators to a power of two. For
+//! indices `S` and claimed values `y`, the verifier draws `x` and the prover
+//! shows the committed `(r, v)` satisfies `Σ x^k v_{S_k} = Σ x^k y_k`. Each
+//! round halves the vector, so a proof is `log2(32) = 5` pairs of points and one
+//! scalar whether it opens one index or all of them.
+//!
+//! The values are the statement and still travel to the verifier. The proof is
+//! not zero-knowledge: its final scalar and round points depend on the unopened
+//! values and the blinding, so use it where `open`, which reveals `r`, would do.
+use anyhow::{anyhow, Result};
+use ark_bls12_377::{Fr, G1Affine, G1Projective};
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, One, Zero};
+
+use crate::curve::{Bls12_377, PcsCurve};
+use crate::error::Error;
+use crate::lazy::Lazy;
+use crate::pcs::{commit, default_vector_len, derive_points, validate_point, DEFAULT_DST, NUM_POINTS, TRUSTED_SETUP};
+use crate::transcript::Transcript;
+
+/// Domain separation tag for `IPA_BASE`, distinct from the setup DST.
+pub const IPA_DST: &[u8] = b"OBLIVIOUS-SYNC-V01_IPA_XMD:SHA-256_SSWU_RO_";
+
+/// `POINTS` followed by the next setup generators up to a power of two; the
+/// padding slots always hold zero.
+static IPA_POINTS: Lazy<Vec<G1Affine>> = Lazy::new(|| {
+    derive_points(&TRUSTED_SETUP, DEFAULT_DST, NUM_POINTS.next_power_of_two()).expect("built-in DST and setup hash to the curve")
+});
+
+/// Base the inner product is committed under, independent of `IPA_POINTS`.
+static IPA_BASE: Lazy<G1Affine> = Lazy::new(|| Bls12_377::hash_to_curve(IPA_DST, b"inner-product").expect("IPA_DST is valid"));
+
+/// Proof that a commitment opens to given values at given indices.
+pub struct SubsetProof {
+    /// `L_i` of each halving round.
+    pub left: Vec<G1Affine>,
+    /// `R_i` of each halving round.
+    pub right: Vec<G1Affine>,
+    /// The committed vector folded down to a single scalar.
+    pub a: Fr,
+}
+
+fn check_statement(indices: &[usize], values: usize) -> crate::error::Result<()> {
+    if indices.len() != values {
+        return Err(Error::ValuesIndicesMismatch);
+    }
+    if indices.is_empty() {
+        return Err(Error::EmptyIndices);
+    }
+    if indices.windows(2).any(|w| w[0] >= w[1]) {
+        return Err(Error::UnsortedIndices);
+    }
+    if indices[indices.len() - 1] >= default_vector_len() {
+        return Err(Error::IndexOutOfBounds);
+    }
+    Ok(())
+}
+
+/// Absorbs the statement and returns the transcript, the public vector `b`,
+/// the inner-product base and the claimed `<(r, v), b>`.
+fn begin(c: &G1Affine, indices: &[usize], values: &[Fr]) -> (Transcript, Vec<Fr>, G1Affine, Fr) {
+    let mut transcript = Transcript::new(b"subset-opening");
+    transcript.append_point(b"C", c);
+    for (&j, y) in indices.iter().zip(values) {
+        transcript.append_u64(b"index", j as u64);
+        transcript.append_scalar(b"value", y);
+    }
+    let x = transcript.challenge_scalar(b"x");
+    let w = transcript.challenge_scalar(b"w");
+
+    // Slot 0 is the blinding, which the inner product skips
+    let mut b = vec![Fr::zero(); IPA_POINTS.len()];
+    let mut claim = Fr::zero();
+    let mut x_k = Fr::one();
+    for (&j, y) in indices.iter().zip(values) {
+        b[j + 1] = x_k;
+        claim += x_k * y;
+        x_k *= x;
+    }
+    (transcript, b, (*IPA_BASE * w).into_affine(), claim)
+}
+
+fn inner_product(a: &[Fr], b: &[Fr]) -> Fr {
+    a.iter().zip(b).map(|(a_i, b_i)| *a_i * b_i).sum()
+}
+
+/// `lo[i] * x_lo + hi[i] * x_hi` over the two halves of `s`.
+fn fold_scalars(s: &[Fr], x_lo: Fr, x_hi: Fr) -> Vec<Fr> {
+    let (lo, hi) = s.split_at(s.len() / 2);
+    lo.iter().zip(hi).map(|(lo, hi)| *lo * x_lo + *hi * x_hi).collect()
+}
+
+fn fold_points(g: &[G1Affine], x_lo: Fr, x_hi: Fr) -> Vec<G1Affine> {
+    let (lo, hi) = g.split_at(g.len() / 2);
+    let folded: Vec<G1Projective> = lo.iter().zip(hi).map(|(lo, hi)| *lo * x_lo + *hi * x_hi).collect();
+    G1Projective::normalize_batch(&folded)
+}
+
+/// Round challenge and its inverse; zero has none.
+fn round_challenge(transcript: &mut Transcript, left: &G1Affine, right: &G1Affine) -> Option<(Fr, Fr)> {
+    transcript.append_point(b"L", left);
+    transcript.append_point(b"R", right);
+    let u = transcript.challenge_scalar(b"u");
+    Some((u, u.inverse()?))
+}
+
+/// Proves that `commit(v, r)` holds `v[j]` at each of `indices`, which must be
+/// strictly ascending.
+pub fn prove_subset(v: &[Fr], r: Fr, indices: &[usize]) -> Result<SubsetProof> {
+    check_statement(indices, indices.len())?;
+    if v.len() != default_vector_len() {
+        return Err(Error::LengthMismatch { expected: default_vector_len(), got: v.len() }.into());
+    }
+    let c = commit(v, r)?;
+    let values: Vec<Fr> = indices.iter().map(|&j| v[j]).collect();
+    let (mut transcript, mut b, u, _) = begin(&c, indices, &values);
+
+    let mut a: Vec<Fr> = core::iter::once(r).chain(v.iter().copied()).collect();
+    a.resize(IPA_POINTS.len(), Fr::zero());
+    let mut g = IPA_POINTS.clone();
+    let mut left = Vec::new();
+    let mut right = Vec::new();
+
+    while a.len() > 1 {
+        let half = a.len() / 2;
+        let (a_lo, a_hi) = a.split_at(half);
+        let (b_lo, b_hi) = b.split_at(half);
+        let (g_lo, g_hi) = g.split_at(half);
+
+        let l = (G1Projective::msm_unchecked(g_hi, a_lo) + u * inner_product(a_lo, b_hi)).into_affine();
+        let r = (G1Projective::msm_unchecked(g_lo, a_hi) + u * inner_product(a_hi, b_lo)).into_affine();
+        let (x, x_inv) = round_challenge(&mut transcript, &l, &r).ok_or_else(|| anyhow!("zero round challenge"))?;
+
+        a = fold_scalars(&a, x, x_inv);
+        b = fold_scalars(&b, x_inv, x);
+        g = fold_points(&g, x_inv, x);
+        left.push(l);
+        right.push(r);
+    }
+
+    Ok(SubsetProof { left, right, a: a[0] })
+}
+
+/// Checks that `c` opens to `values` at `indices` (strictly ascending).
+pub fn verify_subset(c: G1Affine, indices: &[usize], values: &[Fr], proof: &SubsetProof) -> bool {
+    if check_statement(indices, values.len()).is_err() || validate_point(&c).is_err() {
+        return false;
+    }
+    let rounds = IPA_POINTS.len().trailing_zeros() as usize;
+    if proof.left.len() != rounds || proof.right.len() != rounds {
+        return false;
+    }
+    // Round points may be the identity (e.g. for a vector with a zero tail), but
+    // must lie in the subgroup
+    let in_subgroup = |p: &G1Affine| p.is_on_curve() && p.is_in_correct_subgroup_assuming_on_curve();
+    if !proof.left.iter().chain(&proof.right).all(in_subgroup) {
+        return false;
+    }
+
+    let (mut transcript, mut b, u, claim) = begin(&c, indices, values);
+    let mut p = c + u * claim;
+    let mut g = IPA_POINTS.clone();
+
+    for (l, r) in proof.left.iter().zip(&proof.right) {
+        let Some((x, x_inv)) = round_challenge(&mut transcript, l, r) else {
+            return false;
+        };
+        p += *l * x.square() + *r * x_inv.square();
+        b = fold_scalars(&b, x_inv, x);
+        g = fold_points(&g, x_inv, x);
+    }
+
+    p == g[0] * proof.a + u * (proof.a * b[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcs::{batch_open, POINTS};
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    fn random_vector() -> Vec<Fr> {
+        let mut rng = thread_rng();
+        (0..POINTS.len() - 1).map(|_| Fr::rand(&mut rng)).collect()
+    }
+
+    #[test]
+    fn test_ipa_points_extend_points() {
+        assert_eq!(IPA_POINTS.len(), 32);
+        assert_eq!(&IPA_POINTS[..NUM_POINTS], &POINTS[..]);
+    }
+
+    #[test]
+    fn test_subset_proof() {
+        let mut rng = thread_rng();
+        let v = random_vector();
+        let r = Fr::rand(&mut rng);
+        let c = commit(&v, r).unwrap();
+
+        for indices in [vec![7], (0..16).step_by(2).collect(), (0..19).collect::<Vec<usize>>()] {
+            let values: Vec<Fr> = indices.iter().map(|&j| v[j]).collect();
+            let proof = prove_subset(&v, r, &indices).unwrap();
+            assert_eq!(proof.left.len(), 5);
+            assert!(verify_subset(c, &indices, &values, &proof));
+
+            let mut wrong = values.clone();
+            wrong[indices.len() / 2] += Fr::one();
+            assert!(!verify_subset(c, &indices, &wrong, &proof));
+
+            let other = commit(&v, r + Fr::one()).unwrap();
+            assert!(!verify_subset(other, &indices, &values, &proof));
+        }
+    }
+
+    #[test]
+    fn test_subset_proof_matches_batch_open() {
+        let mut rng = thread_rng();
+        let v = random_vector();
+        let r = Fr::rand(&mut rng);
+        let c = commit(&v, r).unwrap();
+
+        let indices = [1, 4, 9, 19];
+        let values = batch_open(&v, r, indices).unwrap().values;
+        let proof = prove_subset(&v, r, &indices).unwrap();
+        assert!(verify_subset(c, &indices, &values, &proof));
+        // The same values claimed at other indices
+        assert!(!verify_subset(c, &[1, 4, 9, 18], &values, &proof));
+    }
+
+    #[test]
+    fn test_subset_proof_rejects_bad_input() {
+        let mut rng = thread_rng();
+        let v = random_vector();
+        let r = Fr::rand(&mut rng);
+        let c = commit(&v, r).unwrap();
+
+        assert!(prove_subset(&v, r, &[]).is_err());
+        assert!(prove_subset(&v, r, &[3, 2]).is_err());
+        assert!(prove_subset(&v, r, &[v.len()]).is_err());
+
+        let mut proof = prove_subset(&v, r, &[2, 3]).unwrap();
+        proof.a += Fr::one();
+        assert!(!verify_subset(c, &[2, 3], &[v[2], v[3]], &proof));
+        proof.a -= Fr::one();
+        proof.left.pop();
+        assert!(!verify_subset(c, &[2, 3], &[v[2], v[3]], &proof));
+        assert!(!verify_subset(c, &[2, 3], &[v[2]], &proof));
+    }
+}
@@ -0,0 +1,462 @@
+//! An explicit generator set, for callers that don't want the global `POINTS`.
+use ark_bls12_377::{Fr, G1Affine};
+use ark_ec::AffineRepr;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate, Write};
+use ark_std::vec::Vec;
+use sha2::{Digest, Sha256};
+
+use crate::curve::{Bls12_377, PcsCurve};
+use crate::error::{Error, Result};
+use crate::pcs::{
+    batch_check_with_points, batch_open_with_points, check_unchecked, commit_with_points, derive_points, open_all_with_points,
+    open_with_points,
+    BLINDING_INDEX, DEFAULT_DST, NUM_POINTS, POINTS, TRUSTED_SETUP,
+};
+
+/// Carries a commitment made under a key with `old_len` value slots over to a
+/// larger key with `new_len` slots that extends it (see `CommitmentKey::is_extension_of`).
+///
+/// The larger key commits to the vector zero-padded to `new_len`, and the added
+/// terms are `[0]G_i`, so the commitment is unchanged and returned as is.
+pub fn extend_commitment(c: G1Affine, old_len: usize, new_len: usize) -> Result<G1Affine> {
+    if new_len < old_len {
+        return Err(Error::LengthMismatch { expected: old_len, got: new_len });
+    }
+    Ok(c)
+}
+
+/// Generators for the commitment scheme; `points[0]` is the blinding base.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitmentKey {
+    points: Vec<G1Affine>,
+}
+
+impl Default for CommitmentKey {
+    /// The key derived from the embedded trusted setup, i.e. `POINTS`.
+    fn default() -> Self {
+        CommitmentKey { points: POINTS.to_vec() }
+    }
+}
+
+/// Domain separation prefix of `CommitmentKey::id`.
+const KEY_ID_DST: &[u8] = b"OBLIVIOUS-SYNC-V01_KEY_ID";
+
+/// Suggested DST for `CommitmentKey::with_blinding_dst`.
+pub const BLINDING_DST: &[u8] = b"OBLIVIOUS-SYNC-V01_BLINDING_XMD:SHA-256_SSWU_RO_";
+
+/// DST for `CommitmentKey::derive`, so label-derived generators never coincide
+/// with setup-derived ones.
+pub const LABEL_KEY_DST: &[u8] = b"OBLIVIOUS-SYNC-V01_LABEL_KEY_XMD:SHA-256_SSWU_RO_";
+
+/// Identifies a generator set; see `CommitmentKey::id`.
+pub type KeyId = [u8; 32];
+
+/// Rejects generator sets containing the identity or the same point twice;
+/// either would let a committer open some slot to two different values.
+fn check_generators(points: &[G1Affine]) -> Result<()> {
+    if points.iter().any(|p| p.is_zero()) {
+        return Err(Error::IdentityPoint);
+    }
+    let mut encodings: Vec<[u8; 48]> = points
+        .iter()
+        .map(|p| {
+            let mut buf = [0u8; 48];
+            p.serialize_compressed(&mut buf[..]).unwrap();
+            buf
+        })
+        .collect();
+    encodings.sort_unstable();
+    if encodings.windows(2).any(|w| w[0] == w[1]) {
+        return Err(Error::DuplicateGenerator);
+    }
+    Ok(())
+}
+
+impl CommitmentKey {
+    /// A key over `points`, checked to be distinct and non-identity.
+    pub fn new(points: Vec<G1Affine>) -> Result<Self> {
+        check_generators(&points)?;
+        Ok(CommitmentKey { points })
+    }
+
+    /// `new` without the generator checks, for points known to be well formed.
+    pub fn from_points(points: Vec<G1Affine>) -> Self {
+        CommitmentKey { points }
+    }
+
+    /// A key with `capacity` value slots owned by one application: generator
+    /// `i` hashes `label || i` (`i` as u64 LE) to the curve under
+    /// `LABEL_KEY_DST`, blinding base first.
+    ///
+    /// Applications with different labels get unrelated generators, so a
+    /// commitment made under one key means nothing under another's. The
+    /// index is fixed-width, so distinct labels never produce the same message.
+    pub fn derive(label: &[u8], capacity: usize) -> Result<Self> {
+        let points = (0..=capacity as u64)
+            .map(|i| {
+                let mut msg = label.to_vec();
+                msg.extend_from_slice(&i.to_le_bytes());
+                Bls12_377::hash_to_curve(LABEL_KEY_DST, &msg)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Self::new(points)
+    }
+
+    /// Derives a key from trusted-setup lines under `DEFAULT_DST`. Only the
+    /// first `NUM_POINTS` lines are used.
+    pub fn from_setup_lines(lines: &[&str]) -> Result<Self> {
+        if lines.len() < NUM_POINTS {
+            return Err(Error::SetupTooShort { expected: NUM_POINTS, got: lines.len() });
+        }
+        Self::new(derive_points(lines, DEFAULT_DST, NUM_POINTS)?)
+    }
+
+    /// Loads a ceremony output at runtime instead of the setup embedded at build
+    /// time; `CommitmentKey::default()` keeps using the embedded one.
+    #[cfg(feature = "std")]
+    pub fn from_setup_file(path: impl AsRef<std::path::Path>) -> anyhow::Result<Self> {
+        use anyhow::Context;
+
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        let lines: Vec<&str> = text.lines().collect();
+        Ok(Self::from_setup_lines(&lines)?)
+    }
+
+    /// A key with `len` value slots from the embedded setup. Generators depend
+    /// only on their own setup line, so keys of different lengths share a prefix.
+    pub fn with_len(len: usize) -> Result<Self> {
+        if len + 1 > TRUSTED_SETUP.len() {
+            return Err(Error::SetupTooShort { expected: len + 1, got: TRUSTED_SETUP.len() });
+        }
+        Self::new(derive_points(&TRUSTED_SETUP, DEFAULT_DST, len + 1)?)
+    }
+
+    /// This key with its blinding base replaced by a point hashed under `dst`.
+    ///
+    /// By default the blinding base is the generator of setup line 0, derived
+    /// exactly like the value generators. Keys cut from other slices of the
+    /// setup can then use that point as a value generator, and a protocol that
+    /// must argue the blinding base is independent of every value base has to
+    /// argue it line by line. Hashing it under its own DST makes the separation
+    /// hold by construction. Commitments under the resulting key differ from
+    /// the default key's, so both sides must agree on `dst` (it changes `id`).
+    pub fn with_blinding_dst(mut self, dst: &[u8]) -> Result<Self> {
+        if self.points.is_empty() {
+            return Err(Error::SetupTooShort { expected: 1, got: 0 });
+        }
+        self.points[BLINDING_INDEX] = Bls12_377::hash_to_curve(dst, b"blinding")?;
+        check_generators(&self.points)?;
+        Ok(self)
+    }
+
+    /// SHA-256 over `KEY_ID_DST`, the generator count (u64 LE) and each
+    /// compressed generator. Keys agree on their id exactly when they have the
+    /// same generators in the same order.
+    pub fn id(&self) -> KeyId {
+        let mut hasher = Sha256::new();
+        hasher.update(KEY_ID_DST);
+        hasher.update((self.points.len() as u64).to_le_bytes());
+        for p in &self.points {
+            let mut buf = [0u8; 48];
+            p.serialize_compressed(&mut buf[..]).unwrap();
+            hasher.update(buf);
+        }
+        hasher.finalize().into()
+    }
+
+    pub fn points(&self) -> &[G1Affine] {
+        &self.points
+    }
+
+    /// Length of the vectors this key commits to: its generators minus the
+    /// blinding base. `default_vector_len()` for the default key.
+    pub fn vector_len(&self) -> usize {
+        self.points.len().saturating_sub(1)
+    }
+
+    /// Whether `smaller`'s generators are a prefix of this key's.
+    pub fn is_extension_of(&self, smaller: &CommitmentKey) -> bool {
+        self.points.starts_with(&smaller.points)
+    }
+
+    /// Checks an opening made under `smaller` (at `j < smaller.vector_len()`) with this
+    /// larger key, after carrying `c` over with `extend_commitment`.
+    pub fn check_extended(&self, smaller: &CommitmentKey, c: G1Affine, v_j: Fr, witness: G1Affine, j: usize) -> bool {
+        if !self.is_extension_of(smaller) || j >= smaller.vector_len() {
+            return false;
+        }
+        match extend_commitment(c, smaller.vector_len(), self.vector_len()) {
+            Ok(c) => self.check(c, v_j, witness, j),
+            Err(_) => false,
+        }
+    }
+
+    pub fn commit(&self, v: &[Fr], r: Fr) -> Result<G1Affine> {
+        commit_with_points::<Bls12_377>(&self.points, v, r)
+    }
+
+    pub fn open(&self, v: &[Fr], r: Fr, j: usize) -> Result<(Fr, Fr, G1Affine)> {
+        open_with_points::<Bls12_377>(&self.points, v, r, j)
+    }
+
+    pub fn open_all(&self, v: &[Fr], r: Fr) -> Result<Vec<(Fr, G1Affine)>> {
+        open_all_with_points::<Bls12_377>(&self.points, v, r)
+    }
+
+    /// `pcs::check_unchecked` against this key's generator for index `j`.
+    pub fn check(&self, c: G1Affine, v_j: Fr, witness: G1Affine, j: usize) -> bool {
+        match self.points.get(j + 1) {
+            Some(h_j) => check_unchecked(c, v_j, witness, *h_j),
+            None => false,
+        }
+    }
+
+    pub fn batch_open(&self, v: &[Fr], r: Fr, indices: impl IntoIterator<Item = usize>) -> Result<(Vec<Fr>, Fr, G1Affine)> {
+        batch_open_with_points::<Bls12_377>(&self.points, v, r, indices)
+    }
+
+    pub fn batch_check(&self, c: G1Affine, values: &[Fr], witness: G1Affine, indices: &[usize]) -> Result<bool> {
+        batch_check_with_points::<Bls12_377>(&self.points, c, values, witness, indices)
+    }
+}
+
+/// Encodes the generators as a canonical `Vec<G1Affine>`.
+impl CanonicalSerialize for CommitmentKey {
+    fn serialize_with_mode<W: Write>(&self, writer: W, compress: Compress) -> core::result::Result<(), SerializationError> {
+        self.points.serialize_with_mode(writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.points.serialized_size(compress)
+    }
+}
+
+impl Valid for CommitmentKey {
+    fn check(&self) -> core::result::Result<(), SerializationError> {
+        self.points.check()?;
+        check_generators(&self.points).map_err(|_| SerializationError::InvalidData)
+    }
+}
+
+/// With `Validate::Yes`, rejects generator sets `CommitmentKey::new` would.
+impl CanonicalDeserialize for CommitmentKey {
+    fn deserialize_with_mode<R: Read>(reader: R, compress: Compress, validate: Validate) -> core::result::Result<Self, SerializationError> {
+        let key = CommitmentKey { points: Vec::deserialize_with_mode(reader, compress, validate)? };
+        if validate == Validate::Yes {
+            check_generators(&key.points).map_err(|_| SerializationError::InvalidData)?;
+        }
+        Ok(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcs::{commit, default_vector_len};
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_default_key_matches_points() {
+        let mut rng = thread_rng();
+        let key = CommitmentKey::default();
+        let v: Vec<Fr> = (0..NUM_POINTS - 1).map(|_| Fr::rand(&mut rng)).collect();
+        let r = Fr::rand(&mut rng);
+
+        let c = key.commit(&v, r).unwrap();
+        assert_eq!(c, commit(&v, r).unwrap());
+        let (v_j, _, witness) = key.open(&v, r, 6).unwrap();
+        assert!(key.check(c, v_j, witness, 6));
+        assert!(!key.check(c, v_j, witness, 7));
+        assert!(!key.check(c, v_j, witness, NUM_POINTS));
+
+        let (values, _, witness) = key.batch_open(&v, r, [2, 4]).unwrap();
+        assert!(key.batch_check(c, &values, witness, &[2, 4]).unwrap());
+    }
+
+    #[test]
+    fn test_vector_len() {
+        assert_eq!(CommitmentKey::default().vector_len(), default_vector_len());
+        assert_eq!(CommitmentKey::with_len(7).unwrap().vector_len(), 7);
+
+        let mut rng = thread_rng();
+        let key = CommitmentKey::with_len(7).unwrap();
+        let v: Vec<Fr> = (0..7).map(|_| Fr::rand(&mut rng)).collect();
+        let r = Fr::rand(&mut rng);
+        let c = key.commit(&v, r).unwrap();
+        let (values, _, witness) = key.batch_open(&v, r, [1, 6]).unwrap();
+        assert!(key.batch_check(c, &values, witness, &[1, 6]).unwrap());
+        assert_eq!(key.batch_check(c, &values, witness, &[1, 7]), Err(Error::IndexOutOfBounds));
+    }
+
+    #[test]
+    fn test_from_setup_file() {
+        let dir = std::env::temp_dir().join(format!("oss-setup-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("setup.txt");
+        let lines: Vec<String> = (0..NUM_POINTS).map(|i| format!("custom ceremony line {i}")).collect();
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        let key = CommitmentKey::from_setup_file(&path).unwrap();
+        assert_eq!(key.points().len(), NUM_POINTS);
+        for (custom, default) in key.points().iter().zip(POINTS.iter()) {
+            assert_ne!(custom, default);
+        }
+
+        let short = dir.join("short.txt");
+        std::fs::write(&short, lines[..NUM_POINTS - 1].join("\n")).unwrap();
+        let err = CommitmentKey::from_setup_file(&short).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<Error>(),
+            Some(&Error::SetupTooShort { expected: NUM_POINTS, got: NUM_POINTS - 1 })
+        );
+        assert!(CommitmentKey::from_setup_file(dir.join("missing.txt")).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_embedded_setup_lines() {
+        assert_eq!(CommitmentKey::from_setup_lines(&TRUSTED_SETUP).unwrap(), CommitmentKey::default());
+    }
+
+    #[test]
+    fn test_independent_blinding_base() {
+        let mut rng = thread_rng();
+        let key = CommitmentKey::default().with_blinding_dst(BLINDING_DST).unwrap();
+        let base = key.points()[BLINDING_INDEX];
+        assert_ne!(base, POINTS[BLINDING_INDEX]);
+        assert!(!key.points()[1..].contains(&base));
+        // Nor any generator a longer key would use
+        assert!(!derive_points(&TRUSTED_SETUP, DEFAULT_DST, 64).unwrap().contains(&base));
+        assert_eq!(&key.points()[1..], &POINTS[1..]);
+        assert_ne!(key.id(), CommitmentKey::default().id());
+
+        let v: Vec<Fr> = (0..key.vector_len()).map(|_| Fr::rand(&mut rng)).collect();
+        let r = Fr::rand(&mut rng);
+        let c = key.commit(&v, r).unwrap();
+        assert_ne!(c, commit(&v, r).unwrap());
+        let (v_j, _, witness) = key.open(&v, r, 8).unwrap();
+        assert!(key.check(c, v_j, witness, 8));
+        let (values, _, witness) = key.batch_open(&v, r, [0, 8, 19]).unwrap();
+        assert!(key.batch_check(c, &values, witness, &[0, 8, 19]).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_blinding_dst() {
+        assert_eq!(CommitmentKey::default().with_blinding_dst(b""), Err(Error::GeneratorDerivationFailed));
+    }
+
+    #[test]
+    fn test_key_id() {
+        let default = CommitmentKey::default();
+        assert_eq!(default.id(), CommitmentKey::from_setup_lines(&TRUSTED_SETUP).unwrap().id());
+        assert_ne!(default.id(), CommitmentKey::with_len(19).unwrap().id());
+        let custom = CommitmentKey::from_points(derive_points(&TRUSTED_SETUP, b"CUSTOM-KEY", NUM_POINTS).unwrap());
+        assert_ne!(default.id(), custom.id());
+
+        let mut swapped = POINTS.to_vec();
+        swapped.swap(1, 2);
+        assert_ne!(default.id(), CommitmentKey::from_points(swapped).id());
+    }
+
+    #[test]
+    fn test_generators_distinct() {
+        assert!(check_generators(&POINTS[..]).is_ok());
+
+        let mut lines: Vec<&str> = TRUSTED_SETUP[..NUM_POINTS].to_vec();
+        lines[5] = lines[3];
+        assert_eq!(CommitmentKey::from_setup_lines(&lines), Err(Error::DuplicateGenerator));
+
+        let mut points = POINTS.to_vec();
+        points[2] = G1Affine::zero();
+        assert_eq!(CommitmentKey::new(points), Err(Error::IdentityPoint));
+        assert_eq!(CommitmentKey::new(POINTS.to_vec()), Ok(CommitmentKey::default()));
+    }
+
+    #[test]
+    fn test_extend_commitment() {
+        let mut rng = thread_rng();
+        let small = CommitmentKey::with_len(10).unwrap();
+        let large = CommitmentKey::with_len(19).unwrap();
+        assert_eq!(small.vector_len(), 10);
+        assert!(large.is_extension_of(&small));
+        assert!(!small.is_extension_of(&large));
+        assert!(CommitmentKey::default().is_extension_of(&large));
+
+        let v: Vec<Fr> = (0..10).map(|_| Fr::rand(&mut rng)).collect();
+        let r = Fr::rand(&mut rng);
+        let c = small.commit(&v, r).unwrap();
+
+        // Zero-padding under the larger key gives the same point
+        let mut padded = v.clone();
+        padded.resize(19, Fr::from(0u64));
+        let extended = extend_commitment(c, 10, 19).unwrap();
+        assert_eq!(extended, large.commit(&padded, r).unwrap());
+        assert!(extend_commitment(c, 19, 10).is_err());
+
+        let (v_j, _, witness) = small.open(&v, r, 4).unwrap();
+        assert!(large.check_extended(&small, c, v_j, witness, 4));
+        assert!(!large.check_extended(&small, c, v_j, witness, 5));
+        assert!(!large.check_extended(&small, c, v_j, witness, 12));
+        let custom = CommitmentKey::from_points(derive_points(&TRUSTED_SETUP, b"CUSTOM-KEY", 20).unwrap());
+        assert!(!custom.check_extended(&small, c, v_j, witness, 4));
+
+        assert!(CommitmentKey::with_len(TRUSTED_SETUP.len()).is_err());
+    }
+
+    #[test]
+    fn test_custom_points() {
+        let mut rng = thread_rng();
+        let key = CommitmentKey::from_points(derive_points(&TRUSTED_SETUP, b"CUSTOM-KEY", NUM_POINTS).unwrap());
+        let v: Vec<Fr> = (0..NUM_POINTS - 1).map(|_| Fr::rand(&mut rng)).collect();
+        let r = Fr::rand(&mut rng);
+
+        let c = key.commit(&v, r).unwrap();
+        assert_ne!(c, commit(&v, r).unwrap());
+        let (v_j, _, witness) = key.open(&v, r, 0).unwrap();
+        assert!(key.check(c, v_j, witness, 0));
+        assert!(!CommitmentKey::default().check(c, v_j, witness, 0));
+    }
+
+    #[test]
+    fn test_derive_from_label() {
+        let mut rng = thread_rng();
+        let a = CommitmentKey::derive(b"app-a", 12).unwrap();
+        let b = CommitmentKey::derive(b"app-b", 12).unwrap();
+        assert_eq!(a.vector_len(), 12);
+        assert_eq!(a, CommitmentKey::derive(b"app-a", 12).unwrap());
+        assert!(CommitmentKey::derive(b"app-a", 20).unwrap().is_extension_of(&a));
+        assert!(!a.points().iter().any(|p| POINTS.contains(p)));
+
+        let v: Vec<Fr> = (0..12).map(|_| Fr::rand(&mut rng)).collect();
+        let r = Fr::rand(&mut rng);
+        let c = a.commit(&v, r).unwrap();
+        assert_ne!(c, b.commit(&v, r).unwrap());
+        assert_ne!(c, commit(&v, r).unwrap());
+
+        let (v_j, _, witness) = a.open(&v, r, 3).unwrap();
+        assert!(a.check(c, v_j, witness, 3));
+        assert!(!b.check(c, v_j, witness, 3));
+        let (values, _, witness) = a.batch_open(&v, r, [1, 11]).unwrap();
+        assert!(a.batch_check(c, &values, witness, &[1, 11]).unwrap());
+    }
+
+    #[test]
+    fn test_key_serialization() {
+        let key = CommitmentKey::derive(b"app-a", 5).unwrap();
+        let mut bytes = Vec::new();
+        key.serialize_compressed(&mut bytes).unwrap();
+        let decoded = CommitmentKey::deserialize_compressed(&bytes[..]).unwrap();
+        assert_eq!(decoded, key);
+        assert_eq!(decoded.id(), key.id());
+
+        // A repeated generator is rejected on the checked path only
+        let duplicated = CommitmentKey::from_points(vec![POINTS[1], POINTS[2], POINTS[1]]);
+        let mut bytes = Vec::new();
+        duplicated.serialize_compressed(&mut bytes).unwrap();
+        assert!(CommitmentKey::deserialize_compressed(&bytes[..]).is_err());
+        assert_eq!(CommitmentKey::deserialize_compressed_unchecked(&bytes[..]).unwrap(), duplicated);
+    }
+}
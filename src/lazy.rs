@@ -0,0 +1,45 @@
+//! One-time initialization that works with and without `std`.
+use core::ops::Deref;
+
+#[cfg(feature = "std")]
+pub(crate) use std::sync::OnceLock as OnceCell;
+
+/// `no_std` stand-in for `OnceLock`. Concurrent first accesses may each run
+/// the initializer, but only one result is stored and all callers observe it.
+#[cfg(not(feature = "std"))]
+pub(crate) struct OnceCell<T>(once_cell::race::OnceBox<T>);
+
+#[cfg(not(feature = "std"))]
+impl<T> OnceCell<T> {
+    pub(crate) const fn new() -> Self {
+        Self(once_cell::race::OnceBox::new())
+    }
+
+    pub(crate) fn get(&self) -> Option<&T> {
+        self.0.get()
+    }
+
+    pub(crate) fn get_or_init(&self, f: impl FnOnce() -> T) -> &T {
+        self.0.get_or_init(|| ark_std::boxed::Box::new(f()))
+    }
+}
+
+/// A value computed on first access.
+pub struct Lazy<T> {
+    cell: OnceCell<T>,
+    init: fn() -> T,
+}
+
+impl<T> Lazy<T> {
+    pub(crate) const fn new(init: fn() -> T) -> Self {
+        Self { cell: OnceCell::new(), init }
+    }
+}
+
+impl<T> Deref for Lazy<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.cell.get_or_init(self.init)
+    }
+}
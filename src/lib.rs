@@ -1,2 +1,34 @@
-pub(crate) mod pcs;
-pub mod accumulator;
\ No newline at end of file
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub mod error;
+pub(crate) mod lazy;
+pub mod matrix;
+pub mod opening;
+pub mod pcs;
+#[cfg(feature = "std")]
+pub mod permutation;
+#[cfg(feature = "std")]
+pub mod accumulator;
+pub mod curve;
+pub mod diff;
+pub mod key;
+#[cfg(feature = "std")]
+pub mod history;
+#[cfg(feature = "std")]
+pub mod ipa;
+pub mod precompute;
+pub mod secret;
+pub mod stream;
+pub mod wire;
+#[cfg(feature = "std")]
+pub mod sigma;
+#[cfg(feature = "std")]
+pub mod test_vectors;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "std")]
+pub mod transcript;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "server")]
+pub mod server;
@@ -0,0 +1,126 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use anyhow::{anyhow, Context, Result};
+use ark_bls12_377::{Fr, G1Affine};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use clap::{Parser, Subcommand};
+use serde::Deserialize;
+
+use oblivious_syncing_service::pcs::{check, commit, deserialize_checked, open, scalar_from_str, Opening};
+
+#[derive(Parser)]
+#[command(about = "Commit to, open and verify Pedersen vector commitments")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Commit to the values in a JSON file
+    Commit {
+        #[arg(long)]
+        input: PathBuf,
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Produce an opening proof for a single index
+    Open {
+        #[arg(long)]
+        input: PathBuf,
+        #[arg(long)]
+        index: usize,
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Check an opening proof against a commitment
+    Verify {
+        #[arg(long)]
+        commitment: PathBuf,
+        #[arg(long)]
+        proof: PathBuf,
+    },
+}
+
+/// `{"values": ["1", "2", ...], "blinding": "42"}` with decimal or `0x` hex field elements.
+#[derive(Deserialize)]
+struct Input {
+    values: Vec<String>,
+    blinding: String,
+}
+
+/// An opening of one index, serialized as `index || value || witness`.
+struct Proof {
+    index: u64,
+    value: Fr,
+    witness: G1Affine,
+}
+
+fn parse_scalar(s: &str) -> Result<Fr> {
+    scalar_from_str(s).map_err(|_| anyhow!("invalid field element: {s}"))
+}
+
+fn read_input(path: &Path) -> Result<(Vec<Fr>, Fr)> {
+    let text = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    let input: Input = serde_json::from_str(&text)?;
+    let values = input.values.iter().map(|s| parse_scalar(s)).collect::<Result<Vec<_>>>()?;
+    Ok((values, parse_scalar(&input.blinding)?))
+}
+
+fn write_proof(path: &Path, proof: &Proof) -> Result<()> {
+    let mut buf = Vec::new();
+    proof.index.serialize_compressed(&mut buf)?;
+    proof.value.serialize_compressed(&mut buf)?;
+    proof.witness.serialize_compressed(&mut buf)?;
+    fs::write(path, buf)?;
+    Ok(())
+}
+
+fn read_proof(path: &Path) -> Result<Proof> {
+    let bytes = fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+    let mut reader = &bytes[..];
+    Ok(Proof {
+        index: u64::deserialize_compressed(&mut reader)?,
+        value: Fr::deserialize_compressed(&mut reader)?,
+        witness: deserialize_checked(reader)?,
+    })
+}
+
+fn run(cli: Cli) -> Result<bool> {
+    match cli.command {
+        Command::Commit { input, out } => {
+            let (values, r) = read_input(&input)?;
+            let c = commit(&values, r)?;
+            let mut buf = Vec::new();
+            c.serialize_compressed(&mut buf)?;
+            fs::write(out, buf)?;
+        }
+        Command::Open { input, index, out } => {
+            let (values, r) = read_input(&input)?;
+            let Opening { value, witness, .. } = open(&values, r, index)?;
+            write_proof(&out, &Proof { index: index as u64, value, witness })?;
+        }
+        Command::Verify { commitment, proof } => {
+            let bytes = fs::read(&commitment).with_context(|| format!("reading {}", commitment.display()))?;
+            let c = deserialize_checked(&bytes)?;
+            let proof = read_proof(&proof)?;
+            let valid = check(c, proof.index as usize, proof.value, proof.witness).map_err(|_| anyhow!("Index out of bounds"))?;
+            println!("{}", if valid { "valid" } else { "invalid" });
+            return Ok(valid);
+        }
+    }
+    Ok(true)
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("error: {e:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
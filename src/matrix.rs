@@ -0,0 +1,216 @@
+//! Commitments to a table of values, binding every row under one root.
+//!
+//! The rows are committed with `pcs::commit_matrix` as `c_i`, and the root
+//! commits to `(row_hash(c_0), row_hash(c_1), ...)`. A row opening is `c_i`
+//! plus an `Opening` of the root at `i` to `row_hash(c_i)`; a cell opening adds
+//! the `pcs::open_cell` opening of `c_i` at the column. Binding of the root and collision
+//! resistance of `row_hash` tie each row commitment to its position.
+use ark_bls12_377::{Fr, G1Affine};
+use ark_ff::field_hashers::{DefaultFieldHasher, HashToField};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::{CryptoRng, RngCore};
+use ark_std::vec::Vec;
+use ark_std::UniformRand;
+use sha2::Sha256;
+
+use crate::error::{self, Error};
+use crate::pcs::{commit, commit_matrix, open, open_cell, CellOpening, Opening};
+
+/// Domain separation tag for `row_hash`.
+pub const ROW_HASH_DST: &[u8] = b"OBLIVIOUS-SYNC-V01_MATRIX_ROW_XMD:SHA-256_";
+
+/// Maps a row commitment to the scalar the root commits to: RFC 9380
+/// hash-to-field over SHA-256 under `ROW_HASH_DST` of its compressed encoding.
+pub fn row_hash(c: &G1Affine) -> Fr {
+    let mut bytes = Vec::with_capacity(c.compressed_size());
+    c.serialize_compressed(&mut bytes).unwrap();
+    let [x] = <DefaultFieldHasher<Sha256> as HashToField<Fr>>::new(ROW_HASH_DST).hash_to_field::<1>(&bytes);
+    x
+}
+
+/// A committed table, kept by the committer to answer openings. Rows share one
+/// length, as in `commit_matrix`; it must fit `max_vector_len()`, as must the
+/// row count.
+pub struct MatrixCommitment {
+    rows: Vec<Vec<Fr>>,
+    row_blindings: Vec<Fr>,
+    row_commitments: Vec<G1Affine>,
+    row_hashes: Vec<Fr>,
+    root_blinding: Fr,
+    root: G1Affine,
+}
+
+impl MatrixCommitment {
+    /// Commits to `rows`, row `i` under `row_blindings[i]` and the root under
+    /// `root_blinding`.
+    pub fn new(rows: Vec<Vec<Fr>>, row_blindings: Vec<Fr>, root_blinding: Fr) -> error::Result<Self> {
+        let row_commitments = commit_matrix(&rows, &row_blindings)?;
+        let row_hashes: Vec<Fr> = row_commitments.iter().map(row_hash).collect();
+        let root = commit(&row_hashes, root_blinding)?;
+        Ok(MatrixCommitment { rows, row_blindings, row_commitments, row_hashes, root_blinding, root })
+    }
+
+    /// `new` with every blinding drawn from `rng`.
+    pub fn with_rng<R: RngCore + CryptoRng>(rows: Vec<Vec<Fr>>, rng: &mut R) -> error::Result<Self> {
+        let row_blindings = (0..rows.len()).map(|_| Fr::rand(rng)).collect();
+        Self::new(rows, row_blindings, Fr::rand(rng))
+    }
+
+    pub fn root(&self) -> G1Affine {
+        self.root
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn row_commitments(&self) -> &[G1Affine] {
+        &self.row_commitments
+    }
+
+    /// Row `i`'s commitment and its inclusion in the root.
+    pub fn open_row(&self, i: usize) -> error::Result<RowOpening> {
+        let commitment = *self.row_commitments.get(i).ok_or(Error::IndexOutOfBounds)?;
+        let inclusion = open(&self.row_hashes, self.root_blinding, i)?;
+        Ok(RowOpening { commitment, inclusion })
+    }
+
+    /// Cell `(i, j)`: `open_row(i)` chained with `open_cell` of the row at `j`.
+    pub fn open_cell(&self, i: usize, j: usize) -> error::Result<MatrixCellOpening> {
+        let row = self.open_row(i)?;
+        let cell = open_cell(&self.rows, &self.row_blindings, i, j)?;
+        Ok(MatrixCellOpening { row, cell })
+    }
+}
+
+/// A row commitment with an `Opening` of the root, at the row's index, to its
+/// `row_hash`.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct RowOpening {
+    pub commitment: G1Affine,
+    pub inclusion: Opening,
+}
+
+impl RowOpening {
+    /// Row index this opening is for.
+    pub fn row(&self) -> usize {
+        self.inclusion.index
+    }
+
+    /// Checks that `commitment` is the row committed at `row()` under `root`.
+    pub fn verify(&self, root: &G1Affine) -> error::Result<bool> {
+        if self.inclusion.value != row_hash(&self.commitment) {
+            return Ok(false);
+        }
+        self.inclusion.verify(root)
+    }
+}
+
+/// A `RowOpening` and a `CellOpening` of that row's commitment.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct MatrixCellOpening {
+    pub row: RowOpening,
+    pub cell: CellOpening,
+}
+
+impl MatrixCellOpening {
+    /// The cell's `(row, column)`.
+    pub fn position(&self) -> (usize, usize) {
+        (self.cell.row, self.cell.col)
+    }
+
+    pub fn value(&self) -> Fr {
+        self.cell.value
+    }
+
+    /// Checks the whole chain: the row is included in `root` at the cell's
+    /// row and the cell opens from it.
+    pub fn verify(&self, root: &G1Affine) -> error::Result<bool> {
+        if self.row.row() != self.cell.row {
+            return Ok(false);
+        }
+        Ok(self.row.verify(root)? && self.cell.verify(&self.row.commitment)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcs::batch_check_matrix;
+    use rand::thread_rng;
+
+    fn table() -> (Vec<Vec<Fr>>, MatrixCommitment) {
+        let mut rng = thread_rng();
+        let rows: Vec<Vec<Fr>> = (0..10).map(|_| (0..19).map(|_| Fr::rand(&mut rng)).collect()).collect();
+        let matrix = MatrixCommitment::with_rng(rows.clone(), &mut rng).unwrap();
+        (rows, matrix)
+    }
+
+    #[test]
+    fn test_row_and_cell_openings() {
+        let (rows, matrix) = table();
+        let root = matrix.root();
+        assert_eq!(matrix.num_rows(), 10);
+
+        for i in [0, 4, 9] {
+            let row = matrix.open_row(i).unwrap();
+            assert_eq!(row.row(), i);
+            assert!(row.verify(&root).unwrap());
+        }
+        for (i, j) in [(0, 0), (3, 7), (9, 18)] {
+            let cell = matrix.open_cell(i, j).unwrap();
+            assert_eq!(cell.position(), (i, j));
+            assert_eq!(cell.value(), rows[i][j]);
+            assert!(cell.verify(&root).unwrap());
+        }
+
+        let cell = matrix.open_cell(3, 7).unwrap();
+        let mut bytes = Vec::new();
+        cell.serialize_compressed(&mut bytes).unwrap();
+        assert!(MatrixCellOpening::deserialize_compressed(&bytes[..]).unwrap().verify(&root).unwrap());
+        // The cell part is a plain `commit_matrix` cell opening
+        assert!(batch_check_matrix(matrix.row_commitments(), &[cell.cell]).unwrap());
+
+        assert_eq!(matrix.open_row(10), Err(Error::IndexOutOfBounds));
+        assert_eq!(matrix.open_cell(0, 19), Err(Error::IndexOutOfBounds));
+    }
+
+    #[test]
+    fn test_tampered_cell() {
+        let (_, matrix) = table();
+        let root = matrix.root();
+        let mut cell = matrix.open_cell(2, 5).unwrap();
+        cell.cell.value += Fr::from(1u64);
+        assert!(!cell.verify(&root).unwrap());
+
+        // A genuine cell of another row, relabelled
+        let mut moved = matrix.open_cell(2, 5).unwrap();
+        moved.cell = matrix.open_cell(3, 5).unwrap().cell;
+        assert!(!moved.verify(&root).unwrap());
+        moved.cell.row = 2;
+        assert!(!moved.verify(&root).unwrap());
+    }
+
+    #[test]
+    fn test_tampered_row_commitment() {
+        let (_, matrix) = table();
+        let root = matrix.root();
+
+        // Another row's commitment under this row's inclusion proof
+        let mut row = matrix.open_row(1).unwrap();
+        row.commitment = matrix.row_commitments()[2];
+        assert!(!row.verify(&root).unwrap());
+
+        // A matching cell opening cannot rescue a swapped row commitment
+        let mut cell = matrix.open_cell(1, 4).unwrap();
+        let other = matrix.open_cell(2, 4).unwrap();
+        cell.row.commitment = other.row.commitment;
+        cell.cell = other.cell;
+        cell.cell.row = 1;
+        assert!(!cell.verify(&root).unwrap());
+
+        // Or a row proved against another table's root
+        let (_, other_matrix) = table();
+        assert!(!matrix.open_row(1).unwrap().verify(&other_matrix.root()).unwrap());
+    }
+}
@@ -0,0 +1,172 @@
+//! Typed openings returned by `pcs::open`, `pcs::batch_open`, `pcs::open_bytes`
+//! and `pcs::open_cell`.
+//!
+//! They carry the committed vector's length, so a verifier looks up the
+//! generators and bounds itself instead of being handed a generator point.
+//! They serialize with arkworks' canonical encoding (compressed or not);
+//! deserializing with validation rejects points outside the subgroup.
+use ark_bls12_377::{Fr, G1Affine};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::vec::Vec;
+
+use crate::error::{self, Error};
+use crate::pcs::{batch_check, check, hash_to_scalar, validate_point};
+
+/// Opening of value `index` of a committed vector of length `len`.
+///
+/// `witness = [r]G_0 + Σ_{i != index} [v_i]G_{i+1}` hides the other values,
+/// so the opening can be sent as is; the blinding stays with the committer.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Opening {
+    pub index: usize,
+    pub value: Fr,
+    pub witness: G1Affine,
+    pub len: usize,
+}
+
+impl Opening {
+    /// `check` of this opening against `commitment`.
+    /// Errors if `index` is not below `len` or either point is invalid.
+    pub fn verify(&self, commitment: &G1Affine) -> error::Result<bool> {
+        if self.index >= self.len {
+            return Err(Error::IndexOutOfBounds);
+        }
+        validate_point(commitment)?;
+        validate_point(&self.witness)?;
+        check(*commitment, self.index, self.value, self.witness)
+    }
+}
+
+/// Opening of several values of a committed vector of length `len`, `values`
+/// in the order `indices` were requested in, with a single witness
+/// `[r]G_0 + Σ_{i ∉ indices} [v_i]G_{i+1}`. The witness is itself a hiding
+/// commitment to the unopened values, so nothing about them leaks, and the
+/// proof is one point whatever the subset size.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct BatchOpening {
+    pub indices: Vec<usize>,
+    pub values: Vec<Fr>,
+    pub witness: G1Affine,
+    pub len: usize,
+}
+
+impl BatchOpening {
+    /// `batch_check` of this opening against `commitment`.
+    pub fn verify(&self, commitment: &G1Affine) -> error::Result<bool> {
+        batch_check(*commitment, &self.values, self.witness, &self.indices, self.len)
+    }
+}
+
+/// An `Opening` of a `commit_bytes` slot together with the message it hashes.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct BytesOpening {
+    pub payload: Vec<u8>,
+    pub opening: Opening,
+}
+
+impl BytesOpening {
+    /// Checks that `payload` hashes to the opened value and that the opening
+    /// verifies against `commitment`.
+    pub fn verify(&self, commitment: &G1Affine) -> error::Result<bool> {
+        if hash_to_scalar(&self.payload) != self.opening.value {
+            return Ok(false);
+        }
+        self.opening.verify(commitment)
+    }
+}
+
+/// Opening of the single cell `(row, col)` of a `pcs::commit_matrix` commitment.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CellOpening {
+    pub row: usize,
+    pub col: usize,
+    pub value: Fr,
+    pub witness: G1Affine,
+}
+
+impl CellOpening {
+    /// `check` of this cell against its row's commitment.
+    pub fn verify(&self, row_commitment: &G1Affine) -> error::Result<bool> {
+        check(*row_commitment, self.col, self.value, self.witness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcs::{batch_open, commit, open};
+    #[cfg(not(feature = "verify-only"))]
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    fn committed(len: usize) -> (Vec<Fr>, Fr, G1Affine) {
+        let mut rng = thread_rng();
+        let v: Vec<Fr> = (0..len).map(|_| Fr::rand(&mut rng)).collect();
+        let r = Fr::rand(&mut rng);
+        let c = commit(&v, r).unwrap();
+        (v, r, c)
+    }
+
+    #[test]
+    fn test_opening_verify() {
+        let (v, r, c) = committed(20);
+        let opening = open(&v, r, 7).unwrap();
+        assert_eq!((opening.index, opening.value, opening.len), (7, v[7], 20));
+        assert_eq!(opening.verify(&c), Ok(true));
+
+        let mut wrong_value = opening.clone();
+        wrong_value.value += Fr::from(1u64);
+        assert_eq!(wrong_value.verify(&c), Ok(false));
+
+        let mut wrong_index = opening.clone();
+        wrong_index.index = 8;
+        assert_eq!(wrong_index.verify(&c), Ok(false));
+        wrong_index.index = 20;
+        assert_eq!(wrong_index.verify(&c), Err(Error::IndexOutOfBounds));
+
+        let (_, _, other) = committed(20);
+        assert_eq!(opening.verify(&other), Ok(false));
+        assert_eq!(opening.verify(&G1Affine::identity()), Err(Error::IdentityPoint));
+    }
+
+    #[cfg(not(feature = "verify-only"))]
+    #[test]
+    fn test_batch_opening_verify() {
+        let (v, r, c) = committed(30);
+        let opening = batch_open(&v, r, [21, 3, 9]).unwrap();
+        assert_eq!(opening.indices, vec![21, 3, 9]);
+        assert_eq!(opening.values, vec![v[21], v[3], v[9]]);
+        assert_eq!(opening.verify(&c), Ok(true));
+
+        let mut tampered = opening.clone();
+        tampered.witness = (tampered.witness + G1Affine::generator()).into_affine();
+        assert_eq!(tampered.verify(&c), Ok(false));
+
+        let mut short = opening.clone();
+        short.len = 20;
+        assert_eq!(short.verify(&c), Err(Error::IndexOutOfBounds));
+    }
+
+    #[test]
+    fn test_opening_serialization() {
+        let (v, r, c) = committed(20);
+        let opening = open(&v, r, 2).unwrap();
+        let batch = batch_open(&v, r, [0, 5, 19]).unwrap();
+
+        let mut bytes = Vec::new();
+        opening.serialize_compressed(&mut bytes).unwrap();
+        let decoded = Opening::deserialize_compressed(&bytes[..]).unwrap();
+        assert_eq!(decoded, opening);
+        assert_eq!(decoded.verify(&c), Ok(true));
+
+        let mut bytes = Vec::new();
+        batch.serialize_uncompressed(&mut bytes).unwrap();
+        let decoded = BatchOpening::deserialize_uncompressed(&bytes[..]).unwrap();
+        assert_eq!(decoded, batch);
+        assert_eq!(decoded.verify(&c), Ok(true));
+
+        assert!(Opening::deserialize_compressed(&bytes[..bytes.len() / 2]).is_err());
+    }
+}
+
@@ -1,193 +1,2418 @@
-use anyhow::{anyhow, Result};
-use ark_bls12_377::{g1::Config, Fr, G1Affine, G1Projective};
-use ark_ec::hashing::{
-    curve_maps::wb::WBMap,
-    map_to_curve_hasher::MapToCurveBasedHasher,
-    HashToCurve,
-};
-use ark_ec::CurveGroup;
-use ark_ff::field_hashers::DefaultFieldHasher;
+use ark_bls12_377::{Fq, Fr, G1Affine, G1Projective};
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::field_hashers::{DefaultFieldHasher, HashToField};
+use ark_ff::{BigInt, PrimeField, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Read, Validate, Write};
+use ark_std::ops::Range;
+use ark_std::rand::{CryptoRng, RngCore};
+use ark_std::string::String;
+use ark_std::UniformRand;
+use ark_std::vec::Vec;
+use sha2::digest::FixedOutputReset;
 use sha2::Sha256;
 
+use crate::curve::{hash_to_wb_curve, Bls12_377, PcsCurve};
+use crate::error::{Error, Result};
+use crate::lazy::Lazy;
+#[cfg(not(feature = "verify-only"))]
+use crate::lazy::OnceCell;
+pub use crate::opening::{BatchOpening, BytesOpening, CellOpening, Opening};
+use crate::precompute::PrecomputedKey;
+
 type Scalar = Fr;
 type GroupAffine = G1Affine;
 type GroupProjective = G1Projective;
 
-const NUM_POINTS: usize = 20 + 1;
-const BLINDING_INDEX: usize = 0;
+pub(crate) const NUM_POINTS: usize = 20 + 1;
+pub(crate) const BLINDING_INDEX: usize = 0;
+
+/// Vector length from which `commit` used a multi-scalar multiplication
+/// instead of folding one scalar multiplication per term.
+///
+/// `commit`, `open` and `batch_open` now always sum with one MSM, blinding term
+/// included, so this no longer changes anything.
+#[deprecated(note = "commit always uses a single MSM")]
+pub const MSM_THRESHOLD: usize = 8;
+
+include!(concat!(env!("OUT_DIR"), "/trusted_setup.rs"));
+#[cfg(feature = "verify-only")]
+include!(concat!(env!("OUT_DIR"), "/generators.rs"));
+
+/// Domain separation tag `POINTS` are derived under.
+pub const DEFAULT_DST: &[u8] = Bls12_377::DST;
+
+/// Generators derived on first access; initialization is thread-safe.
+pub static POINTS: Lazy<[GroupAffine; NUM_POINTS]> = Lazy::new(load_points);
+
+#[cfg(not(feature = "verify-only"))]
+fn load_points() -> [GroupAffine; NUM_POINTS] {
+    derive_points(&TRUSTED_SETUP, DEFAULT_DST, NUM_POINTS)
+        .ok()
+        .and_then(|points| points.try_into().ok())
+        .expect("built-in DST and setup hash to the curve")
+}
+
+/// Decodes the generators `build.rs` derived. They come from our own build, so
+/// decompression skips the subgroup check.
+#[cfg(feature = "verify-only")]
+fn load_points() -> [GroupAffine; NUM_POINTS] {
+    GENERATORS.map(|bytes| GroupAffine::deserialize_with_mode(&bytes[..], Compress::Yes, Validate::No).unwrap())
+}
+
+/// Length of the vectors committed under `POINTS`: one value per generator
+/// after the blinding base.
+pub const fn default_vector_len() -> usize {
+    NUM_POINTS - 1
+}
+
+/// Longest vector `commit` accepts: one value per setup line after the one
+/// holding the blinding base. Under `verify-only` only the embedded `POINTS`
+/// are available, so this is `default_vector_len()`.
+pub const fn max_vector_len() -> usize {
+    #[cfg(feature = "verify-only")]
+    return default_vector_len();
+    #[cfg(not(feature = "verify-only"))]
+    return TRUSTED_SETUP.len() - 1;
+}
+
+/// Setup lines hashed together the first time a generator past `POINTS` is needed.
+#[cfg(not(feature = "verify-only"))]
+const GENERATOR_BLOCK: usize = 256;
+#[cfg(not(feature = "verify-only"))]
+const EXTRA_BLOCKS: usize = (TRUSTED_SETUP.len() - NUM_POINTS).div_ceil(GENERATOR_BLOCK);
+
+/// Generators past `POINTS`, derived a block at a time on first use.
+#[cfg(not(feature = "verify-only"))]
+static EXTRA_GENERATORS: [OnceCell<Vec<GroupAffine>>; EXTRA_BLOCKS] = [const { OnceCell::new() }; EXTRA_BLOCKS];
+
+#[cfg(not(feature = "verify-only"))]
+fn extra_block(b: usize) -> &'static [GroupAffine] {
+    EXTRA_GENERATORS[b].get_or_init(|| {
+        let start = NUM_POINTS + b * GENERATOR_BLOCK;
+        let end = (start + GENERATOR_BLOCK).min(TRUSTED_SETUP.len());
+        derive_points(&TRUSTED_SETUP[start..end], DEFAULT_DST, end - start).expect("built-in DST and setup hash to the curve")
+    })
+}
+
+/// Generator `i` of the embedded setup: `POINTS[i]`, or for `i >= NUM_POINTS`
+/// setup line `i` hashed the same way. Under `verify-only` the setup is not
+/// linked, and any `i >= NUM_POINTS` is `IndexOutOfBounds`.
+pub fn generator(i: usize) -> Result<GroupAffine> {
+    if i < NUM_POINTS {
+        return Ok(POINTS[i]);
+    }
+    #[cfg(feature = "verify-only")]
+    return Err(Error::IndexOutOfBounds);
+    #[cfg(not(feature = "verify-only"))]
+    {
+        if i >= TRUSTED_SETUP.len() {
+            return Err(Error::IndexOutOfBounds);
+        }
+        let k = i - NUM_POINTS;
+        Ok(extra_block(k / GENERATOR_BLOCK)[k % GENERATOR_BLOCK])
+    }
+}
+
+/// Generator of value `j`, `G_{j+1}`. Never the blinding base: an index that
+/// would map to `BLINDING_INDEX` is `BlindingGenerator`.
+pub fn value_generator(j: usize) -> Result<GroupAffine> {
+    let i = j.checked_add(1).ok_or(Error::IndexOutOfBounds)?;
+    if i == BLINDING_INDEX {
+        return Err(Error::BlindingGenerator);
+    }
+    generator(i)
+}
+
+/// The first `count` generators of the embedded setup, `POINTS` first.
+/// Errors with `SetupTooShort` if the setup has fewer than `count` lines, or
+/// under `verify-only` if `count` is past `POINTS`.
+pub fn generators(count: usize) -> Result<Vec<GroupAffine>> {
+    let available = max_vector_len() + 1;
+    if count > available {
+        return Err(Error::SetupTooShort { expected: count, got: available });
+    }
+    #[cfg_attr(feature = "verify-only", allow(unused_mut))]
+    let mut points = POINTS[..count.min(NUM_POINTS)].to_vec();
+    #[cfg(not(feature = "verify-only"))]
+    for b in 0..EXTRA_BLOCKS {
+        let start = NUM_POINTS + b * GENERATOR_BLOCK;
+        if start >= count {
+            break;
+        }
+        let block = extra_block(b);
+        points.extend_from_slice(&block[..block.len().min(count - start)]);
+    }
+    Ok(points)
+}
+
+/// `POINTS` as a slice: the generators `commit` uses for vectors of up to
+/// `default_vector_len()` values, blinding base first.
+pub fn default_generators() -> &'static [GroupAffine] {
+    &POINTS[..]
+}
+
+/// The base `r` multiplies in every commitment.
+pub fn blinding_generator() -> GroupAffine {
+    POINTS[BLINDING_INDEX]
+}
+
+/// Version byte leading `export_generators` output.
+pub const GENERATORS_EXPORT_VERSION: u8 = 1;
+
+/// Generators read back by `import_generators`, with the DST they were hashed
+/// under.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExportedGenerators {
+    pub dst: Vec<u8>,
+    pub points: Vec<GroupAffine>,
+}
+
+/// Writes `default_generators()` for verifiers that do not run this crate.
+///
+/// Layout (version 1):
+///
+/// ```text
+/// version: u8
+/// dst_len: u32 LE || dst
+/// count:   u32 LE
+/// points:  count compressed G1 points, 48 bytes each, blinding base first
+/// ```
+pub fn export_generators<W: Write>(w: W) -> Result<()> {
+    write_generators(w, DEFAULT_DST, default_generators())
+}
+
+/// `export_generators` for the first `count` generators, covering vectors of
+/// up to `count - 1` values.
+pub fn export_generator_prefix<W: Write>(w: W, count: usize) -> Result<()> {
+    write_generators(w, DEFAULT_DST, &generators(count)?)
+}
+
+fn write_generators<W: Write>(mut w: W, dst: &[u8], points: &[GroupAffine]) -> Result<()> {
+    w.write_all(&[GENERATORS_EXPORT_VERSION]).map_err(|_| Error::WriteFailed)?;
+    w.write_all(&(dst.len() as u32).to_le_bytes()).map_err(|_| Error::WriteFailed)?;
+    w.write_all(dst).map_err(|_| Error::WriteFailed)?;
+    w.write_all(&(points.len() as u32).to_le_bytes()).map_err(|_| Error::WriteFailed)?;
+    for p in points {
+        p.serialize_compressed(&mut w).map_err(|_| Error::WriteFailed)?;
+    }
+    Ok(())
+}
+
+/// Reads `export_generators` output. Every point is validated as in
+/// `deserialize_checked`; truncated input is `MalformedEncoding`.
+pub fn import_generators<R: Read>(mut r: R) -> Result<ExportedGenerators> {
+    let mut version = [0u8; 1];
+    r.read_exact(&mut version).map_err(|_| Error::MalformedEncoding)?;
+    if version[0] != GENERATORS_EXPORT_VERSION {
+        return Err(Error::UnsupportedVersion(version[0]));
+    }
+    let dst_len = read_u32(&mut r)? as usize;
+    // Read in steps so a corrupt length fails on truncation, not allocation
+    let mut dst = Vec::new();
+    let mut buf = [0u8; 64];
+    while dst.len() < dst_len {
+        let n = buf.len().min(dst_len - dst.len());
+        r.read_exact(&mut buf[..n]).map_err(|_| Error::MalformedEncoding)?;
+        dst.extend_from_slice(&buf[..n]);
+    }
+    let count = read_u32(&mut r)? as usize;
+    let mut points = Vec::with_capacity(count.min(TRUSTED_SETUP.len()));
+    let mut bytes = [0u8; 48];
+    for _ in 0..count {
+        r.read_exact(&mut bytes).map_err(|_| Error::MalformedEncoding)?;
+        points.push(deserialize_checked(&bytes)?);
+    }
+    Ok(ExportedGenerators { dst, points })
+}
+
+fn read_u32<R: Read>(r: &mut R) -> Result<u32> {
+    let mut bytes = [0u8; 4];
+    r.read_exact(&mut bytes).map_err(|_| Error::MalformedEncoding)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+/// Generators for a committed vector of length `len`.
+fn key_for_len(len: usize) -> Result<Vec<GroupAffine>> {
+    generators(len.saturating_add(1))
+}
+
+/// Hashes the first `count` setup lines to generators under `dst`.
+///
+/// Distinct DSTs yield independent generator sets from the same setup, so
+/// applications sharing a ceremony should each use their own tag. Errors if
+/// `setup` has fewer than `count` lines or a line fails to hash (see
+/// `PcsCurve::hash_to_curve`).
+pub fn derive_points(setup: &[&str], dst: &[u8], count: usize) -> Result<Vec<GroupAffine>> {
+    derive_points_on::<Bls12_377>(setup, dst, count)
+}
+
+/// `derive_points` with `H` in place of SHA-256 inside hash-to-curve, for
+/// integrations standardized on another digest (e.g. SHA-3 or Blake2).
+pub fn derive_points_with<H: FixedOutputReset + Default + Clone>(setup: &[&str], dst: &[u8], count: usize) -> Result<Vec<GroupAffine>> {
+    setup_prefix(setup, count)?
+        .iter()
+        .map(|line| hash_to_wb_curve::<ark_bls12_377::g1::Config, H>(dst, line.as_bytes()))
+        .collect()
+}
+
+pub fn derive_points_on<C: PcsCurve>(setup: &[&str], dst: &[u8], count: usize) -> Result<Vec<C::Affine>> {
+    setup_prefix(setup, count)?
+        .iter()
+        .map(|line| C::hash_to_curve(dst, line.as_bytes()))
+        .collect()
+}
+
+fn setup_prefix<'a, 'b>(setup: &'a [&'b str], count: usize) -> Result<&'a [&'b str]> {
+    setup.get(..count).ok_or(Error::SetupTooShort { expected: count, got: setup.len() })
+}
+
+/// Rejects the identity and points that are off-curve or outside the G1 subgroup.
+pub fn validate_point(p: &GroupAffine) -> Result<()> {
+    Bls12_377::validate_point(p)
+}
+
+/// Decodes a point received from outside (file, RPC, JS) and validates it.
+///
+/// Accepts the compressed or uncompressed encoding, chosen by length. Decoding
+/// itself does no checks; the point then goes through `validate_point`, so the
+/// error says whether it is the identity, off the curve, or on the curve but
+/// outside the prime-order subgroup. Bytes that do not decode at all give
+/// `MalformedEncoding`.
+pub fn deserialize_checked(bytes: &[u8]) -> Result<GroupAffine> {
+    let compress = if bytes.len() == GroupAffine::zero().compressed_size() {
+        Compress::Yes
+    } else if bytes.len() == GroupAffine::zero().uncompressed_size() {
+        Compress::No
+    } else {
+        return Err(Error::MalformedEncoding);
+    };
+    let p = GroupAffine::deserialize_with_mode(bytes, compress, Validate::No).map_err(|_| Error::MalformedEncoding)?;
+    validate_point(&p)?;
+    Ok(p)
+}
+
+/// Commits to `v` of any length up to `max_vector_len()`, using the first
+/// `v.len() + 1` generators. Generators depend only on their position, so a
+/// vector of length 20 commits under exactly `POINTS`, and a shorter one gives
+/// the same commitment as its zero-padded form.
+pub fn commit(v: &[Scalar], r: Scalar) -> Result<GroupAffine> {
+    commit_with_points::<Bls12_377>(&key_for_len(v.len())?, v, r)
+}
+
+/// Opens value `j` of `v`; check it with `Opening::verify`.
+pub fn open(v: &[Scalar], r: Scalar, j: usize) -> Result<Opening> {
+    let (value, _, witness) = open_with_points::<Bls12_377>(&key_for_len(v.len())?, v, r, j)?;
+    Ok(Opening { index: j, value, witness, len: v.len() })
+}
+
+/// `commit` using `key`'s fixed-base tables; same result, errors if `key`
+/// prepares fewer than `v.len() + 1` generators.
+pub fn commit_with(key: &PrecomputedKey, v: &[Scalar], r: Scalar) -> Result<GroupAffine> {
+    key.commit(v, r)
+}
+
+/// `open` using `key`'s fixed-base tables.
+pub fn open_with(key: &PrecomputedKey, v: &[Scalar], r: Scalar, j: usize) -> Result<Opening> {
+    let (value, _, witness) = key.open(v, r, j)?;
+    Ok(Opening { index: j, value, witness, len: v.len() })
+}
+
+/// `commit` for vectors up to the key length, zero-padding short ones.
+///
+/// A zero value adds `[0]G_{j+1}`, the identity, so the result equals `commit`
+/// of the explicitly padded vector (and of `v` itself) and opens the same way
+/// at every index. Unlike `commit`, vectors longer than `POINTS` allows fail
+/// with `LengthMismatch`.
+pub fn commit_padded(v: &[Scalar], r: Scalar) -> Result<GroupAffine> {
+    if v.len() > NUM_POINTS - 1 {
+        return Err(Error::LengthMismatch { expected: NUM_POINTS - 1, got: v.len() });
+    }
+    // Generators depend only on their position, so a prefix is the padded key
+    commit_with_points::<Bls12_377>(&POINTS[..v.len() + 1], v, r)
+}
+
+/// Openings of every index, in index order, each one what `open` returns.
+///
+/// Each witness is `C - [v_j]G_{j+1}`, so this costs one commitment plus one
+/// scalar multiplication per index instead of `v.len()` separate `open`s of
+/// `v.len()` terms each.
+pub fn open_all(v: &[Scalar], r: Scalar) -> Result<Vec<Opening>> {
+    let openings = open_all_with_points::<Bls12_377>(&key_for_len(v.len())?, v, r)?;
+    Ok(openings
+        .into_iter()
+        .enumerate()
+        .map(|(index, (value, witness))| Opening { index, value, witness, len: v.len() })
+        .collect())
+}
+
+/// Recovers the committed vector from `(index, value)` openings covering every
+/// index `0..openings.len()` exactly once, in any order.
+///
+/// Only the index set is checked; verify each opening against the commitment
+/// (e.g. with `check`) before trusting the result.
+pub fn reconstruct(openings: &[(usize, Scalar)]) -> Result<Vec<Scalar>> {
+    if openings.is_empty() {
+        return Err(Error::EmptyIndices);
+    }
+
+    let mut sorted = openings.to_vec();
+    sorted.sort_unstable_by_key(|&(j, _)| j);
+    if sorted.windows(2).any(|w| w[0].0 == w[1].0) {
+        return Err(Error::DuplicateIndices);
+    }
+    // Unique and sorted, so a gap pushes some index past the end
+    if sorted.iter().enumerate().any(|(k, &(j, _))| j != k) {
+        return Err(Error::IndexOutOfBounds);
+    }
+
+    Ok(sorted.into_iter().map(|(_, v_j)| v_j).collect())
+}
+
+/// `open` without index-dependent control flow or memory access.
+///
+/// `open` skips position `j` with a `filter`, so its loop shape reveals which
+/// slot is being opened to anyone who can time the service or observe its
+/// cache. Here the full commitment is computed with every real value, and
+/// `[v_j]G_{j+1}` is subtracted from it. `v_j` and the coordinates of `G_{j+1}`
+/// are selected by a pass over every slot that multiplies each by a 0/1 mask
+/// in the field, so no scalar multiplication or memory access depends on `j`.
+/// This costs one extra scalar multiplication over `open`. It only hides the
+/// index; arkworks scalar multiplication itself is not constant-time in the
+/// scalar, so values remain exposed to a sufficiently fine-grained side channel.
+pub fn open_ct(v: &[Scalar], r: Scalar, j: usize) -> Result<Opening> {
+    if j >= v.len() {
+        return Err(Error::IndexOutOfBounds);
+    }
+
+    let points = key_for_len(v.len())?;
+    let total = commit_with_points::<Bls12_377>(&points, v, r)?;
+
+    let (mut v_j, mut x, mut y) = (Scalar::zero(), Fq::zero(), Fq::zero());
+    for (i, (p, v_i)) in points[1..].iter().zip(v).enumerate() {
+        let selected = (i == j) as u64;
+        v_j += Scalar::from(selected) * v_i;
+        x += Fq::from(selected) * p.x;
+        y += Fq::from(selected) * p.y;
+    }
+    // Generators are never the identity, so the selected coordinates are a point
+    let h_j = GroupAffine::new_unchecked(x, y);
+    let witness = total.into_group() - h_j * v_j;
+    Ok(Opening { index: j, value: v_j, witness: witness.into_affine(), len: v.len() })
+}
+
+/// Checks that `witness` opens value `j` of `c` to `v_j`, against the
+/// generator `value_generator(j)` of the embedded setup. Errors if `j` is past
+/// `max_vector_len()`.
+pub fn check(c: GroupAffine, j: usize, v_j: Scalar, witness: GroupAffine) -> Result<bool> {
+    Ok(check_unchecked(c, v_j, witness, value_generator(j)?))
+}
+
+/// `check` against a caller-supplied generator `h_j`, for generator sets other
+/// than the embedded setup. Nothing ties `h_j` to an index: passing the wrong
+/// generator, or the blinding base, makes the result meaningless.
+pub fn check_unchecked(c: GroupAffine, v_j: Scalar, witness: GroupAffine, h_j: GroupAffine) -> bool {
+    check_on::<Bls12_377>(c, v_j, witness, h_j)
+}
+
+/// Opens `indices`, given in any order; `values` follow the same order.
+/// Check it with `BatchOpening::verify`.
+pub fn batch_open(v: &[Scalar], r: Scalar, indices: impl IntoIterator<Item = usize>) -> Result<BatchOpening> {
+    let indices: Vec<usize> = indices.into_iter().collect();
+    let (values, _, witness) = batch_open_with_points::<Bls12_377>(&key_for_len(v.len())?, v, r, indices.iter().copied())?;
+    Ok(BatchOpening { indices, values, witness, len: v.len() })
+}
+
+/// Checks a `batch_open` opening of a commitment to a vector of length `len`.
+/// An index at or past `len` is `IndexOutOfBounds`, however many generators
+/// the setup has.
+pub fn batch_check(c: GroupAffine, values: &[Scalar], witness: GroupAffine, indices: &[usize], len: usize) -> Result<bool> {
+    batch_check_with_points::<Bls12_377>(&key_for_len(len)?, c, values, witness, indices)
+}
+
+/// `batch_open` of the contiguous indices `range`. A range is sorted and
+/// unique by construction, so there is nothing to check beyond its bounds,
+/// and the witness sums the generator slices either side of it. An empty
+/// range is `EmptyIndices`, one past `v.len()` `IndexOutOfBounds`.
+pub fn open_range(v: &[Scalar], r: Scalar, range: Range<usize>) -> Result<BatchOpening> {
+    if range.is_empty() {
+        return Err(Error::EmptyIndices);
+    }
+    if range.end > v.len() {
+        return Err(Error::IndexOutOfBounds);
+    }
+    let points = key_for_len(v.len())?;
+    let (before, rest) = points[1..].split_at(range.start);
+    let after = &rest[range.len()..];
+    let witness = points[BLINDING_INDEX] * r
+        + GroupProjective::msm_unchecked(before, &v[..range.start])
+        + GroupProjective::msm_unchecked(after, &v[range.end..]);
+    Ok(BatchOpening {
+        indices: range.clone().collect(),
+        values: v[range.clone()].to_vec(),
+        witness: witness.into_affine(),
+        len: v.len(),
+    })
+}
+
+/// `batch_check` of an `open_range` opening, `values` in range order.
+pub fn check_range(c: GroupAffine, values: &[Scalar], witness: GroupAffine, range: Range<usize>) -> Result<bool> {
+    if range.is_empty() {
+        return Err(Error::EmptyIndices);
+    }
+    if values.len() != range.len() {
+        return Err(Error::ValuesIndicesMismatch);
+    }
+    validate_point(&c)?;
+    validate_point(&witness)?;
+    let points = key_for_len(range.end)?;
+    let sum = GroupProjective::msm_unchecked(&points[range.start + 1..], values);
+    Ok(c.into_group() - witness == sum)
+}
+
+/// `check` for a commitment and witness received as bytes (compressed or
+/// uncompressed), against the generator of value `index`.
+///
+/// Both points go through `deserialize_checked`, so malformed, off-curve,
+/// out-of-subgroup and identity encodings are errors rather than `false`.
+pub fn check_from_bytes(c_bytes: &[u8], v_j: Scalar, witness_bytes: &[u8], index: usize) -> Result<bool> {
+    let c = deserialize_checked(c_bytes)?;
+    let witness = deserialize_checked(witness_bytes)?;
+    check(c, index, v_j, witness)
+}
+
+/// `batch_check` for a commitment and witness received as bytes; see `check_from_bytes`.
+pub fn batch_check_from_bytes(
+    c_bytes: &[u8],
+    values: &[Scalar],
+    witness_bytes: &[u8],
+    indices: &[usize],
+    len: usize,
+) -> Result<bool> {
+    let c = deserialize_checked(c_bytes)?;
+    let witness = deserialize_checked(witness_bytes)?;
+    batch_check(c, values, witness, indices, len)
+}
+
+/// Checks sparse `entries` of a vector of length `len`: indices below `len`,
+/// none repeated, and `len` within the setup.
+fn check_sparse(entries: &[(usize, Scalar)], len: usize) -> Result<()> {
+    if len > max_vector_len() {
+        return Err(Error::SetupTooShort { expected: len + 1, got: max_vector_len() + 1 });
+    }
+    let mut indices: Vec<usize> = entries.iter().map(|&(j, _)| j).collect();
+    indices.sort_unstable();
+    if indices.windows(2).any(|w| w[0] == w[1]) {
+        return Err(Error::DuplicateIndices);
+    }
+    if indices.last().is_some_and(|&j| j >= len) {
+        return Err(Error::IndexOutOfBounds);
+    }
+    Ok(())
+}
+
+/// `[r]G_0 + Σ [v_j]G_{j+1}` over the entries not `skip`ped, touching only
+/// their generators.
+fn sparse_sum(entries: &[(usize, Scalar)], r: Scalar, skip: impl Fn(usize) -> bool) -> Result<GroupProjective> {
+    let mut bases = Vec::with_capacity(entries.len() + 1);
+    let mut scalars = Vec::with_capacity(entries.len() + 1);
+    bases.push(POINTS[BLINDING_INDEX]);
+    scalars.push(r);
+    for &(j, v_j) in entries.iter().filter(|&&(j, v_j)| !v_j.is_zero() && !skip(j)) {
+        bases.push(value_generator(j)?);
+        scalars.push(v_j);
+    }
+    Ok(GroupProjective::msm_unchecked(&bases, &scalars))
+}
+
+/// The value at `j` of a sparse vector, zero if no entry names it.
+fn sparse_value(entries: &[(usize, Scalar)], j: usize) -> Scalar {
+    entries.iter().find(|&&(i, _)| i == j).map_or(Scalar::zero(), |&(_, v_j)| v_j)
+}
+
+/// `commit` of the length-`len` vector that is zero except at `entries`, in
+/// time proportional to the non-zero entries rather than to `len`. Equal to
+/// `commit` of the dense vector. Entries may come in any order; a repeated
+/// index is `DuplicateIndices` and one at or past `len` `IndexOutOfBounds`.
+pub fn commit_sparse(entries: &[(usize, Scalar)], len: usize, r: Scalar) -> Result<GroupAffine> {
+    check_sparse(entries, len)?;
+    Ok(sparse_sum(entries, r, |_| false)?.into_affine())
+}
+
+/// `open` of a `commit_sparse` vector at `j`, zero or not.
+pub fn open_sparse(entries: &[(usize, Scalar)], len: usize, r: Scalar, j: usize) -> Result<Opening> {
+    check_sparse(entries, len)?;
+    if j >= len {
+        return Err(Error::IndexOutOfBounds);
+    }
+    let witness = sparse_sum(entries, r, |i| i == j)?.into_affine();
+    Ok(Opening { index: j, value: sparse_value(entries, j), witness, len })
+}
+
+/// `batch_open` of a `commit_sparse` vector; `indices` need not be entries.
+pub fn batch_open_sparse(
+    entries: &[(usize, Scalar)],
+    len: usize,
+    r: Scalar,
+    indices: impl IntoIterator<Item = usize>,
+) -> Result<BatchOpening> {
+    check_sparse(entries, len)?;
+    let indices: Vec<usize> = indices.into_iter().collect();
+    if indices.is_empty() {
+        return Err(Error::EmptyIndices);
+    }
+    let mut sorted_indices = indices.clone();
+    sorted_indices.sort_unstable();
+    if sorted_indices.windows(2).any(|w| w[0] == w[1]) {
+        return Err(Error::DuplicateIndices);
+    }
+    if sorted_indices.last().is_some_and(|&j| j >= len) {
+        return Err(Error::IndexOutOfBounds);
+    }
+
+    let witness = sparse_sum(entries, r, |i| sorted_indices.binary_search(&i).is_ok())?.into_affine();
+    let values = indices.iter().map(|&j| sparse_value(entries, j)).collect();
+    Ok(BatchOpening { indices, values, witness, len })
+}
+
+/// Commits to a single value; equal to `commit` on `x` followed by zeros.
+pub fn commit_scalar(x: Scalar, r: Scalar) -> GroupAffine {
+    (POINTS[BLINDING_INDEX] * r + POINTS[1] * x).into_affine()
+}
+
+/// Opens a `commit_scalar` commitment; the witness is just the blinding term.
+pub fn open_scalar(x: Scalar, r: Scalar) -> (Scalar, Scalar, GroupAffine) {
+    (x, r, (POINTS[BLINDING_INDEX] * r).into_affine())
+}
+
+pub fn check_scalar(c: GroupAffine, x: Scalar, witness: GroupAffine) -> bool {
+    check_unchecked(c, x, witness, POINTS[1])
+}
+
+/// Verifier side of `add_commitments`: the commitment to the element-wise sum
+/// of the two committed vectors, without knowing either blinding.
+pub fn add_commitment_points(c1: GroupAffine, c2: GroupAffine) -> GroupAffine {
+    (c1 + c2).into_affine()
+}
+
+/// Commitment to `v1 + v2` from `c1 = commit(v1, r1)` and `c2 = commit(v2, r2)`,
+/// with its blinding `r1 + r2`, so `open`/`batch_open` of the summed vector
+/// under that blinding verify against it. Vectors of different lengths add as
+/// if the shorter one were zero-padded.
+pub fn add_commitments(c1: GroupAffine, r1: Scalar, c2: GroupAffine, r2: Scalar) -> (GroupAffine, Scalar) {
+    (add_commitment_points(c1, c2), r1 + r2)
+}
+
+/// Commitment to `k * v` from `c = commit(v, r)`, with its blinding `k * r`;
+/// one scalar multiplication of `c`. With `add_commitments` this gives any
+/// linear combination of committed vectors.
+///
+/// `k = 0` yields the identity with blinding zero: a commitment to the zero
+/// vector that hides nothing, and one `check` rejects and `batch_check`
+/// reports as `IdentityPoint`.
+pub fn scale_commitment(c: GroupAffine, k: Scalar, r: Scalar) -> (GroupAffine, Scalar) {
+    ((c * k).into_affine(), k * r)
+}
+
+/// Commitment after value `j` of a length-`len` vector changes from `old` to
+/// `new`: `c + [new - old]G_{j+1}`, one scalar multiplication however long the
+/// vector. Uses the generators `open` does, so openings of the updated vector
+/// verify against the result; `old == new` returns `c` unchanged. See
+/// `update_witness` for keeping existing witnesses current.
+pub fn update_commitment(c: GroupAffine, j: usize, old: Scalar, new: Scalar, len: usize) -> Result<GroupAffine> {
+    if j >= len {
+        return Err(Error::IndexOutOfBounds);
+    }
+    let h_j = generator(j + 1)?;
+    if old == new {
+        return Ok(c);
+    }
+    Ok((c + h_j * (new - old)).into_affine())
+}
+
+/// Witness for index `j` after value `k` of a length-`len` vector changes from
+/// `old_k` to `new_k`: `witness + [new_k - old_k]G_{k+1}`, the counterpart of
+/// `update_commitment` for a held opening.
+///
+/// `k == j` is `OpenedIndexChanged`: the witness excludes slot `j`, so it is
+/// already current and only the opened value becomes `new_k`.
+pub fn update_witness(witness: GroupAffine, j: usize, k: usize, old_k: Scalar, new_k: Scalar, len: usize) -> Result<GroupAffine> {
+    if j >= len {
+        return Err(Error::IndexOutOfBounds);
+    }
+    if k == j {
+        return Err(Error::OpenedIndexChanged);
+    }
+    update_commitment(witness, k, old_k, new_k, len)
+}
+
+/// Non-hiding commitment for public data: `Σ [v_j] POINTS[j]`, with no
+/// blinding term, so `POINTS[0]` carries a value too and `v` has
+/// `POINTS.len()` entries.
+///
+/// This gives NO hiding: the commitment is a deterministic function of `v`,
+/// so anyone can test a guess for the data against it. It is still binding.
+/// Slot `j` here uses `POINTS[j]`, not `POINTS[j + 1]`, so these commitments
+/// must only be checked with `check_no_blind`.
+pub fn commit_no_blind(v: &[Scalar]) -> Result<GroupAffine> {
+    if v.len() != POINTS.len() {
+        return Err(Error::LengthMismatch { expected: POINTS.len(), got: v.len() });
+    }
+    Ok(GroupProjective::msm_unchecked(&POINTS[..], v).into_affine())
+}
+
+/// Opens slot `j` of a `commit_no_blind` commitment; the witness is
+/// `Σ_{i != j} [v_i] POINTS[i]`.
+pub fn open_no_blind(v: &[Scalar], j: usize) -> Result<(Scalar, GroupAffine)> {
+    if v.len() != POINTS.len() {
+        return Err(Error::LengthMismatch { expected: POINTS.len(), got: v.len() });
+    }
+    if j >= v.len() {
+        return Err(Error::IndexOutOfBounds);
+    }
+    let witness = POINTS.iter().zip(v).enumerate()
+        .filter(|(i, _)| *i != j)
+        .map(|(_, (p, v_i))| *p * v_i)
+        .sum::<GroupProjective>();
+    Ok((v[j], witness.into_affine()))
+}
+
+/// Checks a `open_no_blind` opening of slot `j`. Without blinding the
+/// commitment or witness is the identity whenever the corresponding values
+/// are all zero, so the identity is accepted here; other invalid points are not.
+pub fn check_no_blind(c: GroupAffine, v_j: Scalar, witness: GroupAffine, j: usize) -> bool {
+    let valid = |p: &GroupAffine| p.is_zero() || validate_point(p).is_ok();
+    match POINTS.get(j) {
+        Some(h_j) if valid(&c) && valid(&witness) => c.into_group() == witness + *h_j * v_j,
+        _ => false,
+    }
+}
+
+/// Domain separation tag for mapping byte messages to scalars in `commit_bytes`.
+pub const BYTES_DST: &[u8] = b"OBLIVIOUS-SYNC-V02_BYTES_XMD:SHA-256_";
+
+/// Version byte prefixed to every message in `hash_to_scalar`. A new encoding
+/// gets a new version and `BYTES_DST`, so scalars from one never collide with
+/// another's. Version 1 was the unprefixed message under the `V01` tag; its
+/// commitments do not verify under version 2 and must be recomputed.
+pub const BYTES_ENCODING_VERSION: u8 = 2;
+
+/// Maps a message to a scalar with RFC 9380 hash-to-field (`expand_message_xmd`
+/// over SHA-256 under `BYTES_DST`) of `BYTES_ENCODING_VERSION || message`, which
+/// is unbiased. Each message is hashed on its own and XMD appends the DST and
+/// output length after it, so messages that differ only in length (e.g. `b"ab"`
+/// and `b"ab\0"`) map to unrelated scalars. The encoding is stable: a version's
+/// scalars never change.
+pub fn hash_to_scalar(message: &[u8]) -> Scalar {
+    let mut versioned = Vec::with_capacity(1 + message.len());
+    versioned.push(BYTES_ENCODING_VERSION);
+    versioned.extend_from_slice(message);
+    let [x] = <DefaultFieldHasher<Sha256> as HashToField<Scalar>>::new(BYTES_DST).hash_to_field::<1>(&versioned);
+    x
+}
+
+/// Domain separation tag for deriving blindings in `derive_blinding`.
+pub const BLINDING_SEED_DST: &[u8] = b"OBLIVIOUS-SYNC-V01_BLINDING_SEED_XMD:SHA-256_";
+
+/// Blinding for `(seed, context)`: RFC 9380 hash-to-field over SHA-256 under
+/// `BLINDING_SEED_DST`, of the seed length (u64 LE), the seed and the context.
+/// The length prefix keeps `(b"ab", b"c")` and `(b"a", b"bc")` apart.
+///
+/// The derivation is stable: the same inputs give the same blinding in every
+/// version, so openings made from a re-derived blinding keep verifying. Under
+/// a secret seed, blindings for different contexts are independent uniform
+/// scalars, and so are the commitments made with them.
+pub fn derive_blinding(seed: &[u8], context: &[u8]) -> Scalar {
+    let mut message = Vec::with_capacity(8 + seed.len() + context.len());
+    message.extend_from_slice(&(seed.len() as u64).to_le_bytes());
+    message.extend_from_slice(seed);
+    message.extend_from_slice(context);
+    let [r] = <DefaultFieldHasher<Sha256> as HashToField<Scalar>>::new(BLINDING_SEED_DST).hash_to_field::<1>(&message);
+    r
+}
+
+/// `commit` under `derive_blinding(seed, context)`, returning the commitment
+/// and the blinding to open it with.
+pub fn commit_deterministic(v: &[Scalar], seed: &[u8], context: &[u8]) -> Result<(GroupAffine, Scalar)> {
+    let r = derive_blinding(seed, context);
+    Ok((commit(v, r)?, r))
+}
+
+/// Parses a scalar from decimal or `0x`-prefixed hex (either case).
+/// Values at or above the modulus are rejected rather than reduced.
+pub fn scalar_from_str(s: &str) -> Result<Scalar> {
+    let (digits, radix) = match s.strip_prefix("0x") {
+        Some(hex) => (hex, 16),
+        None => (s, 10),
+    };
+    if digits.is_empty() {
+        return Err(Error::InvalidScalar);
+    }
+
+    // Little-endian 64-bit limbs; anything past 256 bits is out of range anyway
+    let mut limbs = [0u64; 4];
+    for c in digits.chars() {
+        let mut carry = c.to_digit(radix).ok_or(Error::InvalidScalar)? as u128;
+        for limb in limbs.iter_mut() {
+            let x = (*limb as u128) * radix as u128 + carry;
+            *limb = x as u64;
+            carry = x >> 64;
+        }
+        if carry != 0 {
+            return Err(Error::InvalidScalar);
+        }
+    }
+    Scalar::from_bigint(BigInt::new(limbs)).ok_or(Error::InvalidScalar)
+}
+
+/// `0x`-prefixed, zero-padded big-endian hex; inverse of `scalar_from_str`.
+pub fn scalar_to_hex(x: &Scalar) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut out = String::from("0x");
+    for limb in x.into_bigint().0.iter().rev() {
+        for shift in (0..16).rev() {
+            out.push(DIGITS[((limb >> (shift * 4)) & 0xf) as usize] as char);
+        }
+    }
+    out
+}
+
+/// Commits to byte messages, one per slot, via `hash_to_scalar`.
+pub fn commit_bytes(messages: &[&[u8]], r: Scalar) -> Result<GroupAffine> {
+    let v: Vec<Scalar> = messages.iter().map(|m| hash_to_scalar(m)).collect();
+    commit(&v, r)
+}
+
+/// Opens slot `j` of a `commit_bytes` commitment, together with its message.
+pub fn open_bytes(messages: &[&[u8]], r: Scalar, j: usize) -> Result<BytesOpening> {
+    let v: Vec<Scalar> = messages.iter().map(|m| hash_to_scalar(m)).collect();
+    let opening = open(&v, r, j)?;
+    Ok(BytesOpening { payload: messages[j].to_vec(), opening })
+}
+
+/// Checks an `open_bytes` witness against the original message at slot `j`,
+/// re-deriving its scalar.
+pub fn check_bytes(c: GroupAffine, message: &[u8], witness: GroupAffine, j: usize) -> Result<bool> {
+    check(c, j, hash_to_scalar(message), witness)
+}
+
+/// Commits to integers, each lifted into the field with `Scalar::from`.
+pub fn commit_u64s(values: &[u64], r: Scalar) -> Result<GroupAffine> {
+    let v: Vec<Scalar> = values.iter().map(|&x| Scalar::from(x)).collect();
+    commit(&v, r)
+}
+
+/// Opens slot `j` of a `commit_u64s` commitment; decode the value with
+/// `scalar_to_u64`.
+pub fn open_u64(values: &[u64], r: Scalar, j: usize) -> Result<Opening> {
+    let v: Vec<Scalar> = values.iter().map(|&x| Scalar::from(x)).collect();
+    open(&v, r, j)
+}
+
+/// `check` of integer `value` at slot `j`.
+pub fn check_u64(c: GroupAffine, j: usize, value: u64, witness: GroupAffine) -> Result<bool> {
+    check(c, j, Scalar::from(value), witness)
+}
+
+/// The integer an opened scalar lifts from. Scalars at or above `2^64` are
+/// `ValueOutOfRange` rather than truncated: an opening of `2^64 + 5` must not
+/// read as 5.
+pub fn scalar_to_u64(x: &Scalar) -> Result<u64> {
+    match x.into_bigint().0 {
+        [low, 0, 0, 0] => Ok(low),
+        _ => Err(Error::ValueOutOfRange),
+    }
+}
+
+/// One `batch_check` call: `(commitment, values, witness, indices, len)`.
+pub type BatchCheckItem<'a> = (GroupAffine, &'a [Scalar], GroupAffine, &'a [usize], usize);
+
+/// Verifies several independent batch openings and reports pass/fail per item.
+/// Malformed items (e.g. mismatched lengths) are reported as failures.
+pub fn batch_check_report(checks: &[BatchCheckItem]) -> Vec<bool> {
+    checks
+        .iter()
+        .map(|&(c, values, witness, indices, len)| batch_check(c, values, witness, indices, len).unwrap_or(false))
+        .collect()
+}
+
+/// Aggregate form of `batch_check_report`: stops at the first failing item.
+pub fn batch_check_many(checks: &[BatchCheckItem]) -> bool {
+    checks
+        .iter()
+        .all(|&(c, values, witness, indices, len)| batch_check(c, values, witness, indices, len).unwrap_or(false))
+}
+
+/// One `check` call: `(commitment, index, value, witness)`.
+pub type OpeningItem = (GroupAffine, usize, Scalar, GroupAffine);
+
+/// Checks many single-index openings, of one commitment or several, with one
+/// multi-scalar multiplication instead of a `check` each.
+///
+/// Item `i`'s equation `c_i - w_i - [v_i]G_{j_i+1} = 0` is scaled by a random
+/// `ρ_i` and the results summed. If any item is false the sum is nonzero
+/// except with probability `1/|Fr|` over the `ρ_i`, so they must come from a
+/// `CryptoRng` the openings' author can't predict. `Ok(false)` says only that
+/// some item failed; run `check` per item to find which. An index past
+/// `max_vector_len()` is an error, an invalid point a failure.
+pub fn verify_openings_batch<R: RngCore + CryptoRng>(items: &[OpeningItem], rng: &mut R) -> Result<bool> {
+    let mut bases = Vec::with_capacity(3 * items.len());
+    let mut scalars = Vec::with_capacity(3 * items.len());
+    for &(c, j, v_j, witness) in items {
+        let h_j = value_generator(j)?;
+        if validate_point(&c).is_err() || validate_point(&witness).is_err() {
+            return Ok(false);
+        }
+        let rho = Scalar::rand(rng);
+        bases.extend([c, witness, h_j]);
+        scalars.extend([rho, -rho, -rho * v_j]);
+    }
+    Ok(GroupProjective::msm_unchecked(&bases, &scalars).is_zero())
+}
+
+/// `verify_openings_batch` for many commitments all opened at index `j`:
+/// item `i` is `(commitments[i], values[i], witnesses[i])`.
+///
+/// With the generator shared, the `[ρ_i v_i]G_{j+1}` terms collapse into one,
+/// so the MSM has `2n + 1` terms instead of `3n`. Errors if the slices differ
+/// in length or `j` is past `max_vector_len()`.
+pub fn verify_same_index_batch<R: RngCore + CryptoRng>(
+    commitments: &[GroupAffine],
+    j: usize,
+    values: &[Scalar],
+    witnesses: &[GroupAffine],
+    rng: &mut R,
+) -> Result<bool> {
+    if values.len() != commitments.len() {
+        return Err(Error::LengthMismatch { expected: commitments.len(), got: values.len() });
+    }
+    if witnesses.len() != commitments.len() {
+        return Err(Error::LengthMismatch { expected: commitments.len(), got: witnesses.len() });
+    }
+    let h_j = value_generator(j)?;
+
+    let mut bases = Vec::with_capacity(2 * commitments.len() + 1);
+    let mut scalars = Vec::with_capacity(2 * commitments.len() + 1);
+    let mut value_sum = Scalar::zero();
+    for ((c, witness), v) in commitments.iter().zip(witnesses).zip(values) {
+        if validate_point(c).is_err() || validate_point(witness).is_err() {
+            return Ok(false);
+        }
+        let rho = Scalar::rand(rng);
+        bases.extend([*c, *witness]);
+        scalars.extend([rho, -rho]);
+        value_sum += rho * v;
+    }
+    bases.push(h_j);
+    scalars.push(-value_sum);
+    Ok(GroupProjective::msm_unchecked(&bases, &scalars).is_zero())
+}
+
+/// Parallel `batch_check_report`: items are verified across the rayon pool and
+/// collected in input order, so the output never depends on scheduling.
+#[cfg(feature = "parallel")]
+pub fn par_batch_check_report(checks: &[BatchCheckItem]) -> Vec<bool> {
+    use rayon::prelude::*;
+
+    checks
+        .par_iter()
+        .map(|&(c, values, witness, indices, len)| batch_check(c, values, witness, indices, len).unwrap_or(false))
+        .collect()
+}
+
+/// Parallel `batch_check_many`: true iff every item verifies, however the
+/// work happens to be scheduled.
+#[cfg(feature = "parallel")]
+pub fn par_batch_check(checks: &[BatchCheckItem]) -> bool {
+    use rayon::prelude::*;
+
+    checks
+        .par_iter()
+        .all(|&(c, values, witness, indices, len)| batch_check(c, values, witness, indices, len).unwrap_or(false))
+}
+
+/// Opening of a subset whose proof is a single group element; see `BatchOpening`.
+pub type AggregateOpening = BatchOpening;
+
+/// `batch_open`, kept under its earlier name.
+pub fn aggregate_open(v: &[Scalar], r: Scalar, indices: impl IntoIterator<Item = usize>) -> Result<AggregateOpening> {
+    batch_open(v, r, indices)
+}
+
+pub fn aggregate_check(c: GroupAffine, opening: &AggregateOpening) -> Result<bool> {
+    opening.verify(&c)
+}
+
+/// Merges single-index openings of `c` into one `BatchOpening`, indices in the
+/// order given.
+///
+/// The batch witness is `c - Σ [v_j]G_{j+1}`, computed from the commitment
+/// and the opened values alone. Any values give a witness that passes
+/// `batch_check` this way, so every input is verified first: one that fails is
+/// `InvalidOpening`, and openings of different lengths are `LengthMismatch`.
+pub fn aggregate_openings(c: &GroupAffine, openings: &[Opening]) -> Result<BatchOpening> {
+    let len = openings.first().ok_or(Error::EmptyIndices)?.len;
+    let indices: Vec<usize> = openings.iter().map(|o| o.index).collect();
+    let mut sorted_indices = indices.clone();
+    sorted_indices.sort_unstable();
+    if sorted_indices.windows(2).any(|w| w[0] == w[1]) {
+        return Err(Error::DuplicateIndices);
+    }
+
+    for opening in openings {
+        if opening.len != len {
+            return Err(Error::LengthMismatch { expected: len, got: opening.len });
+        }
+        if !opening.verify(c)? {
+            return Err(Error::InvalidOpening);
+        }
+    }
+
+    let values: Vec<Scalar> = openings.iter().map(|o| o.value).collect();
+    let bases = indices.iter().map(|&j| value_generator(j)).collect::<Result<Vec<_>>>()?;
+    let witness = c.into_group() - GroupProjective::msm_unchecked(&bases, &values);
+    Ok(BatchOpening { indices, values, witness: witness.into_affine(), len })
+}
+
+/// Commits each row of a matrix under the shared `POINTS`, with blinding `r[i]`
+/// for row `i`. All rows must have the same length and `r` one entry per row.
+pub fn commit_matrix(rows: &[Vec<Scalar>], r: &[Scalar]) -> Result<Vec<GroupAffine>> {
+    if r.len() != rows.len() {
+        return Err(Error::LengthMismatch { expected: rows.len(), got: r.len() });
+    }
+    if let Some(first) = rows.first() {
+        if let Some(row) = rows.iter().find(|row| row.len() != first.len()) {
+            return Err(Error::LengthMismatch { expected: first.len(), got: row.len() });
+        }
+    }
+    rows.iter().zip(r).map(|(row, r_i)| commit(row, *r_i)).collect()
+}
+
+pub fn open_cell(rows: &[Vec<Scalar>], r: &[Scalar], row: usize, col: usize) -> Result<CellOpening> {
+    let (v, r_i) = rows.get(row).zip(r.get(row)).ok_or(Error::IndexOutOfBounds)?;
+    let opening = open(v, *r_i, col)?;
+    Ok(CellOpening { row, col, value: opening.value, witness: opening.witness })
+}
+
+/// Verifies cell openings against the row commitments returned by `commit_matrix`.
+/// Errors if a cell points outside the matrix.
+pub fn batch_check_matrix(commitments: &[GroupAffine], cells: &[CellOpening]) -> Result<bool> {
+    for cell in cells {
+        let c = commitments.get(cell.row).ok_or(Error::IndexOutOfBounds)?;
+        if !cell.verify(c)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// `(values, r, witness)` from `batch_open_on`, in the order of the indices given.
+pub type CurveBatchOpening<C> = (Vec<<C as PcsCurve>::Scalar>, <C as PcsCurve>::Scalar, <C as PcsCurve>::Affine);
+
+/// `commit` over an arbitrary curve, e.g. `commit_on::<Bls12_381>(&v, r)`.
+pub fn commit_on<C: PcsCurve>(v: &[C::Scalar], r: C::Scalar) -> Result<C::Affine> {
+    commit_with_points::<C>(C::points(), v, r)
+}
+
+pub fn open_on<C: PcsCurve>(v: &[C::Scalar], r: C::Scalar, j: usize) -> Result<(C::Scalar, C::Scalar, C::Affine)> {
+    open_with_points::<C>(C::points(), v, r, j)
+}
+
+pub fn batch_open_on<C: PcsCurve>(v: &[C::Scalar], r: C::Scalar, indices: impl IntoIterator<Item = usize>) -> Result<CurveBatchOpening<C>> {
+    batch_open_with_points::<C>(C::points(), v, r, indices)
+}
+
+pub fn batch_check_on<C: PcsCurve>(c: C::Affine, values: &[C::Scalar], witness: C::Affine, indices: &[usize]) -> Result<bool> {
+    batch_check_with_points::<C>(C::points(), c, values, witness, indices)
+}
+
+pub fn check_on<C: PcsCurve>(c: C::Affine, v_j: C::Scalar, witness: C::Affine, h_j: C::Affine) -> bool {
+    if C::validate_point(&c).is_err() || C::validate_point(&witness).is_err() {
+        return false;
+    }
+    c.into_group() == witness + h_j * v_j
+}
+
+/// Core of `commit_on` over an explicit generator list (blinding base first).
+pub(crate) fn commit_with_points<C: PcsCurve>(points: &[C::Affine], v: &[C::Scalar], r: C::Scalar) -> Result<C::Affine> {
+    if v.len() != points.len() - 1 {
+        return Err(Error::LengthMismatch { expected: points.len() - 1, got: v.len() });
+    }
+
+    // One MSM over every generator, blinding base (`BLINDING_INDEX`, first) included
+    let mut scalars = Vec::with_capacity(points.len());
+    scalars.push(r);
+    scalars.extend_from_slice(v);
+    Ok(C::Projective::msm_unchecked(points, &scalars).into_affine())
+}
+
+/// `Σ [v_i] points[i]`, one scalar multiplication at a time. Reference for the
+/// MSM paths.
+pub fn fold_terms<C: PcsCurve>(points: &[C::Affine], v: &[C::Scalar]) -> C::Projective {
+    points
+        .iter()
+        .zip(v)
+        .map(|(p, v_i)| *p * v_i)
+        .fold(C::Projective::zero(), |acc, x| acc + x)
+}
+
+/// `[r]points[0] + Σ [v_i]points[i + 1]` over the `i` not `skip`ped, as one MSM.
+fn msm_except<C: PcsCurve>(points: &[C::Affine], v: &[C::Scalar], r: C::Scalar, skip: impl Fn(usize) -> bool) -> C::Projective {
+    let mut bases = Vec::with_capacity(points.len());
+    let mut scalars = Vec::with_capacity(points.len());
+    bases.push(points[BLINDING_INDEX]);
+    scalars.push(r);
+    for (_, (p, v_i)) in points[1..].iter().zip(v).enumerate().filter(|(i, _)| !skip(*i)) {
+        bases.push(*p);
+        scalars.push(*v_i);
+    }
+    C::Projective::msm_unchecked(&bases, &scalars)
+}
+
+pub(crate) fn open_all_with_points<C: PcsCurve>(points: &[C::Affine], v: &[C::Scalar], r: C::Scalar) -> Result<Vec<(C::Scalar, C::Affine)>> {
+    let c = commit_with_points::<C>(points, v, r)?.into_group();
+    #[cfg(feature = "parallel")]
+    let witnesses: Vec<C::Projective> = {
+        use rayon::prelude::*;
+        points[1..].par_iter().zip(v).map(|(p, v_j)| c - *p * v_j).collect()
+    };
+    #[cfg(not(feature = "parallel"))]
+    let witnesses: Vec<C::Projective> = points[1..].iter().zip(v).map(|(p, v_j)| c - *p * v_j).collect();
+    let witnesses = C::Projective::normalize_batch(&witnesses);
+    Ok(v.iter().copied().zip(witnesses).collect())
+}
+
+pub(crate) fn open_with_points<C: PcsCurve>(points: &[C::Affine], v: &[C::Scalar], r: C::Scalar, j: usize) -> Result<(C::Scalar, C::Scalar, C::Affine)> {
+    if j >= v.len() {
+        return Err(Error::IndexOutOfBounds);
+    }
+
+    let witness = msm_except::<C>(points, v, r, |i| i == j);
+    Ok((v[j], r, witness.into_affine()))
+}
+
+pub(crate) fn batch_open_with_points<C: PcsCurve>(points: &[C::Affine], v: &[C::Scalar], r: C::Scalar, indices: impl IntoIterator<Item = usize>) -> Result<CurveBatchOpening<C>> {
+    let indices: Vec<usize> = indices.into_iter().collect();
+    
+    // Validate indices
+    if indices.is_empty() {
+        return Err(Error::EmptyIndices);
+    }
+    
+    // Check for duplicates and bounds
+    let mut sorted_indices = indices.clone();
+    sorted_indices.sort_unstable();
+    if sorted_indices.windows(2).any(|w| w[0] == w[1]) {
+        return Err(Error::DuplicateIndices);
+    }
+    if sorted_indices.iter().any(|&i| i >= v.len()) {
+        return Err(Error::IndexOutOfBounds);
+    }
+    
+    let witness = msm_except::<C>(points, v, r, |i| sorted_indices.binary_search(&i).is_ok());
+
+    // Values in the caller's index order, so `(indices, values)` goes straight
+    // back into `batch_check`
+    let values: Vec<C::Scalar> = indices.iter().map(|&j| v[j]).collect();
+    Ok((values, r, witness.into_affine()))
+}
+
+pub(crate) fn batch_check_with_points<C: PcsCurve>(points: &[C::Affine], c: C::Affine, values: &[C::Scalar], witness: C::Affine, indices: &[usize]) -> Result<bool> {
+    // Indices come from the prover; `points[j + 1]` below must never panic
+    if indices.iter().any(|&j| j >= points.len() - 1) {
+        return Err(Error::IndexOutOfBounds);
+    }
+
+    C::validate_point(&c)?;
+    C::validate_point(&witness)?;
+
+    if values.len() != indices.len() {
+        return Err(Error::ValuesIndicesMismatch);
+    }
+    
+    // Any order is fine, since each value is paired with its own generator
+    // below; an index listed twice would claim two values for one slot
+    let mut sorted_indices = indices.to_vec();
+    sorted_indices.sort_unstable();
+    if sorted_indices.windows(2).any(|w| w[0] == w[1]) {
+        return Err(Error::DuplicateIndices);
+    }
+
+
+    // c - witness == Σ [v_j] G_{j+1}, as one multi-scalar multiplication.
+    //
+    // Soundness: a witness produced for c1 cannot pass against c2 != c1, since
+    // both equations would give c1 - c2 = 0. A different value vector for the
+    // same indices would need a nontrivial combination of `points` summing to
+    // the identity, i.e. a discrete-log relation between setup generators.
+    // The equation alone does not bind the witness to the unopened slots,
+    // though: whoever picks the witness can take `c - Σ [y_j] G_{j+1}` for any
+    // `y`. `sigma::prove_batch_opening` adds a proof that the witness avoids
+    // the opened generators, for verifiers that do not trust the opener.
+    let bases: Vec<C::Affine> = indices.iter().map(|&j| points[j + 1]).collect();
+    let sum = C::Projective::msm_unchecked(&bases, values);
+
+    Ok(c.into_group() - witness == sum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::{Bls12_381, Bn254};
+    use ark_ff::{Field, UniformRand};
+    use rand::thread_rng;
+
+    #[test]
+    fn test_commit_and_verify() {
+        let mut rng = thread_rng();
+        let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+        let r = Scalar::rand(&mut rng);
+        
+        let commitment = commit(&v, r).unwrap();
+        let j = 0;
+        let Opening { value: v_j, witness, .. } = open(&v, r, j).unwrap();
+        
+        assert!(check(commitment, j, v_j, witness).unwrap());
+    }
+
+    #[test]
+    fn test_check_derives_generator() {
+        let mut rng = thread_rng();
+        let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+        let r = Scalar::rand(&mut rng);
+        let c = commit(&v, r).unwrap();
+        let Opening { value: v_j, witness, .. } = open(&v, r, 4).unwrap();
+
+        // The generator comes from the index, so the old `POINTS[j]` slip can
+        // only be spelled as the wrong index, which fails
+        assert!(check(c, 4, v_j, witness).unwrap());
+        assert!(!check(c, 3, v_j, witness).unwrap());
+        assert!(!check_unchecked(c, v_j, witness, POINTS[4]));
+
+        // No index reaches the blinding base
+        assert_eq!(value_generator(0), Ok(POINTS[1]));
+        assert_eq!(check(c, usize::MAX, v_j, witness), Err(Error::IndexOutOfBounds));
+        assert_eq!(check(c, max_vector_len(), v_j, witness), Err(Error::IndexOutOfBounds));
+        assert!((0..NUM_POINTS - 1).all(|j| value_generator(j).unwrap() != POINTS[BLINDING_INDEX]));
+
+        assert!(check_unchecked(c, v_j, witness, POINTS[5]));
+    }
+
+    #[test]
+    fn test_batch_open() {
+        let mut rng = thread_rng();
+        let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+        let r = Scalar::rand(&mut rng);
+        let commitment = commit(&v, r).unwrap();
+
+        let indices = vec![0, 2, 4];
+        let BatchOpening { values, witness, .. } = batch_open(&v, r, indices.clone()).unwrap();
+        
+        assert!(batch_check(commitment, &values, witness, &indices, NUM_POINTS - 1).unwrap());
+    }
+
+    #[test]
+    fn test_batch_open_edge_cases() {
+        let mut rng = thread_rng();
+        let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+        let r = Scalar::rand(&mut rng);
+        let commitment = commit(&v, r).unwrap();
+
+        // Test with all indices
+        let all_indices: Vec<usize> = (0..v.len()).collect();
+        let BatchOpening { values, witness, .. } = batch_open(&v, r, all_indices.clone()).unwrap();
+        assert!(batch_check(commitment, &values, witness, &all_indices, NUM_POINTS - 1).unwrap());
+
+        // Test with empty indices (should fail)
+        assert!(batch_open(&v, r, Vec::<usize>::new()).is_err());
+
+        // Test with duplicate indices (should fail)
+        let duplicate_indices = vec![0, 0, 2];
+        assert!(batch_open(&v, r, duplicate_indices).is_err());
+
+        // Test with out of bounds index (should fail)
+        let out_of_bounds = vec![0, v.len()];
+        assert!(batch_open(&v, r, out_of_bounds).is_err());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_commit_msm_threshold() {
+        let mut rng = thread_rng();
+        let r = Scalar::rand(&mut rng);
+        for n in [1, MSM_THRESHOLD - 1, MSM_THRESHOLD, MSM_THRESHOLD + 1] {
+            let points = &POINTS[..n + 1];
+            let v: Vec<Scalar> = (0..n).map(|_| Scalar::rand(&mut rng)).collect();
+            let naive = (points[BLINDING_INDEX] * r + fold_terms::<Bls12_377>(&points[1..], &v)).into_affine();
+            let msm = (points[BLINDING_INDEX] * r + GroupProjective::msm_unchecked(&points[1..], &v)).into_affine();
+            assert_eq!(naive, msm);
+            assert_eq!(commit_with_points::<Bls12_377>(points, &v, r).unwrap(), naive);
+        }
+    }
+
+    #[cfg(not(feature = "verify-only"))]
+    #[test]
+    fn test_commit_msm_matches_fold() {
+        let mut rng = thread_rng();
+        let r = Scalar::rand(&mut rng);
+        for n in [1, 7, 8, 9, NUM_POINTS - 1, 400] {
+            let points = generators(n + 1).unwrap();
+            let v: Vec<Scalar> = (0..n).map(|_| Scalar::rand(&mut rng)).collect();
+
+            let naive = (points[BLINDING_INDEX] * r + fold_terms::<Bls12_377>(&points[1..], &v)).into_affine();
+            assert_eq!(commit(&v, r).unwrap(), naive);
+
+            // Witnesses against the same fold over the complement
+            let j = n / 2;
+            let naive_witness = (naive.into_group() - points[j + 1] * v[j]).into_affine();
+            assert_eq!(open(&v, r, j).unwrap().witness, naive_witness);
+            assert_eq!(batch_open(&v, r, [j]).unwrap().witness, naive_witness);
+        }
+    }
+
+    // Runs with and without `parallel`; both must agree with a serial fold.
+    #[cfg(not(feature = "verify-only"))]
+    #[test]
+    fn test_parallel_matches_serial() {
+        let mut rng = thread_rng();
+        let r = Scalar::rand(&mut rng);
+        let n = 1000;
+        let points = generators(n + 1).unwrap();
+        let v: Vec<Scalar> = (0..n).map(|_| Scalar::rand(&mut rng)).collect();
+        let serial = points[1..]
+            .iter()
+            .zip(&v)
+            .fold(points[BLINDING_INDEX] * r, |acc, (p, v_i)| acc + *p * v_i);
+
+        let c = commit(&v, r).unwrap();
+        assert_eq!(c, serial.into_affine());
+
+        let openings = open_all(&v, r).unwrap();
+        for (j, opening) in openings.iter().enumerate() {
+            assert_eq!(opening.witness, (serial - points[j + 1] * v[j]).into_affine());
+        }
+
+        let indices: Vec<usize> = (0..n).step_by(3).collect();
+        let batch = batch_open(&v, r, indices.iter().copied()).unwrap();
+        let opened = indices.iter().fold(serial, |acc, &j| acc - points[j + 1] * v[j]);
+        assert_eq!(batch.witness, opened.into_affine());
+        assert!(batch.verify(&c).unwrap());
+    }
+
+    #[cfg(feature = "verify-only")]
+    #[test]
+    fn test_embedded_generators() {
+        assert_eq!(POINTS.to_vec(), derive_points(&TRUSTED_SETUP, DEFAULT_DST, NUM_POINTS).unwrap());
+        assert!(POINTS.iter().all(|p| validate_point(p).is_ok()));
+
+        let mut rng = thread_rng();
+        let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+        let r = Scalar::rand(&mut rng);
+        let c = commit(&v, r).unwrap();
+        let Opening { value: v_j, witness, .. } = open(&v, r, 5).unwrap();
+        assert!(check(c, 5, v_j, witness).unwrap());
+        let BatchOpening { values, witness, .. } = batch_open(&v, r, [0, 5, 19]).unwrap();
+        assert!(batch_check(c, &values, witness, &[0, 5, 19], NUM_POINTS - 1).unwrap());
+
+        // Generators past `POINTS` would come from the setup, so they are out of range
+        assert_eq!(max_vector_len(), default_vector_len());
+        assert_eq!(generator(NUM_POINTS), Err(Error::IndexOutOfBounds));
+        assert_eq!(check(c, NUM_POINTS - 1, v_j, witness), Err(Error::IndexOutOfBounds));
+        assert!(commit(&[v.as_slice(), &[Scalar::zero()]].concat(), r).is_err());
+        assert_eq!(batch_check(c, &values, witness, &[0, 5, 19], NUM_POINTS), Err(Error::SetupTooShort { expected: NUM_POINTS + 1, got: NUM_POINTS }));
+    }
+
+    #[test]
+    fn test_commit_padded() {
+        let mut rng = thread_rng();
+        let r = Scalar::rand(&mut rng);
+        let v: Vec<Scalar> = (0..10).map(|_| Scalar::rand(&mut rng)).collect();
+        let mut padded = v.clone();
+        padded.resize(NUM_POINTS - 1, Scalar::zero());
+
+        assert_eq!(commit_padded(&v, r).unwrap(), commit(&padded, r).unwrap());
+        assert_eq!(commit_padded(&padded, r).unwrap(), commit(&padded, r).unwrap());
+        assert_eq!(commit_padded(&[], r).unwrap(), commit(&vec![Scalar::zero(); NUM_POINTS - 1], r).unwrap());
+
+        padded.push(Scalar::zero());
+        assert_eq!(commit_padded(&padded, r), Err(Error::LengthMismatch { expected: NUM_POINTS - 1, got: NUM_POINTS }));
+    }
+
+    #[test]
+    fn test_commit_deterministic() {
+        let mut rng = thread_rng();
+        let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+        let seed = b"pipeline seed";
+
+        let (c1, r1) = commit_deterministic(&v, seed, b"shard-0").unwrap();
+        let (c2, r2) = commit_deterministic(&v, seed, b"shard-0").unwrap();
+        let (mut b1, mut b2) = (Vec::new(), Vec::new());
+        (c1, r1).serialize_compressed(&mut b1).unwrap();
+        (c2, r2).serialize_compressed(&mut b2).unwrap();
+        assert_eq!(b1, b2);
+        assert_eq!(c1, commit(&v, r1).unwrap());
+        assert!(open(&v, r1, 3).unwrap().verify(&c1).unwrap());
+
+        // Another context or seed gives an unrelated blinding
+        let (c3, r3) = commit_deterministic(&v, seed, b"shard-1").unwrap();
+        assert_ne!((c3, r3), (c1, r1));
+        assert_ne!(commit_deterministic(&v, b"other seed", b"shard-0").unwrap().1, r1);
+        assert_ne!(derive_blinding(b"ab", b"c"), derive_blinding(b"a", b"bc"));
+    }
+
+    #[test]
+    fn test_u64_values() {
+        let mut rng = thread_rng();
+        let mut values: Vec<u64> = (0..NUM_POINTS - 1).map(|_| rng.next_u64()).collect();
+        values[..3].copy_from_slice(&[0, 1, u64::MAX]);
+        let r = Scalar::rand(&mut rng);
+        let c = commit_u64s(&values, r).unwrap();
+
+        for (j, &x) in values.iter().enumerate() {
+            let opening = open_u64(&values, r, j).unwrap();
+            assert_eq!(scalar_to_u64(&opening.value), Ok(x));
+            assert!(check_u64(c, j, x, opening.witness).unwrap());
+        }
+        assert!(!check_u64(c, 2, u64::MAX - 1, open_u64(&values, r, 2).unwrap().witness).unwrap());
+
+        // 2^64 and above, and -1, would truncate to small integers
+        let two_64 = Scalar::from(u64::MAX) + Scalar::ONE;
+        assert_eq!(scalar_to_u64(&two_64), Err(Error::ValueOutOfRange));
+        assert_eq!(scalar_to_u64(&(two_64 + Scalar::from(5u64))), Err(Error::ValueOutOfRange));
+        assert_eq!(scalar_to_u64(&-Scalar::ONE), Err(Error::ValueOutOfRange));
+
+        let mut v: Vec<Scalar> = values.iter().map(|&x| Scalar::from(x)).collect();
+        v[4] = two_64 + Scalar::from(5u64);
+        let opening = open(&v, r, 4).unwrap();
+        assert!(opening.verify(&commit(&v, r).unwrap()).unwrap());
+        assert_eq!(scalar_to_u64(&opening.value), Err(Error::ValueOutOfRange));
+    }
+
+    #[cfg(not(feature = "verify-only"))]
+    #[test]
+    fn test_open_all() {
+        let mut rng = thread_rng();
+        let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+        let r = Scalar::rand(&mut rng);
+        let commitment = commit(&v, r).unwrap();
+
+        let openings = open_all(&v, r).unwrap();
+        assert_eq!(openings.len(), v.len());
+        for (j, opening) in openings.iter().enumerate() {
+            assert_eq!((opening.index, opening.value), (j, v[j]));
+            assert!(check(commitment, j, opening.value, opening.witness).unwrap());
+            assert!(opening.verify(&commitment).unwrap());
+        }
+        for j in [0, 11, v.len() - 1] {
+            assert_eq!(openings[j], open(&v, r, j).unwrap());
+        }
+
+        // Longer than `POINTS`, and a tampered witness
+        let long: Vec<Scalar> = (0..45).map(|_| Scalar::rand(&mut rng)).collect();
+        let c = commit(&long, r).unwrap();
+        let mut openings = open_all(&long, r).unwrap();
+        assert!(openings.iter().all(|o| o.verify(&c).unwrap()));
+        assert_eq!(openings[40], open(&long, r, 40).unwrap());
+        openings[7].witness = openings[8].witness;
+        assert!(!openings[7].verify(&c).unwrap());
+    }
+
+    #[test]
+    fn test_reconstruct() {
+        let mut rng = thread_rng();
+        let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+        let r = Scalar::rand(&mut rng);
+
+        let mut openings: Vec<(usize, Scalar)> =
+            open_all(&v, r).unwrap().into_iter().map(|o| (o.index, o.value)).collect();
+        assert_eq!(reconstruct(&openings).unwrap(), v);
+
+        openings.reverse();
+        assert_eq!(reconstruct(&openings).unwrap(), v);
+
+        let missing: Vec<(usize, Scalar)> = openings.iter().copied().filter(|&(j, _)| j != 3).collect();
+        assert_eq!(reconstruct(&missing), Err(Error::IndexOutOfBounds));
+
+        let mut duplicate = missing.clone();
+        duplicate.push(duplicate[0]);
+        assert_eq!(reconstruct(&duplicate), Err(Error::DuplicateIndices));
+
+        assert_eq!(reconstruct(&[]), Err(Error::EmptyIndices));
+    }
+
+    /// The term-by-term fold `batch_check` used before switching to MSM.
+    fn batch_check_naive(c: GroupAffine, values: &[Scalar], witness: GroupAffine, indices: &[usize]) -> bool {
+        let sum = values.iter().zip(indices).fold(witness.into_group(), |acc, (v_j, &j)| {
+            acc + POINTS[j + 1] * *v_j
+        });
+        c == sum.into_affine()
+    }
+
+    #[test]
+    fn test_batch_check_msm_matches_naive() {
+        use rand::seq::index::sample;
+        use rand::Rng;
+
+        let mut rng = thread_rng();
+        let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+        let r = Scalar::rand(&mut rng);
+        let commitment = commit(&v, r).unwrap();
+
+        for _ in 0..20 {
+            let size = rng.gen_range(1..=v.len());
+            let mut indices = sample(&mut rng, v.len(), size).into_vec();
+            indices.sort_unstable();
+            let BatchOpening { mut values, witness, .. } = batch_open(&v, r, indices.clone()).unwrap();
+            assert!(batch_check(commitment, &values, witness, &indices, NUM_POINTS - 1).unwrap());
+            assert!(batch_check_naive(commitment, &values, witness, &indices));
 
-include!(concat!(env!("OUT_DIR"), "/trusted_setup.rs"));
+            values[0] += Scalar::from(1u64);
+            assert!(!batch_check(commitment, &values, witness, &indices, NUM_POINTS - 1).unwrap());
+            assert!(!batch_check_naive(commitment, &values, witness, &indices));
+        }
+    }
 
-lazy_static::lazy_static! {
-    pub static ref POINTS: [GroupAffine; NUM_POINTS] = TRUSTED_SETUP[..NUM_POINTS]
-        .iter()
-        .map(|line| hash_message(line))
-        .collect::<Vec<_>>()
-        .try_into()
-        .unwrap();
-}
+    #[test]
+    fn test_batch_check_rejects_reordering() {
+        let mut rng = thread_rng();
+        let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+        let r = Scalar::rand(&mut rng);
+        let commitment = commit(&v, r).unwrap();
 
-fn hash_message(message: &str) -> GroupAffine {
-    MapToCurveBasedHasher::<GroupProjective, DefaultFieldHasher<Sha256>, WBMap<Config>>::new(b"BLS12377G1_XMD:SHA-256_SSWU_RO_")
-        .unwrap()
-        .hash(message.as_bytes())
-        .unwrap()
-        .into()
-}
+        let indices = vec![1, 4, 9];
+        let BatchOpening { values, witness, .. } = batch_open(&v, r, indices.clone()).unwrap();
+        assert!(batch_check(commitment, &values, witness, &indices, NUM_POINTS - 1).unwrap());
 
-pub fn commit(v: &[Scalar], r: Scalar) -> Result<GroupAffine> {
-    if v.len() != POINTS.len() - 1 {
-        return Err(anyhow!("POINTS must have {} entries, got {}", POINTS.len() - 1, v.len()));
+        // Values permuted against the same indices must not verify
+        let mut reversed = values.clone();
+        reversed.reverse();
+        assert!(!batch_check(commitment, &reversed, witness, &indices, NUM_POINTS - 1).unwrap());
+        let mut swapped = values.clone();
+        swapped.swap(0, 1);
+        assert!(!batch_check(commitment, &swapped, witness, &indices, NUM_POINTS - 1).unwrap());
+
+        // Reordering indices and values together still verifies
+        assert!(batch_check(commitment, &[values[1], values[0], values[2]], witness, &[4, 1, 9], NUM_POINTS - 1).unwrap());
+        assert!(!batch_check(commitment, &values, witness, &[4, 1, 9], NUM_POINTS - 1).unwrap());
     }
 
-    let blind = POINTS[BLINDING_INDEX] * r;
-    let h = POINTS[1..]
-        .iter()
-        .zip(v)
-        .map(|(p, v_i)| *p * v_i)
-        .fold(GroupProjective::default(), |acc, x| acc + x);
+    #[test]
+    fn test_batch_open_unsorted_indices() {
+        let mut rng = thread_rng();
+        let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+        let r = Scalar::rand(&mut rng);
+        let c = commit(&v, r).unwrap();
 
-    Ok((blind + h).into_affine())
-}
+        let indices = [4, 0, 2];
+        let opening = batch_open(&v, r, indices).unwrap();
+        assert_eq!(opening.indices, indices);
+        assert_eq!(opening.values, vec![v[4], v[0], v[2]]);
+        assert!(batch_check(c, &opening.values, opening.witness, &indices, NUM_POINTS - 1).unwrap());
+        assert_eq!(opening.witness, batch_open(&v, r, [0, 2, 4]).unwrap().witness);
 
-pub fn open(v: &[Scalar], r: Scalar, j: usize) -> Result<(Scalar, Scalar, GroupAffine)> {
-    if j >= v.len() {
-        return Err(anyhow!("Index out of bounds"));
+        assert_eq!(
+            batch_check(c, &[v[4], v[0], v[4]], opening.witness, &[4, 0, 4], NUM_POINTS - 1),
+            Err(Error::DuplicateIndices)
+        );
+        assert_eq!(batch_open(&v, r, [4, 0, 4]).unwrap_err(), Error::DuplicateIndices);
+        assert_eq!(
+            batch_check(c, &opening.values, opening.witness, &[4, NUM_POINTS - 1, 2], NUM_POINTS - 1),
+            Err(Error::IndexOutOfBounds)
+        );
     }
 
-    let blind = POINTS[BLINDING_INDEX] * r;
-    let witness = POINTS[1..].iter().enumerate()
-        .filter(|(i, _)| *i != j)
-        .map(|(i, p)| *p * v[i])
-        .sum::<GroupProjective>() + blind;
-    
-    Ok((v[j], r, witness.into_affine()))
-}
+    #[cfg(not(feature = "verify-only"))]
+    #[test]
+    fn test_open_range() {
+        let mut rng = thread_rng();
+        let v: Vec<Scalar> = (0..300).map(|_| Scalar::rand(&mut rng)).collect();
+        let r = Scalar::rand(&mut rng);
+        let c = commit(&v, r).unwrap();
 
-pub fn check(c: GroupAffine, v_j: Scalar, witness: GroupAffine, h_j: GroupAffine) -> bool {
-    c == witness + h_j * v_j
-}
+        for range in [0..1, 100..132, 280..300, 0..300] {
+            let opening = open_range(&v, r, range.clone()).unwrap();
+            assert_eq!(opening, batch_open(&v, r, range.clone()).unwrap());
+            assert!(check_range(c, &opening.values, opening.witness, range.clone()).unwrap());
+            assert!(batch_check(c, &opening.values, opening.witness, &opening.indices, v.len()).unwrap());
+        }
 
-pub fn batch_open(v: &[Scalar], r: Scalar, indices: impl IntoIterator<Item = usize>) -> Result<(Vec<Scalar>, Scalar, GroupAffine)> {
-    let indices: Vec<usize> = indices.into_iter().collect();
-    
-    // Validate indices
-    if indices.is_empty() {
-        return Err(anyhow!("Empty indices"));
+        let opening = open_range(&v, r, 100..132).unwrap();
+        assert!(!check_range(c, &opening.values, opening.witness, 101..133).unwrap());
+        let mut values = opening.values.clone();
+        values[31] += Scalar::ONE;
+        assert!(!check_range(c, &values, opening.witness, 100..132).unwrap());
+        assert_eq!(check_range(c, &values[1..], opening.witness, 100..132), Err(Error::ValuesIndicesMismatch));
+
+        assert_eq!(open_range(&v, r, 5..5), Err(Error::EmptyIndices));
+        assert_eq!(open_range(&v, r, 290..301), Err(Error::IndexOutOfBounds));
+        assert_eq!(check_range(c, &[], opening.witness, 7..7), Err(Error::EmptyIndices));
     }
-    
-    // Check for duplicates and bounds
-    let mut sorted_indices = indices.clone();
-    sorted_indices.sort_unstable();
-    if sorted_indices.windows(2).any(|w| w[0] == w[1]) {
-        return Err(anyhow!("Duplicate indices not allowed"));
+
+    #[test]
+    fn test_aggregate_open_subsets() {
+        let mut rng = thread_rng();
+        let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+        let r = Scalar::rand(&mut rng);
+        let commitment = commit(&v, r).unwrap();
+
+        let subsets: [&[usize]; 4] = [&[5, 1, 3], &[3, 5, 7], &[0, 2], &[4, 6]];
+        let openings: Vec<_> = subsets.iter().map(|s| aggregate_open(&v, r, s.iter().copied()).unwrap()).collect();
+        for opening in &openings {
+            assert!(aggregate_check(commitment, opening).unwrap());
+        }
+        assert_eq!(openings[0].indices, vec![5, 1, 3]);
+
+        // Witnesses of overlapping or disjoint subsets are not interchangeable
+        for (a, b) in [(0, 1), (2, 3)] {
+            let mixed = AggregateOpening {
+                indices: openings[a].indices.clone(),
+                values: openings[a].values.clone(),
+                witness: openings[b].witness,
+                len: v.len(),
+            };
+            assert!(!aggregate_check(commitment, &mixed).unwrap());
+        }
     }
-    if sorted_indices.iter().any(|&i| i >= v.len()) {
-        return Err(anyhow!("Index out of bounds"));
+
+    #[test]
+    fn test_aggregate_openings() {
+        let mut rng = thread_rng();
+        let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+        let r = Scalar::rand(&mut rng);
+        let c = commit(&v, r).unwrap();
+
+        let singles: Vec<Opening> = [1, 3, 7].iter().map(|&j| open(&v, r, j).unwrap()).collect();
+        let merged = aggregate_openings(&c, &singles).unwrap();
+        assert_eq!(merged, batch_open(&v, r, [1, 3, 7]).unwrap());
+        assert!(batch_check(c, &merged.values, merged.witness, &merged.indices, v.len()).unwrap());
+
+        // A forged input, and honest ones claimed for another commitment
+        let mut forged = singles.clone();
+        forged[1].value += Scalar::from(1u64);
+        assert_eq!(aggregate_openings(&c, &forged), Err(Error::InvalidOpening));
+        let other = commit(&v, r + Scalar::from(1u64)).unwrap();
+        assert_eq!(aggregate_openings(&other, &singles), Err(Error::InvalidOpening));
+
+        let mut twice = singles.clone();
+        twice.push(singles[0].clone());
+        assert_eq!(aggregate_openings(&c, &twice), Err(Error::DuplicateIndices));
+        assert_eq!(aggregate_openings(&c, &[]), Err(Error::EmptyIndices));
+        let mut other_len = singles.clone();
+        other_len[2].len = 30;
+        assert_eq!(aggregate_openings(&c, &other_len), Err(Error::LengthMismatch { expected: 20, got: 30 }));
     }
-    
-    let blind = POINTS[BLINDING_INDEX] * r;
-    let witness = POINTS[1..].iter().enumerate()
-        .filter(|(i, _)| !sorted_indices.contains(i))
-        .map(|(i, p)| *p * v[i])
-        .sum::<GroupProjective>() + blind;
-    
-    let values: Vec<Scalar> = sorted_indices.iter().map(|&j| v[j]).collect();
-    Ok((values, r, witness.into_affine()))
-}
 
-pub fn batch_check(c: GroupAffine, values: &[Scalar], witness: GroupAffine, indices: &[usize]) -> Result<bool> {
-    if values.len() != indices.len() {
-        return Err(anyhow!("values and indices must match"));
+    #[cfg(not(feature = "verify-only"))]
+    #[test]
+    fn test_export_generators_roundtrip() {
+        let mut bytes = Vec::new();
+        export_generators(&mut bytes).unwrap();
+        let imported = import_generators(&bytes[..]).unwrap();
+        assert_eq!(imported.dst, DEFAULT_DST);
+        assert_eq!(imported.points, default_generators());
+        assert_eq!(imported.points[BLINDING_INDEX], blinding_generator());
+        assert_eq!(generator(5).unwrap(), default_generators()[5]);
+
+        let mut bytes = Vec::new();
+        export_generator_prefix(&mut bytes, NUM_POINTS + 3).unwrap();
+        assert_eq!(import_generators(&bytes[..]).unwrap().points, generators(NUM_POINTS + 3).unwrap());
+        assert!(export_generator_prefix(Vec::new(), max_vector_len() + 2).is_err());
     }
-    
-    // Verify indices are sorted and unique
-    if indices.windows(2).any(|w| w[0] >= w[1]) {
-        return Err(anyhow!("indices must be sorted and unique"));
+
+    #[test]
+    fn test_export_generators_format() {
+        let mut bytes = Vec::new();
+        export_generators(&mut bytes).unwrap();
+
+        // Pinned header: version, DST length and DST, generator count
+        let mut header = vec![1u8, 31, 0, 0, 0];
+        header.extend_from_slice(b"BLS12377G1_XMD:SHA-256_SSWU_RO_");
+        header.extend_from_slice(&[21, 0, 0, 0]);
+        assert_eq!(&bytes[..header.len()], &header[..]);
+        assert_eq!(bytes.len(), header.len() + NUM_POINTS * 48);
+
+        // Pinned points: the first generators, blinding base first, as in test_vectors.txt
+        let points: String = bytes[header.len()..header.len() + 3 * 48].iter().map(|b| format!("{b:02x}")).collect();
+        assert_eq!(
+            points,
+            concat!(
+                "f2008fd3ad14e117a259a50e352c7cfbf9d08a8bd9d722dcd1eef5359ea15d814b2ef1d0a0d65e2aa2722d9ea11bd780",
+                "50ddb4a83c9a776837fa16ba1dd6b08374f6a32d920dc0ac6d025961e172e47ea86f160c31d7d513d33c2b69e1f90981",
+                "73b849d9a6bfa88b1a1d9eba45aa2c2e6cf4f9dfda0d565d21e56e6f95412cb8717538ba12927db4122ffea8f7209701",
+            )
+        );
+
+        assert_eq!(import_generators(&bytes[..bytes.len() - 1]), Err(Error::MalformedEncoding));
+        assert_eq!(import_generators(&bytes[..3]), Err(Error::MalformedEncoding));
+        let mut future = bytes.clone();
+        future[0] = 2;
+        assert_eq!(import_generators(&future[..]), Err(Error::UnsupportedVersion(2)));
+        let mut identity = bytes.clone();
+        let start = header.len();
+        identity[start..start + 48].copy_from_slice(&{
+            let mut buf = Vec::new();
+            GroupAffine::zero().serialize_compressed(&mut buf).unwrap();
+            buf
+        });
+        assert_eq!(import_generators(&identity[..]), Err(Error::IdentityPoint));
     }
-    
-    let sum = values.iter().zip(indices).fold(GroupProjective::from(witness), |acc, (v_j, &j)| {
-        acc + POINTS[j + 1] * *v_j
-    });
-    
-    Ok(c == sum.into_affine())
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use ark_ff::{Field, UniformRand};
-    use rand::thread_rng;
+    #[test]
+    fn test_verify_same_index_batch() {
+        let mut rng = thread_rng();
+        let (n, j) = (300, 4);
+        let mut commitments = Vec::with_capacity(n);
+        let mut values = Vec::with_capacity(n);
+        let mut witnesses = Vec::with_capacity(n);
+        for _ in 0..n {
+            let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+            let r = Scalar::rand(&mut rng);
+            let opening = open(&v, r, j).unwrap();
+            commitments.push(commit(&v, r).unwrap());
+            values.push(opening.value);
+            witnesses.push(opening.witness);
+        }
+
+        for ((c, v), w) in commitments.iter().zip(&values).zip(&witnesses) {
+            assert!(check(*c, j, *v, *w).unwrap());
+        }
+        assert!(verify_same_index_batch(&commitments, j, &values, &witnesses, &mut rng).unwrap());
+
+        // One bad value or witness fails the batch
+        let mut bad = values.clone();
+        bad[n / 2] += Scalar::from(1u64);
+        assert!(!verify_same_index_batch(&commitments, j, &bad, &witnesses, &mut rng).unwrap());
+        let mut bad = witnesses.clone();
+        bad.swap(0, 1);
+        assert!(!verify_same_index_batch(&commitments, j, &values, &bad, &mut rng).unwrap());
+        assert!(!verify_same_index_batch(&commitments, j + 1, &values, &witnesses, &mut rng).unwrap());
+
+        assert!(verify_same_index_batch(&commitments, j, &values[1..], &witnesses, &mut rng).is_err());
+        assert!(verify_same_index_batch(&commitments, j, &values, &witnesses[1..], &mut rng).is_err());
+        assert!(verify_same_index_batch(&commitments, max_vector_len(), &values, &witnesses, &mut rng).is_err());
+        assert!(verify_same_index_batch(&[], j, &[], &[], &mut rng).unwrap());
+    }
 
     #[test]
-    fn test_commit_and_verify() {
+    fn test_verify_openings_batch() {
+        let mut rng = thread_rng();
+        let vectors: Vec<(Vec<Scalar>, Scalar)> = (0..3)
+            .map(|_| ((0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect(), Scalar::rand(&mut rng)))
+            .collect();
+        let c = commit(&vectors[0].0, vectors[0].1).unwrap();
+        let items: Vec<OpeningItem> = open_all(&vectors[0].0, vectors[0].1)
+            .unwrap()
+            .into_iter()
+            .map(|o| (c, o.index, o.value, o.witness))
+            .collect();
+        assert!(verify_openings_batch(&items, &mut rng).unwrap());
+        assert!(verify_openings_batch(&[], &mut rng).unwrap());
+
+        // Exactly one corrupted value
+        for k in [0, 7, items.len() - 1] {
+            let mut corrupted = items.clone();
+            corrupted[k].2 += Scalar::from(1u64);
+            assert!(!verify_openings_batch(&corrupted, &mut rng).unwrap());
+            let failing: Vec<usize> = (0..corrupted.len())
+                .filter(|&i| {
+                    let (c, j, v_j, witness) = corrupted[i];
+                    !check(c, j, v_j, witness).unwrap()
+                })
+                .collect();
+            assert_eq!(failing, vec![k]);
+        }
+
+        // Openings of different commitments, some at the same index
+        let mut mixed = Vec::new();
+        for (v, r) in &vectors {
+            let c = commit(v, *r).unwrap();
+            for j in [2, 5, 11] {
+                let opening = open(v, *r, j).unwrap();
+                mixed.push((c, j, opening.value, opening.witness));
+            }
+        }
+        assert!(verify_openings_batch(&mixed, &mut rng).unwrap());
+        // A witness of one commitment presented against another
+        let mut swapped = mixed.clone();
+        swapped[0].0 = mixed[3].0;
+        assert!(!verify_openings_batch(&swapped, &mut rng).unwrap());
+        // Two false items whose errors would cancel under equal weights
+        let mut cancelling = mixed.clone();
+        cancelling[1].2 += Scalar::from(1u64);
+        cancelling[4].2 -= Scalar::from(1u64);
+        assert!(!verify_openings_batch(&cancelling, &mut rng).unwrap());
+
+        let mut bad = mixed.clone();
+        bad[0].3 = GroupAffine::zero();
+        assert!(!verify_openings_batch(&bad, &mut rng).unwrap());
+        bad[0].1 = max_vector_len();
+        assert_eq!(verify_openings_batch(&bad, &mut rng), Err(Error::IndexOutOfBounds));
+    }
+
+    #[test]
+    fn test_batch_check_report() {
+        let mut rng = thread_rng();
+        let mut openings = Vec::new();
+        for _ in 0..4 {
+            let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+            let r = Scalar::rand(&mut rng);
+            let commitment = commit(&v, r).unwrap();
+            let indices = vec![1, 3, 5];
+            let BatchOpening { values, witness, .. } = batch_open(&v, r, indices.clone()).unwrap();
+            openings.push((commitment, values, witness, indices));
+        }
+        // Corrupt a value in item 1 and swap the witness of item 3 for item 0's
+        openings[1].1[0] += Scalar::from(1u64);
+        openings[3].2 = openings[0].2;
+
+        let checks: Vec<_> = openings
+            .iter()
+            .map(|(c, values, witness, indices)| (*c, &values[..], *witness, &indices[..], NUM_POINTS - 1))
+            .collect();
+        assert_eq!(batch_check_report(&checks), vec![true, false, true, false]);
+        assert!(!batch_check_many(&checks));
+        assert!(batch_check_many(&[checks[0], checks[2]]));
+    }
+
+    #[test]
+    fn test_commit_scalar() {
         let mut rng = thread_rng();
-        let v: Vec<Scalar> = (0..NUM_POINTS-1).map(|_| Scalar::rand(&mut rng)).collect();
+        let x = Scalar::rand(&mut rng);
+        let r = Scalar::rand(&mut rng);
+
+        let mut v = vec![Scalar::zero(); NUM_POINTS-1];
+        v[0] = x;
+        let c = commit_scalar(x, r);
+        assert_eq!(c, commit(&v, r).unwrap());
+
+        let (x_open, _, witness) = open_scalar(x, r);
+        let opening = open(&v, r, 0).unwrap();
+        assert_eq!((x_open, witness), (opening.value, opening.witness));
+        assert!(check_scalar(c, x_open, witness));
+        assert!(!check_scalar(c, x + Scalar::from(1u64), witness));
+    }
+
+    #[test]
+    fn test_commit_bytes() {
+        let mut rng = thread_rng();
+        let r = Scalar::rand(&mut rng);
+        let owned: Vec<Vec<u8>> = (0..NUM_POINTS - 1).map(|i| vec![0xab; i]).collect();
+        let messages: Vec<&[u8]> = owned.iter().map(|m| &m[..]).collect();
+        assert!(messages[0].is_empty());
+
+        let c = commit_bytes(&messages, r).unwrap();
+        for j in [0, 1, 7] {
+            let opened = open_bytes(&messages, r, j).unwrap();
+            assert_eq!(opened.payload, messages[j]);
+            assert!(opened.verify(&c).unwrap());
+            let witness = opened.opening.witness;
+            assert!(check_bytes(c, messages[j], witness, j).unwrap());
+            // A message differing only in length must not verify
+            assert!(!check_bytes(c, &[messages[j], &[0u8][..]].concat(), witness, j).unwrap());
+        }
+
+        // Multi-kilobyte payloads, and tampering with one
+        let large: Vec<Vec<u8>> = (0..4).map(|i| (0..4096 * (i + 1)).map(|b| (b * 31 + i) as u8).collect()).collect();
+        let payloads: Vec<&[u8]> = large.iter().map(|m| &m[..]).collect();
+        let c = commit_bytes(&payloads, r).unwrap();
+        let mut opened = open_bytes(&payloads, r, 3).unwrap();
+        assert!(opened.verify(&c).unwrap());
+        assert!(check_bytes(c, &large[3], opened.opening.witness, 3).unwrap());
+        opened.payload[2048] ^= 1;
+        assert!(!opened.verify(&c).unwrap());
+        assert!(!check_bytes(c, &opened.payload, opened.opening.witness, 3).unwrap());
+        assert!(!check_bytes(c, &large[3], opened.opening.witness, 2).unwrap());
+
+        assert_ne!(hash_to_scalar(b""), hash_to_scalar(b"\0"));
+        assert_ne!(hash_to_scalar(b"ab"), hash_to_scalar(b"ab\0"));
+        // Version 1 scalars do not carry over
+        let v1 = <DefaultFieldHasher<Sha256> as HashToField<Scalar>>::new(b"OBLIVIOUS-SYNC-V01_BYTES_XMD:SHA-256_");
+        let [x] = v1.hash_to_field::<1>(b"ab");
+        assert_ne!(hash_to_scalar(b"ab"), x);
+        // Any number of messages up to the setup capacity
+        let hashed: Vec<Scalar> = messages[1..].iter().map(|m| hash_to_scalar(m)).collect();
+        assert_eq!(commit_bytes(&messages[1..], r).unwrap(), commit(&hashed, r).unwrap());
+        assert!(commit_bytes(&vec![&b""[..]; max_vector_len() + 1], r).is_err());
+    }
+
+    #[test]
+    fn test_invalid_opening() {
+        let mut rng = thread_rng();
+        let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
         let r = Scalar::rand(&mut rng);
-        
         let commitment = commit(&v, r).unwrap();
+
         let j = 0;
-        let (v_j, r_j, witness) = open(&v, r, j).unwrap();
-        
-        assert!(check(commitment, v_j, witness, POINTS[j+1]));
+        let Opening { value: v_j, witness, .. } = open(&v, r, j).unwrap();
+        let invalid_v_j = v_j + Scalar::from(1u64);
+
+        assert!(!check(commitment, j, invalid_v_j, witness).unwrap());
     }
 
     #[test]
-    fn test_batch_open() {
+    fn test_rejects_invalid_points() {
         let mut rng = thread_rng();
-        let v: Vec<Scalar> = (0..NUM_POINTS-1).map(|_| Scalar::rand(&mut rng)).collect();
+        let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
         let r = Scalar::rand(&mut rng);
         let commitment = commit(&v, r).unwrap();
+        let Opening { value: v_j, witness, .. } = open(&v, r, 0).unwrap();
 
-        let indices = vec![0, 2, 4];
-        let (values, r_j, witness) = batch_open(&v, r, indices.clone()).unwrap();
-        
-        assert!(batch_check(commitment, &values, witness, &indices).unwrap());
+        let identity = GroupAffine::zero();
+        let off_curve = GroupAffine::new_unchecked(Fq::from(1u64), Fq::from(1u64));
+        assert!(validate_point(&commitment).is_ok());
+        assert!(validate_point(&identity).is_err());
+        assert!(validate_point(&off_curve).is_err());
+
+        for bad in [identity, off_curve] {
+            assert!(!check(bad, 0, v_j, witness).unwrap());
+            assert!(!check(commitment, 0, v_j, bad).unwrap());
+        }
+
+        let indices = vec![0, 2];
+        let BatchOpening { values, witness, .. } = batch_open(&v, r, indices.clone()).unwrap();
+        for bad in [identity, off_curve] {
+            assert!(batch_check(bad, &values, witness, &indices, NUM_POINTS - 1).is_err());
+            assert!(batch_check(commitment, &values, bad, &indices, NUM_POINTS - 1).is_err());
+        }
     }
 
     #[test]
-    fn test_batch_open_edge_cases() {
+    fn test_deserialize_checked() {
+        let mut rng = thread_rng();
+        let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+        let c = commit(&v, Scalar::rand(&mut rng)).unwrap();
+        let encode = |p: &GroupAffine, compress| {
+            let mut buf = Vec::new();
+            p.serialize_with_mode(&mut buf, compress).unwrap();
+            buf
+        };
+
+        assert_eq!(deserialize_checked(&encode(&c, Compress::Yes)), Ok(c));
+        assert_eq!(deserialize_checked(&encode(&c, Compress::No)), Ok(c));
+
+        // On the curve, but almost every such point lies outside the r-torsion
+        let outside = (1u64..)
+            .filter_map(|x| GroupAffine::get_point_from_x_unchecked(Fq::from(x), false))
+            .find(|p| !p.is_in_correct_subgroup_assuming_on_curve())
+            .unwrap();
+        assert_eq!(deserialize_checked(&encode(&outside, Compress::Yes)), Err(Error::NotInSubgroup));
+
+        let off_curve = GroupAffine::new_unchecked(c.x, c.y + Fq::ONE);
+        assert_eq!(deserialize_checked(&encode(&off_curve, Compress::No)), Err(Error::NotOnCurve));
+        assert_eq!(deserialize_checked(&encode(&GroupAffine::zero(), Compress::Yes)), Err(Error::IdentityPoint));
+
+        let garbage = [0xffu8; 48];
+        assert_eq!(deserialize_checked(&garbage), Err(Error::MalformedEncoding));
+        assert_eq!(deserialize_checked(&[1, 2, 3]), Err(Error::MalformedEncoding));
+    }
+
+    #[test]
+    fn test_check_from_bytes() {
         let mut rng = thread_rng();
-        let v: Vec<Scalar> = (0..NUM_POINTS-1).map(|_| Scalar::rand(&mut rng)).collect();
+        let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
         let r = Scalar::rand(&mut rng);
-        let commitment = commit(&v, r).unwrap();
+        let c = commit(&v, r).unwrap();
+        let encode = |p: &GroupAffine, compress| {
+            let mut buf = Vec::new();
+            p.serialize_with_mode(&mut buf, compress).unwrap();
+            buf
+        };
+        let c_bytes = encode(&c, Compress::Yes);
 
-        // Test with all indices
-        let all_indices: Vec<usize> = (0..v.len()).collect();
-        let (values, r_j, witness) = batch_open(&v, r, all_indices.clone()).unwrap();
-        assert!(batch_check(commitment, &values, witness, &all_indices).unwrap());
+        let Opening { value: v_j, witness, .. } = open(&v, r, 7).unwrap();
+        let witness_bytes = encode(&witness, Compress::Yes);
+        assert_eq!(check_from_bytes(&c_bytes, v_j, &witness_bytes, 7), Ok(true));
+        assert_eq!(check_from_bytes(&encode(&c, Compress::No), v_j, &witness_bytes, 7), Ok(true));
+        assert_eq!(check_from_bytes(&c_bytes, v_j, &witness_bytes, 8), Ok(false));
+        assert_eq!(check_from_bytes(&c_bytes, v_j, &witness_bytes, max_vector_len()), Err(Error::IndexOutOfBounds));
 
-        // Test with empty indices (should fail)
-        assert!(batch_open(&v, r, Vec::<usize>::new()).is_err());
+        let indices = [2, 7, 11];
+        let BatchOpening { values, witness, .. } = batch_open(&v, r, indices).unwrap();
+        let witness_bytes = encode(&witness, Compress::Yes);
+        assert_eq!(batch_check_from_bytes(&c_bytes, &values, &witness_bytes, &indices, NUM_POINTS - 1), Ok(true));
 
-        // Test with duplicate indices (should fail)
-        let duplicate_indices = vec![0, 0, 2];
-        assert!(batch_open(&v, r, duplicate_indices).is_err());
+        // Truncated and off-curve encodings are errors, not a failed check
+        let truncated = &c_bytes[..c_bytes.len() - 1];
+        assert_eq!(check_from_bytes(truncated, v_j, &witness_bytes, 7), Err(Error::MalformedEncoding));
+        assert_eq!(batch_check_from_bytes(truncated, &values, &witness_bytes, &indices, NUM_POINTS - 1), Err(Error::MalformedEncoding));
+        let off_curve = encode(&GroupAffine::new_unchecked(c.x, c.y + Fq::ONE), Compress::No);
+        assert_eq!(check_from_bytes(&off_curve, v_j, &witness_bytes, 7), Err(Error::NotOnCurve));
+        assert_eq!(batch_check_from_bytes(&c_bytes, &values, &off_curve, &indices, NUM_POINTS - 1), Err(Error::NotOnCurve));
+    }
 
-        // Test with out of bounds index (should fail)
-        let out_of_bounds = vec![0, v.len()];
-        assert!(batch_open(&v, r, out_of_bounds).is_err());
+    #[test]
+    fn test_derive_points_dst_separation() {
+        let setup = &TRUSTED_SETUP[..4];
+        let default = derive_points(setup, DEFAULT_DST, 4).unwrap();
+        let app_a = derive_points(setup, b"APP-A-GENERATORS", 4).unwrap();
+        let app_b = derive_points(setup, b"APP-B-GENERATORS", 4).unwrap();
+
+        assert_eq!(default[..], POINTS[..4]);
+        assert_eq!(app_a, derive_points(setup, b"APP-A-GENERATORS", 4).unwrap());
+        for i in 0..4 {
+            assert_ne!(app_a[i], app_b[i]);
+            assert_ne!(app_a[i], default[i]);
+        }
     }
 
     #[test]
-    fn test_invalid_opening() {
+    fn test_derive_points_errors() {
+        assert_eq!(derive_points(&TRUSTED_SETUP, b"", 4), Err(Error::GeneratorDerivationFailed));
+        assert_eq!(
+            derive_points(&TRUSTED_SETUP[..3], DEFAULT_DST, 4),
+            Err(Error::SetupTooShort { expected: 4, got: 3 })
+        );
+        assert_eq!(Bls12_381::hash_to_curve(b"", b"line"), Err(Error::GeneratorDerivationFailed));
+        assert_eq!(Bn254::hash_to_curve(b"", b"line"), Err(Error::GeneratorDerivationFailed));
+    }
+
+    #[test]
+    fn test_points_concurrent_init() {
+        let handles: Vec<_> = (0..8)
+            .map(|_| std::thread::spawn(|| *POINTS))
+            .collect();
+        let expected = derive_points(&TRUSTED_SETUP, DEFAULT_DST, NUM_POINTS).unwrap();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap()[..], expected[..]);
+        }
+    }
+
+    #[cfg(not(feature = "verify-only"))]
+    #[test]
+    fn test_open_ct_matches_open() {
         let mut rng = thread_rng();
-        let v: Vec<Scalar> = (0..NUM_POINTS-1).map(|_| Scalar::rand(&mut rng)).collect();
+        let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
         let r = Scalar::rand(&mut rng);
-        let commitment = commit(&v, r).unwrap();
+        let c = commit(&v, r).unwrap();
+        for j in 0..v.len() {
+            let opening = open_ct(&v, r, j).unwrap();
+            assert_eq!(opening, open(&v, r, j).unwrap());
+            assert!(check(c, j, opening.value, opening.witness).unwrap());
+        }
+        assert_eq!(open_ct(&v, r, v.len()), Err(Error::IndexOutOfBounds));
 
-        let j = 0;
-        let (v_j, r_j, witness) = open(&v, r, j).unwrap();
-        let invalid_v_j = v_j + Scalar::from(1u64);
+        // Any length `open` takes, past the first 20 values included
+        for n in [1, 7, NUM_POINTS + 10] {
+            let v: Vec<Scalar> = (0..n).map(|_| Scalar::rand(&mut rng)).collect();
+            for j in [0, n / 2, n - 1] {
+                assert_eq!(open_ct(&v, r, j).unwrap(), open(&v, r, j).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_scalar_strings() {
+        let mut rng = thread_rng();
+        let max = -Scalar::ONE;
+        for x in [Scalar::zero(), Scalar::ONE, Scalar::from(255u64), Scalar::from(u64::MAX), max, Scalar::rand(&mut rng)] {
+            let hex = scalar_to_hex(&x);
+            assert_eq!(hex.len(), 66);
+            assert_eq!(scalar_from_str(&hex).unwrap(), x);
+            assert_eq!(scalar_from_str(&x.into_bigint().to_string()).unwrap(), x);
+        }
+
+        assert_eq!(scalar_from_str("0").unwrap(), Scalar::zero());
+        assert_eq!(scalar_from_str("0x0").unwrap(), Scalar::zero());
+        assert_eq!(scalar_from_str("0xFF").unwrap(), Scalar::from(255u64));
+        assert_eq!(scalar_from_str("12345").unwrap(), Scalar::from(12345u64));
 
-        assert!(!check(commitment, invalid_v_j, witness, POINTS[j+1]));
+        // The modulus and anything larger are rejected, not reduced
+        let modulus = Scalar::MODULUS.to_string();
+        assert_eq!(scalar_from_str(&modulus), Err(Error::InvalidScalar));
+        assert_eq!(scalar_from_str(&("1".to_string() + &"0".repeat(80))), Err(Error::InvalidScalar));
+        assert_eq!(scalar_from_str(&("0x1".to_string() + &"0".repeat(64))), Err(Error::InvalidScalar));
+
+        for bad in ["", "0x", "-1", "12a", "0xg", " 1"] {
+            assert_eq!(scalar_from_str(bad), Err(Error::InvalidScalar), "{bad:?}");
+        }
     }
 
+    #[cfg(feature = "parallel")]
     #[test]
-    fn test_invalid_vector_length() {
+    fn test_par_batch_check_matches_serial() {
+        let mut rng = thread_rng();
+        let indices = vec![1, 4, 9];
+        let proofs: Vec<(GroupAffine, Vec<Scalar>, GroupAffine)> = (0..40)
+            .map(|k| {
+                let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+                let r = Scalar::rand(&mut rng);
+                let BatchOpening { mut values, witness, .. } = batch_open(&v, r, indices.clone()).unwrap();
+                if k % 7 == 3 {
+                    values[0] += Scalar::ONE;
+                }
+                (commit(&v, r).unwrap(), values, witness)
+            })
+            .collect();
+        let checks: Vec<_> = proofs
+            .iter()
+            .map(|(c, values, witness)| (*c, &values[..], *witness, &indices[..], NUM_POINTS - 1))
+            .collect();
+
+        let serial = batch_check_report(&checks);
+        assert_eq!(serial.iter().filter(|ok| !**ok).count(), 6);
+        for _ in 0..5 {
+            assert_eq!(par_batch_check_report(&checks), serial);
+            assert!(!par_batch_check(&checks));
+        }
+
+        let valid: Vec<_> = checks.iter().zip(&serial).filter(|(_, ok)| **ok).map(|(c, _)| *c).collect();
+        assert!(par_batch_check(&valid));
+        assert_eq!(par_batch_check(&valid), batch_check_many(&valid));
+    }
+
+    #[cfg(not(feature = "verify-only"))]
+    #[test]
+    fn test_commit_no_blind() {
         let mut rng = thread_rng();
         let v: Vec<Scalar> = (0..NUM_POINTS).map(|_| Scalar::rand(&mut rng)).collect();
+        let c = commit_no_blind(&v).unwrap();
+        assert_eq!(c, commit(&v[1..], v[0]).unwrap());
+
+        for j in [0, 1, NUM_POINTS - 1] {
+            let (v_j, witness) = open_no_blind(&v, j).unwrap();
+            assert_eq!(v_j, v[j]);
+            assert!(check_no_blind(c, v_j, witness, j));
+            assert!(!check_no_blind(c, v_j + Scalar::ONE, witness, j));
+            assert!(!check_no_blind(c, v_j, witness, (j + 1) % NUM_POINTS));
+        }
+        assert!(!check_no_blind(c, v[0], c, NUM_POINTS));
+
+        // All generators carry data, so the hiding scheme's length is rejected
+        assert_eq!(
+            commit_no_blind(&v[1..]),
+            Err(Error::LengthMismatch { expected: NUM_POINTS, got: NUM_POINTS - 1 })
+        );
+        assert_ne!(commit(&v, Scalar::ONE).unwrap(), c);
+        assert_eq!(open_no_blind(&v, NUM_POINTS), Err(Error::IndexOutOfBounds));
+
+        // Sparse public data yields identity witnesses, which still verify
+        let mut sparse = vec![Scalar::zero(); NUM_POINTS];
+        sparse[5] = Scalar::from(9u64);
+        let c = commit_no_blind(&sparse).unwrap();
+        let (v_5, witness) = open_no_blind(&sparse, 5).unwrap();
+        assert!(witness.is_zero());
+        assert!(check_no_blind(c, v_5, witness, 5));
+    }
+
+    #[test]
+    fn test_commit_sparse() {
+        let mut rng = thread_rng();
+        let r = Scalar::rand(&mut rng);
+        let entries = vec![(17, Scalar::rand(&mut rng)), (0, Scalar::rand(&mut rng)), (6, Scalar::zero())];
+        let mut dense = vec![Scalar::zero(); NUM_POINTS - 1];
+        for &(j, v_j) in &entries {
+            dense[j] = v_j;
+        }
+        let len = NUM_POINTS - 1;
+        assert_eq!(commit_sparse(&entries, len, r).unwrap(), commit(&dense, r).unwrap());
+        assert_eq!(commit_sparse(&[], len, r).unwrap(), commit(&vec![Scalar::zero(); len], r).unwrap());
+
+        assert_eq!(commit_sparse(&[(len, Scalar::ONE)], len, r), Err(Error::IndexOutOfBounds));
+        assert_eq!(commit_sparse(&[(3, Scalar::ONE), (3, Scalar::ONE)], len, r), Err(Error::DuplicateIndices));
+    }
+
+    #[cfg(not(feature = "verify-only"))]
+    #[test]
+    fn test_sparse_openings() {
+        let mut rng = thread_rng();
+        let len = 3000;
+        let entries: Vec<(usize, Scalar)> =
+            [2999, 4, 1500, 0, 77].iter().map(|&j| (j, Scalar::rand(&mut rng))).collect();
+        let r = Scalar::rand(&mut rng);
+        let mut dense = vec![Scalar::zero(); len];
+        for &(j, v_j) in &entries {
+            dense[j] = v_j;
+        }
+
+        let c = commit_sparse(&entries, len, r).unwrap();
+        assert_eq!(c, commit(&dense, r).unwrap());
+
+        for j in [0, 4, 5, 1500, 2998, 2999] {
+            let opening = open_sparse(&entries, len, r, j).unwrap();
+            assert_eq!(opening.value, dense[j]);
+            assert!(opening.verify(&c).unwrap());
+        }
+        assert_eq!(open_sparse(&entries, len, r, 77).unwrap(), open(&dense, r, 77).unwrap());
+
+        let batch = batch_open_sparse(&entries, len, r, [1500, 3, 0, 2999]).unwrap();
+        assert_eq!(batch, batch_open(&dense, r, [1500, 3, 0, 2999]).unwrap());
+        assert!(batch.verify(&c).unwrap());
+
+        assert_eq!(open_sparse(&entries, len, r, len), Err(Error::IndexOutOfBounds));
+        assert_eq!(batch_open_sparse(&entries, len, r, [1, 1]), Err(Error::DuplicateIndices));
+        assert!(commit_sparse(&[], max_vector_len() + 1, r).is_err());
+    }
+
+    #[test]
+    fn test_batch_check_rejects_out_of_range_indices() {
+        let mut rng = thread_rng();
+        let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+        let r = Scalar::rand(&mut rng);
+        let c = commit(&v, r).unwrap();
+        let BatchOpening { values, witness, .. } = batch_open(&v, r, [2, 5]).unwrap();
+
+        for bad in [NUM_POINTS - 1, NUM_POINTS, usize::MAX] {
+            assert_eq!(batch_check(c, &values, witness, &[2, bad], NUM_POINTS - 1), Err(Error::IndexOutOfBounds));
+        }
+        // Checked before anything else, e.g. the commitment itself
+        assert_eq!(
+            batch_check(GroupAffine::zero(), &values, witness, &[2, NUM_POINTS], NUM_POINTS - 1),
+            Err(Error::IndexOutOfBounds)
+        );
+        // An index the setup has a generator for is still past the committed length
+        let BatchOpening { values: short, witness: short_witness, .. } = batch_open(&v[..5], r, [2]).unwrap();
+        assert_eq!(batch_check(c, &short, short_witness, &[5], 5), Err(Error::IndexOutOfBounds));
+        let opening = AggregateOpening { indices: vec![NUM_POINTS], values: vec![values[0]], witness, len: v.len() };
+        assert_eq!(aggregate_check(c, &opening), Err(Error::IndexOutOfBounds));
+        assert_eq!(batch_check_report(&[(c, &values[..], witness, &[2, NUM_POINTS][..], NUM_POINTS - 1)]), vec![false]);
+    }
+
+    #[test]
+    fn test_commit_matrix() {
+        let mut rng = thread_rng();
+        let rows: Vec<Vec<Scalar>> = (0..4)
+            .map(|_| (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect())
+            .collect();
+        let r: Vec<Scalar> = (0..4).map(|_| Scalar::rand(&mut rng)).collect();
+        let commitments = commit_matrix(&rows, &r).unwrap();
+        assert_eq!(commitments[2], commit(&rows[2], r[2]).unwrap());
+
+        let cell = open_cell(&rows, &r, 2, 7).unwrap();
+        assert_eq!(cell.value, rows[2][7]);
+        let other = open_cell(&rows, &r, 0, 3).unwrap();
+        assert!(batch_check_matrix(&commitments, &[cell.clone(), other]).unwrap());
+
+        // Checked against the wrong row, or claiming another column, fails
+        let wrong_row = CellOpening { row: 1, ..cell.clone() };
+        assert!(!batch_check_matrix(&commitments, &[wrong_row]).unwrap());
+        let wrong_col = CellOpening { col: 8, ..cell.clone() };
+        assert!(!batch_check_matrix(&commitments, &[wrong_col]).unwrap());
+        let out_of_range = CellOpening { row: 4, ..cell };
+        assert_eq!(batch_check_matrix(&commitments, &[out_of_range]), Err(Error::IndexOutOfBounds));
+        assert_eq!(open_cell(&rows, &r, 4, 0), Err(Error::IndexOutOfBounds));
+
+        // Ragged rows and a short blinding vector are rejected
+        let mut ragged = rows.clone();
+        ragged[3].pop();
+        assert!(matches!(commit_matrix(&ragged, &r), Err(Error::LengthMismatch { .. })));
+        assert_eq!(
+            commit_matrix(&rows, &r[..3]),
+            Err(Error::LengthMismatch { expected: 4, got: 3 })
+        );
+    }
+
+    #[test]
+    fn test_derive_points_with_digest() {
+        let setup = &TRUSTED_SETUP[..NUM_POINTS];
+        assert_eq!(derive_points_with::<Sha256>(setup, DEFAULT_DST, NUM_POINTS).unwrap()[..], POINTS[..]);
+
+        let sha3 = derive_points_with::<sha3::Sha3_256>(setup, DEFAULT_DST, NUM_POINTS).unwrap();
+        assert_eq!(sha3, derive_points_with::<sha3::Sha3_256>(setup, DEFAULT_DST, NUM_POINTS).unwrap());
+        for (a, b) in sha3.iter().zip(POINTS.iter()) {
+            assert_ne!(a, b);
+            assert!(validate_point(a).is_ok());
+        }
+
+        // The alternate generators form a working key
+        let mut rng = thread_rng();
+        let v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+        let r = Scalar::rand(&mut rng);
+        let c = commit_with_points::<Bls12_377>(&sha3, &v, r).unwrap();
+        let (v_j, _, witness) = open_with_points::<Bls12_377>(&sha3, &v, r, 2).unwrap();
+        assert!(check_unchecked(c, v_j, witness, sha3[3]));
+        assert_ne!(c, commit(&v, r).unwrap());
+    }
+
+    fn roundtrip_on<C: PcsCurve>() {
+        let mut rng = thread_rng();
+        let v: Vec<C::Scalar> = (0..NUM_POINTS - 1).map(|_| C::Scalar::rand(&mut rng)).collect();
+        let r = C::Scalar::rand(&mut rng);
+        let commitment = commit_on::<C>(&v, r).unwrap();
+
+        let j = 3;
+        let (v_j, _, witness) = open_on::<C>(&v, r, j).unwrap();
+        assert!(check_on::<C>(commitment, v_j, witness, C::points()[j+1]));
+        assert!(!check_on::<C>(commitment, v_j + C::Scalar::from(1u64), witness, C::points()[j+1]));
+
+        let indices = vec![1, 4, 7];
+        let (values, _, witness) = batch_open_on::<C>(&v, r, indices.clone()).unwrap();
+        assert!(batch_check_on::<C>(commitment, &values, witness, &indices).unwrap());
+    }
+
+    #[test]
+    fn test_alternative_curves() {
+        roundtrip_on::<Bls12_381>();
+        roundtrip_on::<Bn254>();
+    }
+
+    #[cfg(not(feature = "verify-only"))]
+    #[test]
+    fn test_invalid_vector_length() {
+        let v = vec![Scalar::ONE; max_vector_len() + 1];
+        let too_long = Error::SetupTooShort { expected: TRUSTED_SETUP.len() + 1, got: TRUSTED_SETUP.len() };
+        assert_eq!(commit(&v, Scalar::ONE).unwrap_err(), too_long);
+        assert_eq!(open(&v, Scalar::ONE, 0).unwrap_err(), too_long);
+        assert_eq!(batch_check(GroupAffine::zero(), &[], GroupAffine::zero(), &[], v.len()).unwrap_err(), too_long);
+    }
+
+    #[test]
+    fn test_add_commitments() {
+        let mut rng = thread_rng();
+        let v1: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+        let v2: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+        let (r1, r2) = (Scalar::rand(&mut rng), Scalar::rand(&mut rng));
+        let (c, r) = add_commitments(commit(&v1, r1).unwrap(), r1, commit(&v2, r2).unwrap(), r2);
+        assert_eq!(r, r1 + r2);
+        assert_eq!(add_commitment_points(commit(&v1, r1).unwrap(), commit(&v2, r2).unwrap()), c);
+
+        let sum: Vec<Scalar> = v1.iter().zip(&v2).map(|(a, b)| *a + b).collect();
+        assert_eq!(c, commit(&sum, r).unwrap());
+        let j = 7;
+        let Opening { value: v_j, witness, .. } = open(&sum, r, j).unwrap();
+        assert_eq!(v_j, v1[j] + v2[j]);
+        assert!(check(c, j, v_j, witness).unwrap());
+        assert!(!check(c, j, v1[j], witness).unwrap());
+        let BatchOpening { values, witness, .. } = batch_open(&sum, r, [0, 7, 19]).unwrap();
+        assert!(batch_check(c, &values, witness, &[0, 7, 19], sum.len()).unwrap());
+
+        // A shorter vector adds as its zero-padded form
+        let (c, r) = add_commitments(commit(&v1, r1).unwrap(), r1, commit(&v2[..3], r2).unwrap(), r2);
+        let mut padded_sum = v1.clone();
+        for (a, b) in padded_sum.iter_mut().zip(&v2[..3]) {
+            *a += b;
+        }
+        assert_eq!(c, commit(&padded_sum, r).unwrap());
+    }
+
+    #[test]
+    fn test_scale_commitment() {
+        let mut rng = thread_rng();
+        let v1: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+        let v2: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+        let (r1, r2) = (Scalar::rand(&mut rng), Scalar::rand(&mut rng));
+        let (c1, c2) = (commit(&v1, r1).unwrap(), commit(&v2, r2).unwrap());
+        let k = Scalar::rand(&mut rng);
+
+        let (c, r) = scale_commitment(c1, k, r1);
+        assert_eq!(r, k * r1);
+        let scaled: Vec<Scalar> = v1.iter().map(|x| k * x).collect();
+        assert_eq!(c, commit(&scaled, r).unwrap());
+        let Opening { value: v_j, witness, .. } = open(&scaled, r, 4).unwrap();
+        assert_eq!(v_j, k * v1[4]);
+        assert!(check(c, 4, v_j, witness).unwrap());
+
+        // 3 * v1 + k * v2
+        let (a, r_a) = scale_commitment(c1, Scalar::from(3u64), r1);
+        let (b, r_b) = scale_commitment(c2, k, r2);
+        let (c, r) = add_commitments(a, r_a, b, r_b);
+        let combined: Vec<Scalar> = v1.iter().zip(&v2).map(|(x, y)| Scalar::from(3u64) * x + k * y).collect();
+        let BatchOpening { values, witness, .. } = batch_open(&combined, r, [1, 12]).unwrap();
+        assert_eq!(values, vec![combined[1], combined[12]]);
+        assert!(batch_check(c, &values, witness, &[1, 12], combined.len()).unwrap());
+
+        let (zero, r_zero) = scale_commitment(c1, Scalar::zero(), r1);
+        assert!(zero.is_zero() && r_zero.is_zero());
+        let BatchOpening { values, witness, .. } = batch_open(&vec![Scalar::zero(); NUM_POINTS - 1], r_zero, [0]).unwrap();
+        assert_eq!(batch_check(zero, &values, witness, &[0], NUM_POINTS - 1), Err(Error::IdentityPoint));
+    }
+
+    #[test]
+    fn test_update_commitment() {
+        let mut rng = thread_rng();
+        let mut v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
+        let r = Scalar::rand(&mut rng);
+        let c = commit(&v, r).unwrap();
+
+        let new = Scalar::rand(&mut rng);
+        let updated = update_commitment(c, 6, v[6], new, v.len()).unwrap();
+        v[6] = new;
+        assert_eq!(updated, commit(&v, r).unwrap());
+        let Opening { value: v_j, witness, .. } = open(&v, r, 6).unwrap();
+        assert!(check(updated, 6, v_j, witness).unwrap());
+        let BatchOpening { values, witness, .. } = batch_open(&v, r, [2, 6]).unwrap();
+        assert!(batch_check(updated, &values, witness, &[2, 6], v.len()).unwrap());
+
+        assert_eq!(update_commitment(c, 3, v[3], v[3], v.len()), Ok(c));
+        assert_eq!(update_commitment(c, v.len(), v[0], new, v.len()), Err(Error::IndexOutOfBounds));
+        assert_eq!(update_commitment(c, max_vector_len(), v[0], new, usize::MAX), Err(Error::IndexOutOfBounds));
+    }
+
+    #[cfg(not(feature = "verify-only"))]
+    #[test]
+    fn test_update_witness() {
+        let mut rng = thread_rng();
+        let mut v: Vec<Scalar> = (0..NUM_POINTS - 1).map(|_| Scalar::rand(&mut rng)).collect();
         let r = Scalar::rand(&mut rng);
-        assert!(commit(&v, r).is_err());
+        let c = commit(&v, r).unwrap();
+        let Opening { value: v_j, witness, .. } = open(&v, r, 2).unwrap();
+
+        let (k, new) = (11, Scalar::rand(&mut rng));
+        let updated_c = update_commitment(c, k, v[k], new, v.len()).unwrap();
+        let updated_witness = update_witness(witness, 2, k, v[k], new, v.len()).unwrap();
+        v[k] = new;
+        assert_eq!(updated_witness, open(&v, r, 2).unwrap().witness);
+        assert!(check(updated_c, 2, v_j, updated_witness).unwrap());
+        // The stale witness no longer opens the updated commitment
+        assert!(!check(updated_c, 2, v_j, witness).unwrap());
+
+        assert_eq!(update_witness(witness, 2, 2, v_j, new, v.len()), Err(Error::OpenedIndexChanged));
+        assert_eq!(update_witness(witness, v.len(), k, v[k], new, v.len()), Err(Error::IndexOutOfBounds));
+        assert_eq!(update_witness(witness, 2, v.len(), v[k], new, v.len()), Err(Error::IndexOutOfBounds));
+
+        // Past the first 20 values, using the same generators as `commit`
+        let mut long: Vec<Scalar> = (0..NUM_POINTS + 10).map(|_| Scalar::rand(&mut rng)).collect();
+        let witness = open(&long, r, 2).unwrap().witness;
+        let k = NUM_POINTS + 5;
+        let updated_witness = update_witness(witness, 2, k, long[k], new, long.len()).unwrap();
+        long[k] = new;
+        assert_eq!(updated_witness, open(&long, r, 2).unwrap().witness);
+    }
+
+    #[cfg(not(feature = "verify-only"))]
+    #[test]
+    fn test_update_commitment_long_vector() {
+        let mut rng = thread_rng();
+        let r = Scalar::rand(&mut rng);
+        for len in [NUM_POINTS - 1, 2000] {
+            let mut v: Vec<Scalar> = (0..len).map(|_| Scalar::rand(&mut rng)).collect();
+            let c = commit(&v, r).unwrap();
+
+            // One scalar multiplication, whatever `len` is
+            let j = len - 1;
+            let new = Scalar::rand(&mut rng);
+            let updated = update_commitment(c, j, v[j], new, len).unwrap();
+            v[j] = new;
+            assert_eq!(updated, commit(&v, r).unwrap());
+        }
+    }
+
+    #[cfg(not(feature = "verify-only"))]
+    #[test]
+    fn test_arbitrary_vector_lengths() {
+        let mut rng = thread_rng();
+        let r = Scalar::rand(&mut rng);
+        for len in [1, NUM_POINTS - 2, NUM_POINTS - 1, NUM_POINTS, 3000] {
+            let v: Vec<Scalar> = (0..len).map(|_| Scalar::rand(&mut rng)).collect();
+            let c = commit(&v, r).unwrap();
+            let points = generators(len + 1).unwrap();
+            assert_eq!(c, commit_with_points::<Bls12_377>(&points, &v, r).unwrap());
+
+            for j in [0, len / 2, len - 1] {
+                let Opening { value: v_j, witness, .. } = open(&v, r, j).unwrap();
+                assert!(check_unchecked(c, v_j, witness, points[j + 1]));
+                assert_eq!(generator(j + 1), Ok(points[j + 1]));
+            }
+            let mut indices = vec![0, len / 2, len - 1];
+            indices.dedup();
+            let BatchOpening { values, witness, .. } = batch_open(&v, r, indices.clone()).unwrap();
+            assert!(batch_check(c, &values, witness, &indices, len).unwrap());
+            assert_eq!(batch_check(c, &values[..1], witness, &[len], len), Err(Error::IndexOutOfBounds));
+        }
+
+        // Short vectors commit like their zero-padded form, and generators
+        // past `POINTS` continue the same derivation
+        let v: Vec<Scalar> = (0..5).map(|_| Scalar::rand(&mut rng)).collect();
+        assert_eq!(commit(&v, r).unwrap(), commit_padded(&v, r).unwrap());
+        assert_eq!(generators(NUM_POINTS + 3).unwrap(), derive_points(&TRUSTED_SETUP, DEFAULT_DST, NUM_POINTS + 3).unwrap());
+        assert_eq!(generator(TRUSTED_SETUP.len()), Err(Error::IndexOutOfBounds));
     }
 }
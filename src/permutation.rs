@@ -0,0 +1,398 @@
+//! Proof that two committed vectors hold the same values in a different order.
+//!
+//! For `c1 = commit(v, r)` and `c2 = commit(w, r2)`, the verifier draws `γ`
+//! after seeing both commitments, and the prover shows
+//! `Π (v_i + γ) = Π (w_i + γ)`. Both sides are polynomials in `γ` whose roots
+//! are the negated entries, so they agree at a random `γ` only if `v` and `w`
+//! are the same multiset, i.e. `w` is a permutation of `v`.
+//!
+//! Each side is shown with a grand-product chain over scalar Pedersen
+//! commitments: `entries[i]` commits to the `i`-th value (tied to the vector
+//! commitment by a sigma proof), `P_k` commits to the running product
+//! `Π_{i<=k} (v_i + γ)`, and a product proof links each `P_k` to `P_{k-1}`. A
+//! final Schnorr proof shows both chains end at the same product. Everything is
+//! hidden behind fresh blindings, so neither vector nor the permutation is
+//! revealed; the proof grows linearly with the vector length.
+use anyhow::{anyhow, Result};
+use ark_bls12_377::{Fr, G1Affine, G1Projective};
+use ark_ec::CurveGroup;
+use ark_ff::{UniformRand, Zero};
+use rand::{thread_rng, CryptoRng, RngCore};
+
+use crate::curve::{Bls12_377, PcsCurve};
+use crate::error::Error;
+use crate::lazy::Lazy;
+use crate::pcs::{commit, default_vector_len, validate_point};
+use crate::transcript::Transcript;
+
+/// Domain separation tag for the scalar commitment bases of the product chain.
+pub const PERMUTATION_DST: &[u8] = b"OBLIVIOUS-SYNC-V01_PERMUTATION_XMD:SHA-256_SSWU_RO_";
+
+static VALUE_BASE: Lazy<G1Affine> =
+    Lazy::new(|| Bls12_377::hash_to_curve(PERMUTATION_DST, b"value").expect("PERMUTATION_DST is valid"));
+static BLINDING_BASE: Lazy<G1Affine> =
+    Lazy::new(|| Bls12_377::hash_to_curve(PERMUTATION_DST, b"blinding").expect("PERMUTATION_DST is valid"));
+
+/// `[x]G + [r]H` over the chain bases.
+fn pedersen(x: Fr, r: Fr) -> G1Projective {
+    *VALUE_BASE * x + *BLINDING_BASE * r
+}
+
+/// Sigma proof that `entries[i]` commits to `v_i` for the vector committed in `c`.
+pub struct ConsistencyProof {
+    pub t_vector: G1Affine,
+    pub t_entries: Vec<G1Affine>,
+    pub s: Vec<Fr>,
+    pub s_r: Fr,
+    pub s_entries: Vec<Fr>,
+}
+
+/// Sigma proof that `Z` commits to `x * y`, where `X` commits to `x` and `Y`
+/// to `y`: `X = [x]G + [r_x]H` and `Z = [x]Y + [ρ]H`.
+pub struct ProductProof {
+    pub t_x: G1Affine,
+    pub t_z: G1Affine,
+    pub s_x: Fr,
+    pub s_r: Fr,
+    pub s_z: Fr,
+}
+
+/// One vector's grand product.
+pub struct ProductChain {
+    /// `[v_i]G + [s_i]H` for each entry.
+    pub entries: Vec<G1Affine>,
+    /// `P_1..P_{n-1}`; `P_0 = entries[0] + [γ]G` is implied.
+    pub partials: Vec<G1Affine>,
+    pub consistency: ConsistencyProof,
+    /// `products[k - 1]` shows `P_k = P_{k-1} * (v_k + γ)`.
+    pub products: Vec<ProductProof>,
+}
+
+pub struct PermutationProof {
+    pub left: ProductChain,
+    pub right: ProductChain,
+    /// Schnorr proof on `H` that both chains end at the same product.
+    pub t_eq: G1Affine,
+    pub s_eq: Fr,
+}
+
+/// `w[i] = v[sigma[i]]`. Errors unless `sigma` is a permutation of `0..v.len()`.
+pub fn permute(v: &[Fr], sigma: &[usize]) -> Result<Vec<Fr>> {
+    if sigma.len() != v.len() {
+        return Err(anyhow!("permutation has {} entries, vector has {}", sigma.len(), v.len()));
+    }
+    let mut seen = vec![false; v.len()];
+    for &i in sigma {
+        if i >= v.len() || std::mem::replace(&mut seen[i], true) {
+            return Err(anyhow!("sigma is not a permutation of 0..{}", v.len()));
+        }
+    }
+    Ok(sigma.iter().map(|&i| v[i]).collect())
+}
+
+/// Proves that `commit(v, r)` and `commit(&permute(v, sigma)?, r2)` hold the
+/// same values. `verify_permutation` only accepts chains of
+/// `default_vector_len()` entries, so any other length of `v` errors.
+pub fn prove_permutation(v: &[Fr], r: Fr, sigma: &[usize], r2: Fr) -> Result<PermutationProof> {
+    prove_permutation_with_rng(v, r, sigma, r2, &mut thread_rng())
+}
+
+/// `prove_permutation` drawing every blinding and nonce from `rng`.
+pub fn prove_permutation_with_rng<R: RngCore + CryptoRng>(
+    v: &[Fr],
+    r: Fr,
+    sigma: &[usize],
+    r2: Fr,
+    rng: &mut R,
+) -> Result<PermutationProof> {
+    if v.len() != default_vector_len() {
+        return Err(Error::LengthMismatch { expected: default_vector_len(), got: v.len() }.into());
+    }
+    let w = permute(v, sigma)?;
+    prove_same_multiset(v, r, &w, r2, rng)
+}
+
+/// Prover-side openings of a `ProductChain`, plus the nonces of its proofs.
+struct ChainWitness {
+    values: Vec<Fr>,
+    r: Fr,
+    entry_blinds: Vec<Fr>,
+    /// Running products `p_0..p_{n-1}` and their blindings (`π_0 = s_0`).
+    products: Vec<Fr>,
+    product_blinds: Vec<Fr>,
+    nonces: Vec<Fr>,
+    nonce_r: Fr,
+    nonce_entries: Vec<Fr>,
+    /// `(α, β, δ)` of each product proof.
+    product_nonces: Vec<(Fr, Fr, Fr)>,
+}
+
+/// Commits to a chain for `values` and the first messages of its proofs;
+/// responses are filled in by `respond` once the challenge is known.
+fn commit_chain<R: RngCore + CryptoRng>(rng: &mut R, values: &[Fr], r: Fr, gamma: Fr) -> Result<(ProductChain, ChainWitness)> {
+    let n = values.len();
+    let g = *VALUE_BASE * gamma;
+    let entry_blinds: Vec<Fr> = (0..n).map(|_| Fr::rand(rng)).collect();
+    let entries: Vec<G1Projective> = values.iter().zip(&entry_blinds).map(|(v_i, s_i)| pedersen(*v_i, *s_i)).collect();
+
+    let mut products = vec![values[0] + gamma];
+    let mut product_blinds = vec![entry_blinds[0]];
+    let mut partials = Vec::with_capacity(n - 1);
+    for k in 1..n {
+        let p_k = products[k - 1] * (values[k] + gamma);
+        let pi_k = Fr::rand(rng);
+        partials.push(pedersen(p_k, pi_k));
+        products.push(p_k);
+        product_blinds.push(pi_k);
+    }
+
+    let nonces: Vec<Fr> = (0..n).map(|_| Fr::rand(rng)).collect();
+    let nonce_r = Fr::rand(rng);
+    let nonce_entries: Vec<Fr> = (0..n).map(|_| Fr::rand(rng)).collect();
+    let t_vector = commit(&nonces, nonce_r)?;
+    let t_entries: Vec<G1Projective> = nonces.iter().zip(&nonce_entries).map(|(a, b)| pedersen(*a, *b)).collect();
+
+    let mut product_nonces = Vec::with_capacity(n - 1);
+    let mut product_proofs = Vec::with_capacity(n - 1);
+    for entry in &entries[1..] {
+        let (alpha, beta, delta) = (Fr::rand(rng), Fr::rand(rng), Fr::rand(rng));
+        let y = *entry + g;
+        product_proofs.push(ProductProof {
+            t_x: pedersen(alpha, beta).into_affine(),
+            t_z: (y * alpha + *BLINDING_BASE * delta).into_affine(),
+            s_x: Fr::zero(),
+            s_r: Fr::zero(),
+            s_z: Fr::zero(),
+        });
+        product_nonces.push((alpha, beta, delta));
+    }
+
+    let chain = ProductChain {
+        entries: G1Projective::normalize_batch(&entries),
+        partials: G1Projective::normalize_batch(&partials),
+        consistency: ConsistencyProof {
+            t_vector,
+            t_entries: G1Projective::normalize_batch(&t_entries),
+            s: Vec::new(),
+            s_r: Fr::zero(),
+            s_entries: Vec::new(),
+        },
+        products: product_proofs,
+    };
+    let witness = ChainWitness {
+        values: values.to_vec(),
+        r,
+        entry_blinds,
+        products,
+        product_blinds,
+        nonces,
+        nonce_r,
+        nonce_entries,
+        product_nonces,
+    };
+    Ok((chain, witness))
+}
+
+fn respond(chain: &mut ProductChain, witness: &ChainWitness, e: Fr) {
+    let consistency = &mut chain.consistency;
+    consistency.s = witness.nonces.iter().zip(&witness.values).map(|(a, v_i)| *a + e * v_i).collect();
+    consistency.s_r = witness.nonce_r + e * witness.r;
+    consistency.s_entries = witness.nonce_entries.iter().zip(&witness.entry_blinds).map(|(b, s_i)| *b + e * s_i).collect();
+
+    for (k, (proof, (alpha, beta, delta))) in chain.products.iter_mut().zip(&witness.product_nonces).enumerate() {
+        let k = k + 1;
+        let x = witness.products[k - 1];
+        // Z = [x]Y + [π_k - x s_k]H, with Y blinded by s_k
+        let rho = witness.product_blinds[k] - x * witness.entry_blinds[k];
+        proof.s_x = *alpha + e * x;
+        proof.s_r = *beta + e * witness.product_blinds[k - 1];
+        proof.s_z = *delta + e * rho;
+    }
+}
+
+fn absorb(transcript: &mut Transcript, chain: &ProductChain) {
+    for p in &chain.entries {
+        transcript.append_point(b"entry", p);
+    }
+    for p in &chain.partials {
+        transcript.append_point(b"partial", p);
+    }
+    transcript.append_point(b"t_vector", &chain.consistency.t_vector);
+    for p in &chain.consistency.t_entries {
+        transcript.append_point(b"t_entry", p);
+    }
+    for proof in &chain.products {
+        transcript.append_point(b"t_x", &proof.t_x);
+        transcript.append_point(b"t_z", &proof.t_z);
+    }
+}
+
+/// `prove_permutation` without checking that `w` is a rearrangement of `v`; a
+/// proof for any other `w` fails to verify.
+fn prove_same_multiset<R: RngCore + CryptoRng>(v: &[Fr], r: Fr, w: &[Fr], r2: Fr, rng: &mut R) -> Result<PermutationProof> {
+    let c1 = commit(v, r)?;
+    let c2 = commit(w, r2)?;
+    let mut transcript = Transcript::new(b"permutation");
+    transcript.append_point(b"c1", &c1);
+    transcript.append_point(b"c2", &c2);
+    let gamma = transcript.challenge_scalar(b"gamma");
+
+    let (mut left, left_witness) = commit_chain(rng, v, r, gamma)?;
+    let (mut right, right_witness) = commit_chain(rng, w, r2, gamma)?;
+    let k = Fr::rand(rng);
+    let t_eq = (*BLINDING_BASE * k).into_affine();
+
+    absorb(&mut transcript, &left);
+    absorb(&mut transcript, &right);
+    transcript.append_point(b"t_eq", &t_eq);
+    let e = transcript.challenge_scalar(b"e");
+
+    respond(&mut left, &left_witness, e);
+    respond(&mut right, &right_witness, e);
+    let last = v.len() - 1;
+    let s_eq = k + e * (left_witness.product_blinds[last] - right_witness.product_blinds[last]);
+    Ok(PermutationProof { left, right, t_eq, s_eq })
+}
+
+/// Checks one chain against `c` and returns its final product commitment.
+fn verify_chain(c: G1Affine, chain: &ProductChain, gamma: Fr, e: Fr) -> Option<G1Projective> {
+    let n = default_vector_len();
+    let consistency = &chain.consistency;
+    if chain.entries.len() != n
+        || chain.partials.len() != n - 1
+        || chain.products.len() != n - 1
+        || consistency.t_entries.len() != n
+        || consistency.s.len() != n
+        || consistency.s_entries.len() != n
+    {
+        return None;
+    }
+
+    if commit(&consistency.s, consistency.s_r).ok()? != consistency.t_vector + c * e {
+        return None;
+    }
+    for i in 0..n {
+        if pedersen(consistency.s[i], consistency.s_entries[i]) != consistency.t_entries[i] + chain.entries[i] * e {
+            return None;
+        }
+    }
+
+    let g = *VALUE_BASE * gamma;
+    let mut x = chain.entries[0] + g;
+    for (k, proof) in chain.products.iter().enumerate() {
+        let y = chain.entries[k + 1] + g;
+        let z = chain.partials[k].into();
+        if pedersen(proof.s_x, proof.s_r) != proof.t_x + x * e
+            || y * proof.s_x + *BLINDING_BASE * proof.s_z != proof.t_z + z * e
+        {
+            return None;
+        }
+        x = z;
+    }
+    Some(x)
+}
+
+/// Checks that `c1` and `c2` commit to vectors that are permutations of each other.
+pub fn verify_permutation(c1: G1Affine, c2: G1Affine, proof: &PermutationProof) -> bool {
+    if validate_point(&c1).is_err() || validate_point(&c2).is_err() {
+        return false;
+    }
+    let mut transcript = Transcript::new(b"permutation");
+    transcript.append_point(b"c1", &c1);
+    transcript.append_point(b"c2", &c2);
+    let gamma = transcript.challenge_scalar(b"gamma");
+    absorb(&mut transcript, &proof.left);
+    absorb(&mut transcript, &proof.right);
+    transcript.append_point(b"t_eq", &proof.t_eq);
+    let e = transcript.challenge_scalar(b"e");
+
+    let (Some(left), Some(right)) = (verify_chain(c1, &proof.left, gamma, e), verify_chain(c2, &proof.right, gamma, e))
+    else {
+        return false;
+    };
+    *BLINDING_BASE * proof.s_eq == proof.t_eq + (left - right) * e
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::One;
+
+    fn random_vector() -> Vec<Fr> {
+        let mut rng = thread_rng();
+        (0..default_vector_len()).map(|_| Fr::rand(&mut rng)).collect()
+    }
+
+    #[test]
+    fn test_permutation_proof() {
+        let mut rng = thread_rng();
+        let v = random_vector();
+        let (r, r2) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let sigma: Vec<usize> = (0..v.len()).map(|i| (i * 7 + 3) % v.len()).collect();
+
+        let w = permute(&v, &sigma).unwrap();
+        let (c1, c2) = (commit(&v, r).unwrap(), commit(&w, r2).unwrap());
+        let proof = prove_permutation(&v, r, &sigma, r2).unwrap();
+        assert!(verify_permutation(c1, c2, &proof));
+
+        // The identity permutation, and repeated values, are permutations too
+        let identity: Vec<usize> = (0..v.len()).collect();
+        let proof = prove_permutation(&v, r, &identity, r2).unwrap();
+        assert!(verify_permutation(c1, commit(&v, r2).unwrap(), &proof));
+        let mut repeated = v.clone();
+        repeated[4] = repeated[9];
+        let proof = prove_permutation(&repeated, r, &sigma, r2).unwrap();
+        let c = commit(&permute(&repeated, &sigma).unwrap(), r2).unwrap();
+        assert!(verify_permutation(commit(&repeated, r).unwrap(), c, &proof));
+
+        // Swapped or unrelated commitments are rejected
+        let proof = prove_permutation(&v, r, &sigma, r2).unwrap();
+        assert!(!verify_permutation(c2, c1, &proof));
+        assert!(!verify_permutation(c1, commit(&w, r2 + Fr::one()).unwrap(), &proof));
+    }
+
+    #[test]
+    fn test_non_permutation_fails() {
+        let mut rng = thread_rng();
+        let v = random_vector();
+        let (r, r2) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+
+        // One changed value
+        let mut w = v.clone();
+        w.swap(0, 5);
+        w[2] += Fr::one();
+        let proof = prove_same_multiset(&v, r, &w, r2, &mut rng).unwrap();
+        assert!(!verify_permutation(commit(&v, r).unwrap(), commit(&w, r2).unwrap(), &proof));
+
+        // Same values with different multiplicities
+        let mut w = v.clone();
+        w[1] = w[0];
+        let proof = prove_same_multiset(&v, r, &w, r2, &mut rng).unwrap();
+        assert!(!verify_permutation(commit(&v, r).unwrap(), commit(&w, r2).unwrap(), &proof));
+    }
+
+    #[test]
+    fn test_permute_rejects_non_permutations() {
+        let v = random_vector();
+        let mut sigma: Vec<usize> = (0..v.len()).collect();
+        sigma[3] = 4;
+        assert!(permute(&v, &sigma).is_err());
+        assert!(permute(&v, &sigma[1..]).is_err());
+        sigma[3] = v.len();
+        assert!(permute(&v, &sigma).is_err());
+    }
+
+    #[test]
+    fn test_permutation_rejects_unsupported_lengths() {
+        let r = Fr::from(1u64);
+        assert!(prove_permutation(&[], r, &[], r).is_err());
+        let v = random_vector();
+        for n in [1, v.len() - 1] {
+            let identity: Vec<usize> = (0..n).collect();
+            assert!(prove_permutation(&v[..n], r, &identity, r).is_err());
+        }
+        let long: Vec<Fr> = (0..v.len() as u64 + 1).map(Fr::from).collect();
+        let identity: Vec<usize> = (0..long.len()).collect();
+        assert!(prove_permutation(&long, r, &identity, r).is_err());
+    }
+}
@@ -0,0 +1,244 @@
+//! Fixed-base windowed tables for the setup generators.
+//!
+//! With window `w`, each generator stores `⌈253 / w⌉ · 2^w` affine points and a
+//! scalar multiplication becomes `⌈253 / w⌉` mixed additions instead of a full
+//! double-and-add. The default `w = 6` costs roughly 280 KB per generator
+//! (about 6 MB for `POINTS`) and makes `commit` several times faster, which
+//! pays off for services producing many commitments; one-off callers should
+//! stick to `pcs::commit`.
+//!
+//! A generator's table is built the first time a value at its index is
+//! multiplied, so a key only pays for the prefix its vectors actually use. The
+//! key is `Sync` and can be shared across threads; `table_bytes` reports what
+//! has been allocated so far.
+use ark_bls12_377::{Fr, G1Affine, G1Projective};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{AdditiveGroup, BigInteger, PrimeField, Zero};
+use ark_std::vec::Vec;
+use core::mem::size_of;
+
+use crate::error::{Error, Result};
+use crate::lazy::OnceCell;
+use crate::pcs::{generators, POINTS};
+
+pub const DEFAULT_WINDOW: usize = 6;
+
+type Table = Vec<Vec<G1Affine>>;
+
+pub struct PrecomputedKey {
+    window: usize,
+    bases: Vec<G1Affine>,
+    /// `tables[i][k][d] = d · 2^(w·k) · bases[i]`, built on first use.
+    tables: Vec<OnceCell<Table>>,
+}
+
+impl Default for PrecomputedKey {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW).expect("DEFAULT_WINDOW is in range")
+    }
+}
+
+impl PrecomputedKey {
+    /// Prepares the generators in `POINTS`, for vectors of up to
+    /// `POINTS.len() - 1` values. Errors with `InvalidWindow` unless `window`
+    /// is in `1..16`.
+    pub fn new(window: usize) -> Result<Self> {
+        Self::from_bases(POINTS.to_vec(), window)
+    }
+
+    /// Prepares only the first `k` setup generators (the blinding base and
+    /// `k - 1` value generators), for callers whose vectors are short or longer
+    /// than `POINTS`. Errors if the setup has fewer than `k` lines or, as
+    /// `new`, on a window outside `1..16`.
+    pub fn with_generators(k: usize, window: usize) -> Result<Self> {
+        Self::from_bases(generators(k)?, window)
+    }
+
+    fn from_bases(bases: Vec<G1Affine>, window: usize) -> Result<Self> {
+        if !(1..16).contains(&window) {
+            return Err(Error::InvalidWindow(window));
+        }
+        let tables = bases.iter().map(|_| OnceCell::new()).collect();
+        Ok(PrecomputedKey { window, bases, tables })
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Number of prepared generators, including the blinding base.
+    pub fn num_generators(&self) -> usize {
+        self.bases.len()
+    }
+
+    /// Bytes one generator's table occupies at `window`.
+    pub fn table_bytes_per_generator(window: usize) -> usize {
+        (Fr::MODULUS_BIT_SIZE as usize).div_ceil(window) * (1 << window) * size_of::<G1Affine>()
+    }
+
+    /// Bytes held by the tables built so far; at most
+    /// `num_generators() * table_bytes_per_generator(window())`.
+    pub fn table_bytes(&self) -> usize {
+        let built = self.tables.iter().filter(|t| t.get().is_some()).count();
+        built * Self::table_bytes_per_generator(self.window)
+    }
+
+    fn check_len(&self, len: usize) -> Result<()> {
+        if len >= self.bases.len() {
+            return Err(Error::SetupTooShort { expected: len + 1, got: self.bases.len() });
+        }
+        Ok(())
+    }
+
+    /// `bases[i] * s` using the table for generator `i`.
+    fn mul(&self, i: usize, s: &Fr) -> G1Projective {
+        let bits = s.into_bigint().to_bits_le();
+        self.tables[i]
+            .get_or_init(|| build_table(self.bases[i], self.window))
+            .iter()
+            .enumerate()
+            .map(|(k, row)| {
+                let digit = (0..self.window)
+                    .filter(|b| bits.get(k * self.window + b).copied().unwrap_or(false))
+                    .fold(0usize, |acc, b| acc | (1 << b));
+                row[digit]
+            })
+            .fold(G1Projective::zero(), |acc, p| acc + p)
+    }
+
+    /// Same result as `pcs::commit`.
+    pub fn commit(&self, v: &[Fr], r: Fr) -> Result<G1Affine> {
+        self.check_len(v.len())?;
+
+        let sum = v
+            .iter()
+            .enumerate()
+            .fold(self.mul(0, &r), |acc, (i, v_i)| acc + self.mul(i + 1, v_i));
+        Ok(sum.into_affine())
+    }
+
+    /// Same result as `pcs::open`.
+    pub fn open(&self, v: &[Fr], r: Fr, j: usize) -> Result<(Fr, Fr, G1Affine)> {
+        self.check_len(v.len())?;
+        if j >= v.len() {
+            return Err(Error::IndexOutOfBounds);
+        }
+
+        let witness = v
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != j)
+            .fold(self.mul(0, &r), |acc, (i, v_i)| acc + self.mul(i + 1, v_i));
+        Ok((v[j], r, witness.into_affine()))
+    }
+}
+
+fn build_table(base: G1Affine, window: usize) -> Table {
+    let rows = (Fr::MODULUS_BIT_SIZE as usize).div_ceil(window);
+    let mut g = base.into_group();
+    (0..rows)
+        .map(|_| {
+            let mut row = Vec::with_capacity(1 << window);
+            let mut acc = G1Projective::zero();
+            for _ in 0..(1 << window) {
+                row.push(acc);
+                acc += g;
+            }
+            for _ in 0..window {
+                g.double_in_place();
+            }
+            G1Projective::normalize_batch(&row)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcs::{commit, commit_with, open, open_with};
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_precomputed_matches_commit() {
+        let mut rng = thread_rng();
+        let key = PrecomputedKey::default();
+        let inputs: Vec<(Vec<Fr>, Fr)> = (0..32)
+            .map(|_| ((0..POINTS.len() - 1).map(|_| Fr::rand(&mut rng)).collect(), Fr::rand(&mut rng)))
+            .collect();
+
+        let plain: Vec<_> = inputs.iter().map(|(v, r)| commit(v, *r).unwrap()).collect();
+        let fast: Vec<_> = inputs.iter().map(|(v, r)| key.commit(v, *r).unwrap()).collect();
+        assert_eq!(plain, fast);
+
+        let (v, r) = &inputs[0];
+        let (v_4, _, witness) = key.open(v, *r, 4).unwrap();
+        let opening = open(v, *r, 4).unwrap();
+        assert_eq!((v_4, witness), (opening.value, opening.witness));
+        assert_eq!(key.commit(&v[1..], *r).unwrap(), commit(&v[1..], *r).unwrap());
+        assert!(key.commit(&[v.as_slice(), &[Fr::zero()]].concat(), *r).is_err());
+        assert!(key.open(v, *r, v.len()).is_err());
+    }
+
+    #[test]
+    fn test_small_windows() {
+        let mut rng = thread_rng();
+        let v: Vec<Fr> = (0..POINTS.len() - 1).map(|_| Fr::rand(&mut rng)).collect();
+        let r = Fr::rand(&mut rng);
+        for window in [1, 3, 5] {
+            assert_eq!(PrecomputedKey::new(window).unwrap().commit(&v, r).unwrap(), commit(&v, r).unwrap());
+        }
+        for window in [0, 16, usize::MAX] {
+            assert_eq!(PrecomputedKey::new(window).err(), Some(Error::InvalidWindow(window)));
+            assert_eq!(PrecomputedKey::with_generators(4, window).err(), Some(Error::InvalidWindow(window)));
+        }
+    }
+
+    #[test]
+    fn test_prepared_prefix() {
+        let mut rng = thread_rng();
+        let key = PrecomputedKey::with_generators(6, 4).unwrap();
+        assert_eq!(key.table_bytes(), 0);
+
+        let v: Vec<Fr> = (0..3).map(|_| Fr::rand(&mut rng)).collect();
+        let r = Fr::rand(&mut rng);
+        assert_eq!(commit_with(&key, &v, r).unwrap(), commit(&v, r).unwrap());
+        // Only the blinding base and the three value generators were touched
+        assert_eq!(key.table_bytes(), 4 * PrecomputedKey::table_bytes_per_generator(4));
+
+        let v: Vec<Fr> = (0..5).map(|_| Fr::rand(&mut rng)).collect();
+        assert_eq!(open_with(&key, &v, r, 2).unwrap(), open(&v, r, 2).unwrap());
+        assert_eq!(key.table_bytes(), key.num_generators() * PrecomputedKey::table_bytes_per_generator(key.window()));
+
+        let too_long: Vec<Fr> = (0..6).map(|_| Fr::rand(&mut rng)).collect();
+        assert_eq!(commit_with(&key, &too_long, r), Err(Error::SetupTooShort { expected: 7, got: 6 }));
+    }
+
+    #[cfg(not(feature = "verify-only"))]
+    #[test]
+    fn test_beyond_points() {
+        let mut rng = thread_rng();
+        let n = POINTS.len() + 10;
+        let key = PrecomputedKey::with_generators(n + 1, 3).unwrap();
+        let v: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let r = Fr::rand(&mut rng);
+        assert_eq!(commit_with(&key, &v, r).unwrap(), commit(&v, r).unwrap());
+        assert_eq!(open_with(&key, &v, r, n - 1).unwrap(), open(&v, r, n - 1).unwrap());
+    }
+
+    #[test]
+    fn test_shared_across_threads() {
+        let mut rng = thread_rng();
+        let key = PrecomputedKey::new(4).unwrap();
+        let inputs: Vec<(Vec<Fr>, Fr)> = (0..4)
+            .map(|_| ((0..POINTS.len() - 1).map(|_| Fr::rand(&mut rng)).collect(), Fr::rand(&mut rng)))
+            .collect();
+        let shared: Vec<G1Affine> = std::thread::scope(|s| {
+            let handles: Vec<_> = inputs.iter().map(|(v, r)| s.spawn(|| commit_with(&key, v, *r).unwrap())).collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        for ((v, r), c) in inputs.iter().zip(shared) {
+            assert_eq!(c, commit(v, *r).unwrap());
+        }
+    }
+}
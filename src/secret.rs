@@ -0,0 +1,186 @@
+//! Secret wrappers that wipe blinding factors and committed values on drop.
+use ark_bls12_377::{Fr, G1Affine};
+use ark_serialize::CanonicalSerialize;
+use ark_std::collections::BTreeSet;
+use ark_std::vec::Vec;
+use sha2::{Digest, Sha256};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::error::{Error, Result};
+use crate::pcs::{commit, open, Opening};
+
+/// A blinding factor that is zeroized when dropped.
+#[derive(Clone)]
+pub struct Blinding(Fr);
+
+impl Blinding {
+    pub fn new(r: Fr) -> Self {
+        Blinding(r)
+    }
+
+    /// Copies the secret out; the copy is not zeroized.
+    pub fn expose(&self) -> Fr {
+        self.0
+    }
+}
+
+impl Zeroize for Blinding {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for Blinding {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for Blinding {}
+
+/// A committed vector that is zeroized when dropped.
+#[derive(Clone)]
+pub struct SecretVector(Vec<Fr>);
+
+impl SecretVector {
+    pub fn new(v: Vec<Fr>) -> Self {
+        SecretVector(v)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Zeroize for SecretVector {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for SecretVector {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SecretVector {}
+
+/// `commit` over zeroizing secrets.
+pub fn commit_secret(v: &SecretVector, r: &Blinding) -> Result<G1Affine> {
+    commit(&v.0, r.0)
+}
+
+/// `open` over zeroizing secrets.
+pub fn open_secret(v: &SecretVector, r: &Blinding, j: usize) -> Result<Opening> {
+    open(&v.0, r.0, j)
+}
+
+/// Opt-in guard against blinding reuse. Committing two vectors `v`, `w` with
+/// the same `r` reveals `c_v - c_w`, a commitment to `v - w` with no blinding.
+///
+/// Only SHA-256 digests of the blinders are kept, never the blinders. Reuse is
+/// only caught among commitments made through the same tracker during its
+/// lifetime; nothing is persisted.
+#[derive(Default)]
+pub struct BlindingTracker {
+    seen: BTreeSet<[u8; 32]>,
+}
+
+impl BlindingTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of distinct blinders recorded.
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+
+    /// `commit`, failing with `BlindingReused` if `r` was already used through
+    /// this tracker. `r` is recorded only when the commitment succeeds.
+    pub fn commit_tracked(&mut self, v: &[Fr], r: Fr) -> Result<G1Affine> {
+        let mut buf = [0u8; 32];
+        r.serialize_compressed(&mut buf[..]).unwrap();
+        let digest: [u8; 32] = Sha256::digest(buf).into();
+        buf.zeroize();
+
+        if self.seen.contains(&digest) {
+            return Err(Error::BlindingReused);
+        }
+        let c = commit(v, r)?;
+        self.seen.insert(digest);
+        Ok(c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcs::{check, max_vector_len, POINTS};
+    use ark_ff::{UniformRand, Zero};
+    use rand::thread_rng;
+
+    #[test]
+    fn test_secret_commit_open() {
+        let mut rng = thread_rng();
+        let values: Vec<Fr> = (0..POINTS.len() - 1).map(|_| Fr::rand(&mut rng)).collect();
+        let r = Fr::rand(&mut rng);
+
+        let v = SecretVector::new(values.clone());
+        let blinding = Blinding::new(r);
+        let c = commit_secret(&v, &blinding).unwrap();
+        assert_eq!(c, commit(&values, r).unwrap());
+
+        let opening = open_secret(&v, &blinding, 2).unwrap();
+        assert!(check(c, 2, opening.value, opening.witness).unwrap());
+    }
+
+    #[test]
+    fn test_blinding_tracker() {
+        let mut rng = thread_rng();
+        let v: Vec<Fr> = (0..POINTS.len() - 1).map(|_| Fr::rand(&mut rng)).collect();
+        let w: Vec<Fr> = (0..POINTS.len() - 1).map(|_| Fr::rand(&mut rng)).collect();
+        let (r1, r2) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+
+        let mut tracker = BlindingTracker::new();
+        assert_eq!(tracker.commit_tracked(&v, r1).unwrap(), commit(&v, r1).unwrap());
+        assert!(tracker.commit_tracked(&w, r2).is_ok());
+        assert_eq!(tracker.commit_tracked(&w, r1), Err(Error::BlindingReused));
+        assert_eq!(tracker.commit_tracked(&v, r2), Err(Error::BlindingReused));
+        assert_eq!(tracker.len(), 2);
+
+        // A failed commitment does not burn the blinder
+        let r3 = Fr::rand(&mut rng);
+        let too_long = vec![Fr::zero(); max_vector_len() + 1];
+        assert!(tracker.commit_tracked(&too_long, r3).is_err());
+        assert!(tracker.commit_tracked(&v, r3).is_ok());
+
+        // A fresh tracker knows nothing of earlier commitments
+        assert!(BlindingTracker::new().commit_tracked(&v, r1).is_ok());
+    }
+
+    #[test]
+    fn test_zeroize_clears_secrets() {
+        let mut rng = thread_rng();
+        let mut blinding = Blinding::new(Fr::rand(&mut rng));
+        blinding.zeroize();
+        assert!(blinding.0.is_zero());
+
+        let mut v = SecretVector::new((0..4).map(|_| Fr::rand(&mut rng)).collect());
+        v.zeroize();
+        assert!(v.is_empty());
+
+        fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<Blinding>();
+        assert_zeroize_on_drop::<SecretVector>();
+    }
+}
@@ -0,0 +1,115 @@
+//! gRPC surface for the syncing operations (see `proto/sync.proto`).
+use std::sync::Mutex;
+
+use ark_bls12_377::{Fr, G1Affine};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use tonic::{Request, Response, Status};
+
+use crate::accumulator::{check_non_membership, insert, State};
+use crate::key::CommitmentKey;
+use crate::pcs::deserialize_checked;
+
+pub mod proto {
+    tonic::include_proto!("sync");
+}
+
+use proto::syncing_server::Syncing;
+pub use proto::syncing_server::SyncingServer;
+use proto::{
+    CommitRequest, CommitResponse, InsertRequest, NonMembershipRequest, OpenRequest, OpenResponse, StateResponse,
+    VerifyRequest, VerifyResponse,
+};
+
+fn decode<T: CanonicalDeserialize>(bytes: &[u8]) -> Result<T, Status> {
+    T::deserialize_compressed(bytes).map_err(|e| Status::invalid_argument(e.to_string()))
+}
+
+fn decode_point(bytes: &[u8]) -> Result<G1Affine, Status> {
+    deserialize_checked(bytes).map_err(|e| Status::invalid_argument(e.to_string()))
+}
+
+fn decode_all(items: &[Vec<u8>]) -> Result<Vec<Fr>, Status> {
+    items.iter().map(|b| decode(b)).collect()
+}
+
+fn encode(x: &impl CanonicalSerialize) -> Vec<u8> {
+    let mut buf = Vec::new();
+    x.serialize_compressed(&mut buf).unwrap();
+    buf
+}
+
+fn state_response(state: &State) -> StateResponse {
+    StateResponse { accumulator: encode(&state.Accumulator), commitment: encode(&state.Commitment) }
+}
+
+/// Holds the commitment key and the heads of the insert and non-membership chains.
+pub struct SyncService {
+    key: CommitmentKey,
+    accumulator: Mutex<G1Affine>,
+    non_membership: Mutex<G1Affine>,
+}
+
+impl SyncService {
+    pub fn new(key: CommitmentKey, genesis: G1Affine) -> Self {
+        SyncService { key, accumulator: Mutex::new(genesis), non_membership: Mutex::new(genesis) }
+    }
+}
+
+impl Default for SyncService {
+    fn default() -> Self {
+        Self::new(CommitmentKey::default(), G1Affine::default())
+    }
+}
+
+#[tonic::async_trait]
+impl Syncing for SyncService {
+    async fn commit(&self, request: Request<CommitRequest>) -> Result<Response<CommitResponse>, Status> {
+        let req = request.into_inner();
+        let c = self
+            .key
+            .commit(&decode_all(&req.values)?, decode(&req.blinding)?)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        Ok(Response::new(CommitResponse { commitment: encode(&c) }))
+    }
+
+    async fn open(&self, request: Request<OpenRequest>) -> Result<Response<OpenResponse>, Status> {
+        let req = request.into_inner();
+        let (value, _, witness) = self
+            .key
+            .open(&decode_all(&req.values)?, decode(&req.blinding)?, req.index as usize)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        Ok(Response::new(OpenResponse { value: encode(&value), witness: encode(&witness) }))
+    }
+
+    async fn verify(&self, request: Request<VerifyRequest>) -> Result<Response<VerifyResponse>, Status> {
+        let req = request.into_inner();
+        let valid = self.key.check(
+            decode_point(&req.commitment)?,
+            decode(&req.value)?,
+            decode_point(&req.witness)?,
+            req.index as usize,
+        );
+        Ok(Response::new(VerifyResponse { valid }))
+    }
+
+    async fn insert(&self, request: Request<InsertRequest>) -> Result<Response<StateResponse>, Status> {
+        let req = request.into_inner();
+        let roots = decode_all(&req.roots)?;
+        let r = decode(&req.blinding)?;
+        let mut head = self.accumulator.lock().unwrap();
+        let state = insert(&roots, *head, r).map_err(|e| Status::invalid_argument(e.to_string()))?;
+        *head = state.Accumulator;
+        Ok(Response::new(state_response(&state)))
+    }
+
+    async fn non_membership(&self, request: Request<NonMembershipRequest>) -> Result<Response<StateResponse>, Status> {
+        let req = request.into_inner();
+        let roots = decode_all(&req.roots)?;
+        let v = decode(&req.value)?;
+        let r = decode(&req.blinding)?;
+        let mut head = self.non_membership.lock().unwrap();
+        let proof = check_non_membership(&roots, v, r, *head).map_err(|e| Status::failed_precondition(e.to_string()))?;
+        *head = proof.state.Accumulator;
+        Ok(Response::new(state_response(&proof.state)))
+    }
+}
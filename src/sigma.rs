@@ -0,0 +1,927 @@
+use anyhow::Result;
+use ark_bls12_377::{Fr, G1Affine};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{One, UniformRand, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rand::{thread_rng, CryptoRng, RngCore};
+
+use crate::curve::{Bls12_377, PcsCurve};
+use crate::error::Error;
+use crate::lazy::Lazy;
+use crate::pcs::{batch_check, batch_open, commit, validate_point, value_generator, BatchOpening, POINTS};
+use crate::transcript::Transcript;
+
+/// Fiat-Shamir challenge for the proof type `label` over its statement and
+/// first-round messages, derived through a `Transcript`.
+pub(crate) fn challenge(label: &[u8], points: &[G1Affine], scalars: &[Fr]) -> Fr {
+    let mut transcript = Transcript::new(label);
+    for p in points {
+        transcript.append_point(b"point", p);
+    }
+    for x in scalars {
+        transcript.append_scalar(b"scalar", x);
+    }
+    transcript.challenge_scalar(b"challenge")
+}
+
+/// Schnorr proof that `c1 - c2 == POINTS[0] * (r1 - r2)`, i.e. both commitments
+/// hide the same vector and differ only in blinding.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct EqualityProof {
+    pub t: G1Affine,
+    pub s: Fr,
+}
+
+pub fn prove_equality(v: &[Fr], r1: Fr, r2: Fr) -> Result<EqualityProof> {
+    prove_equality_with_rng(v, r1, r2, &mut thread_rng())
+}
+
+/// `prove_equality` drawing its nonce from `rng`; a seeded `rng` makes the
+/// proof reproducible.
+pub fn prove_equality_with_rng<R: RngCore + CryptoRng>(v: &[Fr], r1: Fr, r2: Fr, rng: &mut R) -> Result<EqualityProof> {
+    Ok(prove_equal(commit(v, r1)?, commit(v, r2)?, r1, r2, rng))
+}
+
+pub fn verify_equality(c1: G1Affine, c2: G1Affine, proof: &EqualityProof) -> bool {
+    verify_equal(c1, c2, proof)
+}
+
+/// `prove_equality` for a prover who kept the commitments and blindings but
+/// not the vector. If `c1` and `c2` hide different vectors, or `r1`, `r2` are
+/// not their blindings, the proof does not verify.
+pub fn prove_equal<R: RngCore + CryptoRng>(c1: G1Affine, c2: G1Affine, r1: Fr, r2: Fr, rng: &mut R) -> EqualityProof {
+    let k = Fr::rand(rng);
+    let t = (POINTS[0] * k).into_affine();
+    let e = challenge(b"equality", &[POINTS[0], c1, c2, t], &[]);
+    EqualityProof { t, s: k + e * (r1 - r2) }
+}
+
+pub fn verify_equal(c1: G1Affine, c2: G1Affine, proof: &EqualityProof) -> bool {
+    if [c1, c2, proof.t].iter().any(|p| validate_point(p).is_err()) {
+        return false;
+    }
+    let e = challenge(b"equality", &[POINTS[0], c1, c2, proof.t], &[]);
+    POINTS[0] * proof.s == proof.t + (c1 - c2) * e
+}
+
+/// Adds `[delta_r]POINTS[0]` to `c`: same vector, fresh blinding `r + delta_r`.
+pub fn rerandomize(c: G1Affine, delta_r: Fr) -> G1Affine {
+    (c + POINTS[0] * delta_r).into_affine()
+}
+
+/// Rerandomizes `c` under a fresh `delta_r` from `rng` and returns it too: the
+/// holder opens the new commitment with blinding `r + delta_r`, while copies
+/// published with different deltas cannot be linked to each other.
+pub fn rerandomize_with_rng<R: RngCore + CryptoRng>(c: G1Affine, rng: &mut R) -> (G1Affine, Fr) {
+    let delta_r = Fr::rand(rng);
+    (rerandomize(c, delta_r), delta_r)
+}
+
+/// `rerandomize_with_rng` plus a `RerandProof` linking the new commitment to
+/// `c`, for the parties that may learn the link.
+pub fn rerandomize_with_proof<R: RngCore + CryptoRng>(c: G1Affine, rng: &mut R) -> (G1Affine, Fr, RerandProof) {
+    let (c_new, delta_r) = rerandomize_with_rng(c, rng);
+    let proof = prove_rerandomization_with_rng(c, c_new, delta_r, rng);
+    (c_new, delta_r, proof)
+}
+
+/// Schnorr proof that `c_new - c_old == POINTS[0] * delta_r`, i.e. a relay only
+/// changed the blinding and the committed vector is untouched.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct RerandProof {
+    pub t: G1Affine,
+    pub s: Fr,
+}
+
+pub fn prove_rerandomization(c_old: G1Affine, c_new: G1Affine, delta_r: Fr) -> RerandProof {
+    prove_rerandomization_with_rng(c_old, c_new, delta_r, &mut thread_rng())
+}
+
+pub fn prove_rerandomization_with_rng<R: RngCore + CryptoRng>(
+    c_old: G1Affine,
+    c_new: G1Affine,
+    delta_r: Fr,
+    rng: &mut R,
+) -> RerandProof {
+    let k = Fr::rand(rng);
+    let t = (POINTS[0] * k).into_affine();
+    let e = challenge(b"rerandomization", &[POINTS[0], c_old, c_new, t], &[]);
+    RerandProof { t, s: k + e * delta_r }
+}
+
+pub fn verify_rerandomization(c_old: G1Affine, c_new: G1Affine, proof: &RerandProof) -> bool {
+    if [c_old, c_new, proof.t].iter().any(|p| validate_point(p).is_err()) {
+        return false;
+    }
+    let e = challenge(b"rerandomization", &[POINTS[0], c_old, c_new, proof.t], &[]);
+    POINTS[0] * proof.s == proof.t + (c_new - c_old) * e
+}
+
+/// Schnorr proof that `c3` commits to `a·v1 + b·v2`, where `c1` and `c2`
+/// commit to `v1` and `v2` and `a`, `b` are public.
+///
+/// If the relation holds, `c3 - [a]c1 - [b]c2 = POINTS[0] * (r3 - a·r1 - b·r2)`,
+/// and the proof shows knowledge of that discrete log. Otherwise the difference
+/// has a nonzero value component and no such proof exists. Nothing about the
+/// vectors or blindings is revealed.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct LinearRelationProof {
+    pub t: G1Affine,
+    pub s: Fr,
+}
+
+fn linear_relation_challenge(c1: G1Affine, c2: G1Affine, c3: G1Affine, a: Fr, b: Fr, t: G1Affine) -> Fr {
+    challenge(b"linear-relation", &[POINTS[0], c1, c2, c3, t], &[a, b])
+}
+
+/// Proves `c3` commits to `a·v1 + b·v2` given each commitment's blinding; see
+/// `LinearRelationProof`. If the relation does not hold, or the blindings are
+/// wrong, the proof does not verify.
+pub fn prove_linear<R: RngCore + CryptoRng>(
+    (c1, r1): (G1Affine, Fr),
+    (c2, r2): (G1Affine, Fr),
+    (c3, r3): (G1Affine, Fr),
+    (a, b): (Fr, Fr),
+    rng: &mut R,
+) -> LinearRelationProof {
+    let k = Fr::rand(rng);
+    let t = (POINTS[0] * k).into_affine();
+    let e = linear_relation_challenge(c1, c2, c3, a, b, t);
+    LinearRelationProof { t, s: k + e * (r3 - a * r1 - b * r2) }
+}
+
+/// Checks a `LinearRelationProof` that `c3` commits to `a·v1 + b·v2`.
+pub fn verify_linear(c1: G1Affine, c2: G1Affine, c3: G1Affine, a: Fr, b: Fr, proof: &LinearRelationProof) -> bool {
+    if [c1, c2, c3, proof.t].iter().any(|p| validate_point(p).is_err()) {
+        return false;
+    }
+    let e = linear_relation_challenge(c1, c2, c3, a, b, proof.t);
+    POINTS[0] * proof.s == proof.t + (c3 - c1 * a - c2 * b) * e
+}
+
+/// Domain separation tag for `VALUE_HASH_BASE`, distinct from the setup DST.
+pub const VALUE_HASH_DST: &[u8] = b"OBLIVIOUS-SYNC-V01_VALUE_HASH_XMD:SHA-256_SSWU_RO_";
+
+/// Generator `G_H` for `hash_value`, with no known discrete log relative to `POINTS`.
+pub static VALUE_HASH_BASE: Lazy<G1Affine> = Lazy::new(|| Bls12_377::hash_to_curve(VALUE_HASH_DST, b"value-hash").expect("VALUE_HASH_DST is valid"));
+
+/// Group hash of a committed value, `[v]G_H`.
+pub fn hash_value(v: Fr) -> G1Affine {
+    (*VALUE_HASH_BASE * v).into_affine()
+}
+
+/// Chaum-Pedersen proof that an opening `(c, witness)` at base `h_j` and a
+/// value hash `Y` share the same exponent: `c - witness = [v_j]h_j` and
+/// `Y = [v_j]G_H`.
+///
+/// Guarantee: a verifier holding `Y = hash_value(v_j)` learns that the opened
+/// slot holds the preimage of `Y`, without being sent `v_j`. The hash is a
+/// group hash rather than e.g. SHA-256, since proving a bit-oriented hash
+/// preimage needs a general-purpose proof system. Like any deterministic hash
+/// it hides only high-entropy values: a low-entropy `v_j` can be recovered by
+/// trying candidates against `Y` (or against `c - witness` itself).
+pub struct CommittedHashProof {
+    pub t_opening: G1Affine,
+    pub t_hash: G1Affine,
+    pub s: Fr,
+}
+
+/// Proves the opening of `c` at `h_j` with this `witness` holds the preimage of `hash_value(v_j)`.
+pub fn prove_committed_hash(c: G1Affine, v_j: Fr, witness: G1Affine, h_j: G1Affine) -> CommittedHashProof {
+    prove_committed_hash_with_rng(c, v_j, witness, h_j, &mut thread_rng())
+}
+
+pub fn prove_committed_hash_with_rng<R: RngCore + CryptoRng>(
+    c: G1Affine,
+    v_j: Fr,
+    witness: G1Affine,
+    h_j: G1Affine,
+    rng: &mut R,
+) -> CommittedHashProof {
+    let x = (c - witness).into_affine();
+    let y = hash_value(v_j);
+
+    let k = Fr::rand(rng);
+    let t_opening = (h_j * k).into_affine();
+    let t_hash = (*VALUE_HASH_BASE * k).into_affine();
+    let e = challenge(b"committed-hash", &[h_j, *VALUE_HASH_BASE, x, y, t_opening, t_hash], &[]);
+
+    CommittedHashProof { t_opening, t_hash, s: k + e * v_j }
+}
+
+/// Checks that the value opened by `witness` at `h_j` in `c` hashes to `value_hash`.
+pub fn check_committed_hash(
+    c: G1Affine,
+    h_j: G1Affine,
+    witness: G1Affine,
+    value_hash: G1Affine,
+    proof: &CommittedHashProof,
+) -> bool {
+    if validate_point(&c).is_err() || validate_point(&witness).is_err() {
+        return false;
+    }
+    let x = (c - witness).into_affine();
+    let e = challenge(b"committed-hash", &[h_j, *VALUE_HASH_BASE, x, value_hash, proof.t_opening, proof.t_hash], &[]);
+    h_j * proof.s == proof.t_opening + x * e && *VALUE_HASH_BASE * proof.s == proof.t_hash + value_hash * e
+}
+
+/// Proof of knowledge of `(r, u)` with `witness = commit(u, r)` and `u` zero
+/// at every opened index.
+///
+/// `batch_check` alone accepts `witness = c - Σ [y_j] G_{j+1}` for any claimed
+/// `y`, so it only binds an opener who builds the witness honestly. With this
+/// proof the opener must know a representation of the witness that skips the
+/// opened generators; together with `c - witness = Σ [y_j] G_{j+1}` that gives
+/// an opening of `c` with `y` at the indices, and a second one with other
+/// values would be a discrete-log relation between `POINTS`. The challenge
+/// absorbs `c`, the witness and the index set, so a proof is not reusable for
+/// another commitment or another subset.
+pub struct BatchOpeningProof {
+    pub t: G1Affine,
+    /// Responses over the whole vector; zero at the opened indices.
+    pub s: Vec<Fr>,
+    pub s_r: Fr,
+}
+
+fn batch_opening_challenge(c: G1Affine, witness: G1Affine, t: G1Affine, values: &[Fr], indices: &[usize]) -> Fr {
+    let scalars: Vec<Fr> = indices.iter().map(|&j| Fr::from(j as u64)).chain(values.iter().copied()).collect();
+    challenge(b"batch-opening", &[c, witness, t], &scalars)
+}
+
+/// `batch_open` together with a `BatchOpeningProof` for the returned witness.
+/// Returns `(values, witness, proof)`, with values in the order of `indices`.
+pub fn prove_batch_opening(v: &[Fr], r: Fr, indices: &[usize]) -> Result<(Vec<Fr>, G1Affine, BatchOpeningProof)> {
+    prove_batch_opening_with_rng(v, r, indices, &mut thread_rng())
+}
+
+pub fn prove_batch_opening_with_rng<R: RngCore + CryptoRng>(
+    v: &[Fr],
+    r: Fr,
+    indices: &[usize],
+    rng: &mut R,
+) -> Result<(Vec<Fr>, G1Affine, BatchOpeningProof)> {
+    let c = commit(v, r)?;
+    let BatchOpening { values, witness, .. } = batch_open(v, r, indices.iter().copied())?;
+
+    let mut k: Vec<Fr> = (0..v.len()).map(|_| Fr::rand(rng)).collect();
+    let mut u = v.to_vec();
+    for &j in indices {
+        k[j] = Fr::zero();
+        u[j] = Fr::zero();
+    }
+    let k_r = Fr::rand(rng);
+    let t = commit(&k, k_r)?;
+    let e = batch_opening_challenge(c, witness, t, &values, indices);
+
+    let s = k.iter().zip(&u).map(|(k_i, u_i)| *k_i + e * u_i).collect();
+    Ok((values, witness, BatchOpeningProof { t, s, s_r: k_r + e * r }))
+}
+
+/// `batch_check` plus the `BatchOpeningProof` that the witness does not hide
+/// other values at `indices`. Errors as `batch_check` does on malformed input.
+pub fn verify_batch_opening(
+    c: G1Affine,
+    values: &[Fr],
+    witness: G1Affine,
+    indices: &[usize],
+    proof: &BatchOpeningProof,
+) -> Result<bool> {
+    // The responses span the committed vector, so they also give its length
+    if !batch_check(c, values, witness, indices, proof.s.len())? {
+        return Ok(false);
+    }
+    if indices.iter().any(|&j| proof.s[j] != Fr::zero()) {
+        return Ok(false);
+    }
+    if validate_point(&proof.t).is_err() {
+        return Ok(false);
+    }
+    let e = batch_opening_challenge(c, witness, proof.t, values, indices);
+    Ok(commit(&proof.s, proof.s_r)? == (proof.t + witness * e).into_affine())
+}
+
+/// Opening of value `j` that proves knowledge of the rest of the vector and
+/// the blinding without sending the witness.
+///
+/// The verifier computes `witness = c - [v_j]G_{j+1}` itself, and the proof
+/// shows knowledge of `(r, u)` with `witness = commit(u, r)` and `u_j = 0`, as
+/// a one-index `BatchOpeningProof` does. Every response is `k + e·x` for a
+/// fresh uniform `k`, so the proof is uniform whatever `r` and the other
+/// values are.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ZkOpening {
+    pub t: G1Affine,
+    /// Responses over the whole vector; zero at the opened index.
+    pub s: Vec<Fr>,
+    pub s_r: Fr,
+}
+
+fn zk_opening_challenge(c: G1Affine, j: usize, v_j: Fr, t: G1Affine) -> Fr {
+    challenge(b"zk-opening", &[c, t], &[Fr::from(j as u64), v_j])
+}
+
+/// Proves that `commit(v, r)` holds `v[j]` at index `j`; see `ZkOpening`.
+pub fn open_zk<R: RngCore + CryptoRng>(v: &[Fr], r: Fr, j: usize, rng: &mut R) -> Result<ZkOpening> {
+    if j >= v.len() {
+        return Err(Error::IndexOutOfBounds.into());
+    }
+    let c = commit(v, r)?;
+
+    let mut k: Vec<Fr> = (0..v.len()).map(|_| Fr::rand(rng)).collect();
+    k[j] = Fr::zero();
+    let k_r = Fr::rand(rng);
+    let t = commit(&k, k_r)?;
+    let e = zk_opening_challenge(c, j, v[j], t);
+
+    let mut s: Vec<Fr> = k.iter().zip(v).map(|(k_i, v_i)| *k_i + e * v_i).collect();
+    s[j] = Fr::zero();
+    Ok(ZkOpening { t, s, s_r: k_r + e * r })
+}
+
+/// Checks a `ZkOpening` of value `j` of `c` to `v_j`.
+pub fn verify_zk(c: G1Affine, j: usize, v_j: Fr, proof: &ZkOpening) -> bool {
+    if proof.s.get(j) != Some(&Fr::zero()) {
+        return false;
+    }
+    if validate_point(&c).is_err() || validate_point(&proof.t).is_err() {
+        return false;
+    }
+    let Ok(h_j) = value_generator(j) else {
+        return false;
+    };
+    let witness = c.into_group() - h_j * v_j;
+    let e = zk_opening_challenge(c, j, v_j, proof.t);
+    match commit(&proof.s, proof.s_r) {
+        Ok(lhs) => lhs.into_group() == proof.t + witness * e,
+        Err(_) => false,
+    }
+}
+
+/// Proof that two commitments, to different vectors under different
+/// blindings, hold the same value at index `j`, without revealing it.
+///
+/// With `d = v_a - v_b` (the shorter vector zero-padded), `c_a - c_b =
+/// commit(d, r_a - r_b)`, and the proof shows knowledge of such a `d` with
+/// `d_j = 0`, as a `ZkOpening` of value zero at `j` of `c_a - c_b` would. The
+/// responses reveal the longer vector's length, not the values.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SameAtProof {
+    pub t: G1Affine,
+    /// Responses over the difference vector; zero at `j`.
+    pub s: Vec<Fr>,
+    pub s_r: Fr,
+}
+
+fn same_at_challenge(c_a: G1Affine, c_b: G1Affine, j: usize, t: G1Affine) -> Fr {
+    challenge(b"same-at", &[c_a, c_b, t], &[Fr::from(j as u64)])
+}
+
+/// Proves that `c_a = commit(v_a, r_a)` and `c_b = commit(v_b, r_b)` agree at
+/// index `j`; see `SameAtProof`. Errors if `j` is outside either vector. If the
+/// values differ, the proof does not verify.
+pub fn prove_same_at<R: RngCore + CryptoRng>(
+    c_a: G1Affine,
+    (v_a, r_a): (&[Fr], Fr),
+    c_b: G1Affine,
+    (v_b, r_b): (&[Fr], Fr),
+    j: usize,
+    rng: &mut R,
+) -> Result<SameAtProof> {
+    if j >= v_a.len() || j >= v_b.len() {
+        return Err(Error::IndexOutOfBounds.into());
+    }
+    let n = v_a.len().max(v_b.len());
+    let d: Vec<Fr> = (0..n)
+        .map(|i| v_a.get(i).copied().unwrap_or_default() - v_b.get(i).copied().unwrap_or_default())
+        .collect();
+
+    let mut k: Vec<Fr> = (0..n).map(|_| Fr::rand(rng)).collect();
+    k[j] = Fr::zero();
+    let k_r = Fr::rand(rng);
+    let t = commit(&k, k_r)?;
+    let e = same_at_challenge(c_a, c_b, j, t);
+
+    let mut s: Vec<Fr> = k.iter().zip(&d).map(|(k_i, d_i)| *k_i + e * d_i).collect();
+    s[j] = Fr::zero();
+    Ok(SameAtProof { t, s, s_r: k_r + e * (r_a - r_b) })
+}
+
+/// Checks a `SameAtProof` that `c_a` and `c_b` hold the same value at `j`.
+pub fn verify_same_at(c_a: G1Affine, c_b: G1Affine, j: usize, proof: &SameAtProof) -> bool {
+    if proof.s.get(j) != Some(&Fr::zero()) {
+        return false;
+    }
+    if validate_point(&c_a).is_err() || validate_point(&c_b).is_err() || validate_point(&proof.t).is_err() {
+        return false;
+    }
+    let e = same_at_challenge(c_a, c_b, j, proof.t);
+    match commit(&proof.s, proof.s_r) {
+        Ok(lhs) => lhs.into_group() == proof.t + (c_a - c_b) * e,
+        Err(_) => false,
+    }
+}
+
+/// Proof that the vector in `c` has inner product `value` with a public
+/// weight vector `b`, without opening any slot.
+///
+/// Proves knowledge of `(v, r)` with `c = commit(v, r)` and `<v, b> = value`:
+/// the prover sends `t = commit(k, k_r)` and `t_value = <k, b>`, and answers
+/// the challenge `e` with `s = k + e·v`, `s_r = k_r + e·r`. The verifier checks
+/// `commit(s, s_r) = t + [e]c` and `<s, b> = t_value + e·value`, combining the
+/// weights itself, so only `value` is revealed (and the vector's length).
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct LinearOpening {
+    pub value: Fr,
+    pub t: G1Affine,
+    pub t_value: Fr,
+    pub s: Vec<Fr>,
+    pub s_r: Fr,
+}
+
+fn inner_product(a: &[Fr], b: &[Fr]) -> Fr {
+    a.iter().zip(b).map(|(a_i, b_i)| *a_i * b_i).sum()
+}
+
+fn linear_challenge(label: &[u8], c: G1Affine, b: &[Fr], value: Fr, t: G1Affine, t_value: Fr) -> Fr {
+    let scalars: Vec<Fr> = [value, t_value].into_iter().chain(b.iter().copied()).collect();
+    challenge(label, &[c, t], &scalars)
+}
+
+fn prove_linear_opening<R: RngCore + CryptoRng>(label: &[u8], v: &[Fr], r: Fr, b: &[Fr], rng: &mut R) -> Result<LinearOpening> {
+    if b.len() != v.len() {
+        return Err(Error::LengthMismatch { expected: v.len(), got: b.len() }.into());
+    }
+    let c = commit(v, r)?;
+    let value = inner_product(v, b);
+
+    let k: Vec<Fr> = (0..v.len()).map(|_| Fr::rand(rng)).collect();
+    let k_r = Fr::rand(rng);
+    let t = commit(&k, k_r)?;
+    let t_value = inner_product(&k, b);
+    let e = linear_challenge(label, c, b, value, t, t_value);
+
+    let s = k.iter().zip(v).map(|(k_i, v_i)| *k_i + e * v_i).collect();
+    Ok(LinearOpening { value, t, t_value, s, s_r: k_r + e * r })
+}
+
+fn verify_linear_opening(label: &[u8], c: G1Affine, b: &[Fr], y: Fr, proof: &LinearOpening) -> Result<bool> {
+    if b.len() != proof.s.len() {
+        return Err(Error::LengthMismatch { expected: proof.s.len(), got: b.len() }.into());
+    }
+    if proof.value != y || validate_point(&c).is_err() || validate_point(&proof.t).is_err() {
+        return Ok(false);
+    }
+    let e = linear_challenge(label, c, b, y, proof.t, proof.t_value);
+    if inner_product(&proof.s, b) != proof.t_value + e * y {
+        return Ok(false);
+    }
+    Ok(commit(&proof.s, proof.s_r)?.into_group() == proof.t + c * e)
+}
+
+/// Proves `<v, b> = value` for `c = commit(v, r)`; see `LinearOpening`.
+pub fn open_linear(v: &[Fr], r: Fr, b: &[Fr]) -> Result<LinearOpening> {
+    open_linear_with_rng(v, r, b, &mut thread_rng())
+}
+
+pub fn open_linear_with_rng<R: RngCore + CryptoRng>(v: &[Fr], r: Fr, b: &[Fr], rng: &mut R) -> Result<LinearOpening> {
+    prove_linear_opening(b"linear-opening", v, r, b, rng)
+}
+
+/// Checks that `proof` shows `<v, b> = y` for the vector in `c`. Errors if `b`
+/// and the proof disagree on the vector's length.
+pub fn check_linear(c: G1Affine, b: &[Fr], y: Fr, proof: &LinearOpening) -> Result<bool> {
+    verify_linear_opening(b"linear-opening", c, b, y, proof)
+}
+
+/// `open_linear` with all-ones weights: proves the sum of `v`.
+pub fn open_sum(v: &[Fr], r: Fr) -> Result<LinearOpening> {
+    open_linear(v, r, &vec![Fr::one(); v.len()])
+}
+
+/// Checks that `proof` shows the vector in `c` sums to `y`.
+pub fn check_sum(c: G1Affine, y: Fr, proof: &LinearOpening) -> Result<bool> {
+    check_linear(c, &vec![Fr::one(); proof.s.len()], y, proof)
+}
+
+/// Proof that the coefficient vector in `c` is a polynomial `p` with
+/// `p(x) = value`: a `LinearOpening` with weights `(1, x, x^2, ...)` under its
+/// own transcript domain.
+///
+/// High zero coefficients change neither `p` nor the commitment, so a proof
+/// over `coeffs` also verifies against `commit_padded(coeffs, r)`, e.g. an
+/// accumulator step's `P_i`.
+pub type EvalOpening = LinearOpening;
+
+/// `(1, x, ..., x^(n-1))`.
+fn eval_weights(x: Fr, n: usize) -> Vec<Fr> {
+    core::iter::successors(Some(Fr::one()), |p| Some(*p * x)).take(n).collect()
+}
+
+/// Proves `p(x) = value` for `p` with coefficients `coeffs` (constant term
+/// first) committed as `commit(coeffs, r)`.
+pub fn open_eval(coeffs: &[Fr], r: Fr, x: Fr) -> Result<EvalOpening> {
+    open_eval_with_rng(coeffs, r, x, &mut thread_rng())
+}
+
+pub fn open_eval_with_rng<R: RngCore + CryptoRng>(coeffs: &[Fr], r: Fr, x: Fr, rng: &mut R) -> Result<EvalOpening> {
+    prove_linear_opening(b"eval-opening", coeffs, r, &eval_weights(x, coeffs.len()), rng)
+}
+
+/// Checks that the polynomial committed in `c` evaluates to `y` at `x`.
+pub fn check_eval(c: G1Affine, x: Fr, y: Fr, proof: &EvalOpening) -> Result<bool> {
+    verify_linear_opening(b"eval-opening", c, &eval_weights(x, proof.s.len()), y, proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcs::{open, Opening};
+
+    fn random_vector() -> Vec<Fr> {
+        let mut rng = thread_rng();
+        (0..POINTS.len() - 1).map(|_| Fr::rand(&mut rng)).collect()
+    }
+
+    #[test]
+    fn test_equality_proof() {
+        let mut rng = thread_rng();
+        let v = random_vector();
+        let (r1, r2) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let c1 = commit(&v, r1).unwrap();
+        let c2 = commit(&v, r2).unwrap();
+
+        let proof = prove_equality(&v, r1, r2).unwrap();
+        assert!(verify_equality(c1, c2, &proof));
+
+        // The proof is bound to the ordered pair of commitments
+        assert!(!verify_equality(c2, c1, &proof));
+        let tampered = EqualityProof { t: proof.t, s: proof.s + Fr::one() };
+        assert!(!verify_equality(c1, c2, &tampered));
+    }
+
+    #[test]
+    fn test_equality_proof_different_vectors() {
+        let mut rng = thread_rng();
+        let v = random_vector();
+        let w = random_vector();
+        let (r1, r2) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let c1 = commit(&v, r1).unwrap();
+        let c2 = commit(&w, r2).unwrap();
+
+        let proof = prove_equality(&v, r1, r2).unwrap();
+        assert!(!verify_equality(c1, c2, &proof));
+    }
+
+    #[test]
+    fn test_prove_equal() {
+        let mut rng = thread_rng();
+        let v = random_vector();
+        let (r1, r2) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let c1 = commit(&v, r1).unwrap();
+        let c2 = commit(&v, r2).unwrap();
+
+        let proof = prove_equal(c1, c2, r1, r2, &mut rng);
+        assert!(verify_equal(c1, c2, &proof));
+        assert!(verify_equality(c1, c2, &proof));
+
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).unwrap();
+        let decoded = EqualityProof::deserialize_compressed(&bytes[..]).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(verify_equal(c1, c2, &decoded));
+
+        // Vectors differing at one index
+        let mut w = v.clone();
+        w[7] += Fr::one();
+        let c3 = commit(&w, r2).unwrap();
+        assert!(!verify_equal(c1, c3, &prove_equal(c1, c3, r1, r2, &mut rng)));
+
+        // Same vector, wrong blindings
+        assert!(!verify_equal(c1, c2, &prove_equal(c1, c2, r2, r1, &mut rng)));
+        assert!(!verify_equal(c1, c2, &prove_equal(c1, c2, r1 + Fr::one(), r2, &mut rng)));
+
+        // The identity passes the Schnorr equation against itself but is not a
+        // valid commitment
+        let identity = G1Affine::default();
+        assert!(!verify_equal(identity, identity, &prove_equal(identity, identity, r1, r1, &mut rng)));
+    }
+
+    #[test]
+    fn test_open_after_rerandomize() {
+        let mut rng = thread_rng();
+        let v = random_vector();
+        let r = Fr::rand(&mut rng);
+        let c = commit(&v, r).unwrap();
+
+        let (c1, delta1) = rerandomize_with_rng(c, &mut rng);
+        let (c2, delta2, proof) = rerandomize_with_proof(c, &mut rng);
+        assert_ne!(c1, c);
+        assert_ne!(c1, c2);
+        assert_eq!(c1, commit(&v, r + delta1).unwrap());
+
+        for (c_new, delta) in [(c1, delta1), (c2, delta2)] {
+            let opening = open(&v, r + delta, 9).unwrap();
+            assert!(opening.verify(&c_new).unwrap());
+            assert!(!opening.verify(&c).unwrap());
+        }
+
+        assert!(verify_rerandomization(c, c2, &proof));
+        assert!(!verify_rerandomization(c, c1, &proof));
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).unwrap();
+        let decoded = RerandProof::deserialize_compressed(&bytes[..]).unwrap();
+        assert!(verify_rerandomization(c, c2, &decoded));
+    }
+
+    #[test]
+    fn test_rerandomization_proof() {
+        let mut rng = thread_rng();
+        let v = random_vector();
+        let (r, delta_r) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let c_old = commit(&v, r).unwrap();
+        let c_new = rerandomize(c_old, delta_r);
+        assert_eq!(c_new, commit(&v, r + delta_r).unwrap());
+
+        let proof = prove_rerandomization(c_old, c_new, delta_r);
+        assert!(verify_rerandomization(c_old, c_new, &proof));
+
+        // Wrong delta, swapped commitments, or a tampered response all fail
+        let wrong = prove_rerandomization(c_old, c_new, delta_r + Fr::one());
+        assert!(!verify_rerandomization(c_old, c_new, &wrong));
+        assert!(!verify_rerandomization(c_new, c_old, &proof));
+        let tampered = RerandProof { t: proof.t, s: proof.s + Fr::one() };
+        assert!(!verify_rerandomization(c_old, c_new, &tampered));
+
+        // A changed vector is not a multiple of POINTS[0] away, so no delta works
+        let mut w = v.clone();
+        w[3] += Fr::one();
+        let c_changed = commit(&w, r + delta_r).unwrap();
+        let proof = prove_rerandomization(c_old, c_changed, delta_r);
+        assert!(!verify_rerandomization(c_old, c_changed, &proof));
+
+        // The identity "rerandomized" by zero satisfies the equation but is not
+        // a valid commitment
+        let identity = G1Affine::default();
+        assert!(!verify_rerandomization(identity, identity, &prove_rerandomization(identity, identity, Fr::zero())));
+    }
+
+    #[test]
+    fn test_committed_hash() {
+        let mut rng = thread_rng();
+        let v = random_vector();
+        let r = Fr::rand(&mut rng);
+        let c = commit(&v, r).unwrap();
+        let j = 6;
+        let Opening { value: v_j, witness, .. } = open(&v, r, j).unwrap();
+        let h_j = POINTS[j + 1];
+
+        let proof = prove_committed_hash(c, v_j, witness, h_j);
+        assert!(check_committed_hash(c, h_j, witness, hash_value(v[j]), &proof));
+
+        // A different digest, slot, or commitment fails
+        assert!(!check_committed_hash(c, h_j, witness, hash_value(v[j + 1]), &proof));
+        assert!(!check_committed_hash(c, POINTS[j + 2], witness, hash_value(v[j]), &proof));
+        let other = commit(&random_vector(), r).unwrap();
+        assert!(!check_committed_hash(other, h_j, witness, hash_value(v[j]), &proof));
+
+        // A proof for a wrong value does not verify against either hash
+        let forged = prove_committed_hash(c, v_j + Fr::one(), witness, h_j);
+        assert!(!check_committed_hash(c, h_j, witness, hash_value(v_j + Fr::one()), &forged));
+        assert!(!check_committed_hash(c, h_j, witness, hash_value(v_j), &forged));
+    }
+
+    #[test]
+    fn test_seeded_proofs() {
+        use crate::accumulator::{insert, prove_membership_with_rng, verify_membership};
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut rng = thread_rng();
+        let v = random_vector();
+        let (r1, r2) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let (c1, c2) = (commit(&v, r1).unwrap(), commit(&v, r2).unwrap());
+
+        let prove = |seed| prove_equality_with_rng(&v, r1, r2, &mut StdRng::seed_from_u64(seed)).unwrap();
+        let (a, b, other) = (prove(7), prove(7), prove(8));
+        assert_eq!((a.t, a.s), (b.t, b.s));
+        assert_ne!((a.t, a.s), (other.t, other.s));
+        assert!(verify_equality(c1, c2, &a) && verify_equality(c1, c2, &other));
+
+        let roots: Vec<Fr> = (1..20).map(|_| Fr::rand(&mut rng)).collect();
+        let state = insert(&roots, G1Affine::default(), r1).unwrap();
+        let prove = |seed| prove_membership_with_rng(&roots, roots[2], r1, &mut StdRng::seed_from_u64(seed)).unwrap();
+        let (a, b, other) = (prove(7), prove(7), prove(8));
+        assert_eq!((a.quotient, a.t_q, a.t_p, &a.s, a.s_q, a.s_p), (b.quotient, b.t_q, b.t_p, &b.s, b.s_q, b.s_p));
+        assert_ne!(a.quotient, other.quotient);
+        assert!(verify_membership(state.Commitment, &a) && verify_membership(state.Commitment, &other));
+    }
+
+    #[test]
+    fn test_batch_opening_proof() {
+        let mut rng = thread_rng();
+        let v = random_vector();
+        let r = Fr::rand(&mut rng);
+        let c = commit(&v, r).unwrap();
+
+        // Values come back, and are verified, in the requested order
+        let indices = [11, 2, 5];
+        let (values, witness, proof) = prove_batch_opening(&v, r, &indices).unwrap();
+        assert!(verify_batch_opening(c, &values, witness, &indices, &proof).unwrap());
+
+        // Bound to the commitment and the index set
+        let other = commit(&v, r + Fr::one()).unwrap();
+        assert!(!verify_batch_opening(other, &values, witness, &indices, &proof).unwrap());
+        let shifted = batch_open(&v, r, [2, 5]).unwrap().witness;
+        assert!(!verify_batch_opening(c, &values[..2], shifted, &[2, 5], &proof).unwrap());
+    }
+
+    #[test]
+    fn test_forged_witness_needs_opened_generator() {
+        let mut rng = thread_rng();
+        let v = random_vector();
+        let r = Fr::rand(&mut rng);
+        let c = commit(&v, r).unwrap();
+
+        // Claim a different value at index 3 by folding the difference into the witness
+        let (mut values, witness, _) = prove_batch_opening(&v, r, &[3, 8]).unwrap();
+        values[0] += Fr::one();
+        let forged = (witness - POINTS[4]).into_affine();
+        assert!(batch_check(c, &values, forged, &[3, 8], v.len()).unwrap());
+
+        // The honest representation of the forged witness uses slot 3
+        let mut u = v.clone();
+        u[3] = -Fr::one();
+        u[8] = Fr::zero();
+        let mut k: Vec<Fr> = (0..v.len()).map(|_| Fr::rand(&mut rng)).collect();
+        k[3] = Fr::zero();
+        k[8] = Fr::zero();
+        let k_r = Fr::rand(&mut rng);
+        let t = commit(&k, k_r).unwrap();
+        let e = batch_opening_challenge(c, forged, t, &values, &[3, 8]);
+        let mut s: Vec<Fr> = k.iter().zip(&u).map(|(k_i, u_i)| *k_i + e * u_i).collect();
+        let proof = BatchOpeningProof { t, s: s.clone(), s_r: k_r + e * r };
+        assert!(!verify_batch_opening(c, &values, forged, &[3, 8], &proof).unwrap());
+
+        // Zeroing that response breaks the equation instead
+        s[3] = Fr::zero();
+        let proof = BatchOpeningProof { t, s, s_r: k_r + e * r };
+        assert!(!verify_batch_opening(c, &values, forged, &[3, 8], &proof).unwrap());
+    }
+
+    #[test]
+    fn test_zk_opening() {
+        let mut rng = thread_rng();
+        let v = random_vector();
+        let r = Fr::rand(&mut rng);
+        let c = commit(&v, r).unwrap();
+
+        let proof = open_zk(&v, r, 5, &mut rng).unwrap();
+        assert!(verify_zk(c, 5, v[5], &proof));
+        assert!(!verify_zk(c, 5, v[5] + Fr::one(), &proof));
+        assert!(!verify_zk(c, 4, v[5], &proof));
+        assert!(!verify_zk(commit(&v, r + Fr::one()).unwrap(), 5, v[5], &proof));
+        assert!(open_zk(&v, r, v.len(), &mut rng).is_err());
+
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).unwrap();
+        let decoded = ZkOpening::deserialize_compressed(&bytes[..]).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(verify_zk(c, 5, v[5], &decoded));
+
+        // `r` appears in neither byte order, in either encoding of the proof
+        let mut uncompressed = Vec::new();
+        proof.serialize_uncompressed(&mut uncompressed).unwrap();
+        let mut r_le = Vec::new();
+        r.serialize_compressed(&mut r_le).unwrap();
+        let r_be: Vec<u8> = r_le.iter().rev().copied().collect();
+        for encoding in [&bytes, &uncompressed] {
+            for r_bytes in [&r_le, &r_be] {
+                assert!(!encoding.windows(r_bytes.len()).any(|w| w == &r_bytes[..]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_same_at() {
+        let mut rng = thread_rng();
+        let mut a = random_vector();
+        let mut b: Vec<Fr> = (0..12).map(|_| Fr::rand(&mut rng)).collect();
+        b[3] = a[3];
+        let (r_a, r_b) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let c_a = commit(&a, r_a).unwrap();
+        let c_b = commit(&b, r_b).unwrap();
+
+        let proof = prove_same_at(c_a, (&a, r_a), c_b, (&b, r_b), 3, &mut rng).unwrap();
+        assert!(verify_same_at(c_a, c_b, 3, &proof));
+        assert!(!verify_same_at(c_a, c_b, 4, &proof));
+        assert!(!verify_same_at(c_b, c_a, 3, &proof));
+
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).unwrap();
+        let decoded = SameAtProof::deserialize_compressed(&bytes[..]).unwrap();
+        assert!(verify_same_at(c_a, c_b, 3, &decoded));
+
+        // Values one apart at `j`
+        a[3] += Fr::one();
+        let c_a = commit(&a, r_a).unwrap();
+        let proof = prove_same_at(c_a, (&a, r_a), c_b, (&b, r_b), 3, &mut rng).unwrap();
+        assert!(!verify_same_at(c_a, c_b, 3, &proof));
+
+        // `j` outside the shorter vector
+        assert!(prove_same_at(c_a, (&a, r_a), c_b, (&b, r_b), b.len(), &mut rng).is_err());
+        assert!(!verify_same_at(c_a, c_b, a.len(), &proof));
+    }
+
+    #[test]
+    fn test_linear_opening() {
+        let mut rng = thread_rng();
+        let v = random_vector();
+        let r = Fr::rand(&mut rng);
+        let c = commit(&v, r).unwrap();
+
+        let sum: Fr = v.iter().sum();
+        let proof = open_sum(&v, r).unwrap();
+        assert_eq!(proof.value, sum);
+        assert!(check_sum(c, sum, &proof).unwrap());
+        assert!(!check_sum(c, sum + Fr::one(), &proof).unwrap());
+
+        let b: Vec<Fr> = (0..v.len()).map(|_| Fr::rand(&mut rng)).collect();
+        let y = inner_product(&v, &b);
+        let proof = open_linear(&v, r, &b).unwrap();
+        assert!(check_linear(c, &b, y, &proof).unwrap());
+        assert!(!check_linear(commit(&v, r + Fr::one()).unwrap(), &b, y, &proof).unwrap());
+
+        // A forged value, even with the proof's own field rewritten to match
+        let forged = LinearOpening { value: y + Fr::one(), ..proof.clone() };
+        assert!(!check_linear(c, &b, y + Fr::one(), &forged).unwrap());
+        let mut other = b.clone();
+        other[0] += Fr::one();
+        assert!(!check_linear(c, &other, y, &proof).unwrap());
+        assert!(check_linear(c, &b[1..], y, &proof).is_err());
+        assert!(open_linear(&v, r, &b[1..]).is_err());
+
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(LinearOpening::deserialize_compressed(&bytes[..]).unwrap(), proof);
+    }
+
+    #[test]
+    fn test_eval_opening() {
+        use crate::accumulator::poly_from_roots;
+        use crate::pcs::commit_padded;
+        use ark_poly::Polynomial;
+
+        let mut rng = thread_rng();
+        let roots: Vec<Fr> = (0..6).map(|_| Fr::rand(&mut rng)).collect();
+        let p = poly_from_roots(&roots);
+        let r = Fr::rand(&mut rng);
+        let c = commit(&p.coeffs, r).unwrap();
+        assert_eq!(c, commit_padded(&p.coeffs, r).unwrap());
+
+        let x = Fr::rand(&mut rng);
+        let y = p.evaluate(&x);
+        let proof = open_eval(&p.coeffs, r, x).unwrap();
+        assert_eq!(proof.value, y);
+        assert!(check_eval(c, x, y, &proof).unwrap());
+        assert!(!check_eval(c, x, y + Fr::one(), &proof).unwrap());
+        assert!(!check_eval(c, x + Fr::one(), y, &proof).unwrap());
+
+        // Zero at a root
+        let proof = open_eval(&p.coeffs, r, roots[2]).unwrap();
+        assert!(proof.value.is_zero());
+        assert!(check_eval(c, roots[2], Fr::zero(), &proof).unwrap());
+
+        // Separate domain from `open_linear` over the same weights
+        let linear = open_linear(&p.coeffs, r, &eval_weights(x, p.coeffs.len())).unwrap();
+        assert!(!check_eval(c, x, y, &linear).unwrap());
+    }
+
+    #[test]
+    fn test_linear_relation() {
+        let mut rng = thread_rng();
+        let (v1, v2) = (random_vector(), random_vector());
+        let (a, b) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let mut v3: Vec<Fr> = v1.iter().zip(&v2).map(|(x, y)| a * x + b * y).collect();
+        let (r1, r2, r3) = (Fr::rand(&mut rng), Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let (c1, c2, c3) = (commit(&v1, r1).unwrap(), commit(&v2, r2).unwrap(), commit(&v3, r3).unwrap());
+
+        let proof = prove_linear((c1, r1), (c2, r2), (c3, r3), (a, b), &mut rng);
+        assert!(verify_linear(c1, c2, c3, a, b, &proof));
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).unwrap();
+        let decoded = LinearRelationProof::deserialize_compressed(&bytes[..]).unwrap();
+        assert!(verify_linear(c1, c2, c3, a, b, &decoded));
+
+        // Wrong coefficient, swapped inputs
+        assert!(!verify_linear(c1, c2, c3, a + Fr::one(), b, &proof));
+        let wrong = prove_linear((c1, r1), (c2, r2), (c3, r3), (a + Fr::one(), b), &mut rng);
+        assert!(!verify_linear(c1, c2, c3, a + Fr::one(), b, &wrong));
+        assert!(!verify_linear(c2, c1, c3, a, b, &proof));
+
+        // Vectors that don't satisfy the relation
+        v3[6] += Fr::one();
+        let c3_bad = commit(&v3, r3).unwrap();
+        let bad = prove_linear((c1, r1), (c2, r2), (c3_bad, r3), (a, b), &mut rng);
+        assert!(!verify_linear(c1, c2, c3_bad, a, b, &bad));
+    }
+}
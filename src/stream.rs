@@ -0,0 +1,266 @@
+//! Commit to a vector without holding it in memory all at once.
+use ark_bls12_377::{Fr, G1Affine, G1Projective};
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::Zero;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::vec;
+use ark_std::vec::Vec;
+
+use crate::error::{self, Error};
+use crate::pcs::{max_vector_len, value_generator, BLINDING_INDEX, POINTS};
+
+/// Accumulates `Σ [v_j] G_{j+1}` as values arrive, in any order. The blinding
+/// term is only added in `finalize`, which yields the same point as `commit`
+/// on the full vector.
+pub struct StreamingCommitter {
+    acc: G1Projective,
+    r: Fr,
+    filled: Vec<bool>,
+    count: usize,
+}
+
+impl StreamingCommitter {
+    pub fn new(r: Fr) -> Self {
+        StreamingCommitter {
+            acc: G1Projective::zero(),
+            r,
+            filled: vec![false; POINTS.len() - 1],
+            count: 0,
+        }
+    }
+
+    /// Adds `value` at position `index`; each position may be pushed once.
+    pub fn push(&mut self, index: usize, value: Fr) -> error::Result<()> {
+        match self.filled.get_mut(index) {
+            None => return Err(Error::IndexOutOfBounds),
+            Some(true) => return Err(Error::DuplicateIndices),
+            Some(slot) => *slot = true,
+        }
+        self.acc += POINTS[index + 1] * value;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Pushes a contiguous chunk starting at position `start`.
+    pub fn push_chunk(&mut self, start: usize, values: &[Fr]) -> error::Result<()> {
+        values
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, v)| self.push(start + i, *v))
+    }
+
+    /// Adds the blinding term. Errors unless every position has been pushed.
+    pub fn finalize(self) -> error::Result<G1Affine> {
+        if self.count != self.filled.len() {
+            return Err(Error::LengthMismatch { expected: self.filled.len(), got: self.count });
+        }
+        Ok((self.acc + POINTS[BLINDING_INDEX] * self.r).into_affine())
+    }
+}
+
+/// Appends values in index order, keeping only `Σ [v_j] G_{j+1}` so far and
+/// the count, for vectors of any length up to `max_vector_len()`.
+///
+/// The state serializes with arkworks' canonical encoding, so a long ingest
+/// can checkpoint it and resume later; `finalize` on the resumed builder gives
+/// the same point as `commit` on everything pushed.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CommitmentBuilder {
+    acc: G1Projective,
+    len: usize,
+}
+
+impl Default for CommitmentBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommitmentBuilder {
+    pub fn new() -> Self {
+        CommitmentBuilder { acc: G1Projective::zero(), len: 0 }
+    }
+
+    /// Number of values pushed so far; the next one lands at this index.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Appends `value` at index `len()`. Errors once `max_vector_len()`
+    /// values have been pushed.
+    pub fn push(&mut self, value: Fr) -> error::Result<()> {
+        self.acc += value_generator(self.len)? * value;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Appends `values` with one multi-scalar multiplication. Either all of
+    /// them are pushed or, if they would exceed the capacity, none.
+    pub fn push_many(&mut self, values: &[Fr]) -> error::Result<()> {
+        // `len` may come from a deserialized checkpoint, so it can already be
+        // past the capacity
+        let room = max_vector_len().checked_sub(self.len).ok_or(Error::IndexOutOfBounds)?;
+        if values.len() > room {
+            return Err(Error::IndexOutOfBounds);
+        }
+        let bases = (self.len..self.len + values.len()).map(value_generator).collect::<error::Result<Vec<_>>>()?;
+        self.acc += G1Projective::msm_unchecked(&bases, values);
+        self.len += values.len();
+        Ok(())
+    }
+
+    /// Adds the blinding term: `commit` of the pushed values under `r`. The
+    /// builder is left as is, so more values can still follow.
+    pub fn finalize(&self, r: Fr) -> error::Result<G1Affine> {
+        Ok((self.acc + POINTS[BLINDING_INDEX] * r).into_affine())
+    }
+}
+
+/// Values buffered by `commit_iter` per multi-scalar multiplication.
+const ITER_CHUNK: usize = 256;
+
+/// `commit` over values produced lazily, returning the commitment and how many
+/// values were committed. Memory stays bounded by `ITER_CHUNK` however long the
+/// iterator is; errors once it yields more than `max_vector_len()` values.
+pub fn commit_iter(values: impl IntoIterator<Item = Fr>, r: Fr) -> error::Result<(G1Affine, usize)> {
+    let mut builder = CommitmentBuilder::new();
+    let mut chunk = Vec::with_capacity(ITER_CHUNK);
+    for value in values {
+        chunk.push(value);
+        if chunk.len() == ITER_CHUNK {
+            builder.push_many(&chunk)?;
+            chunk.clear();
+        }
+    }
+    builder.push_many(&chunk)?;
+    Ok((builder.finalize(r)?, builder.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcs::commit;
+    use ark_ff::UniformRand;
+    use rand::{seq::SliceRandom, thread_rng, Rng};
+
+    #[test]
+    fn test_streaming_matches_commit() {
+        let mut rng = thread_rng();
+        let v: Vec<Fr> = (0..POINTS.len() - 1).map(|_| Fr::rand(&mut rng)).collect();
+        let r = Fr::rand(&mut rng);
+        let expected = commit(&v, r).unwrap();
+
+        for _ in 0..5 {
+            let mut committer = StreamingCommitter::new(r);
+            let mut start = 0;
+            while start < v.len() {
+                let end = (start + rng.gen_range(1..=7)).min(v.len());
+                committer.push_chunk(start, &v[start..end]).unwrap();
+                start = end;
+            }
+            assert_eq!(committer.finalize().unwrap(), expected);
+        }
+
+        // Order does not matter
+        let mut order: Vec<usize> = (0..v.len()).collect();
+        order.shuffle(&mut rng);
+        let mut committer = StreamingCommitter::new(r);
+        for j in order {
+            committer.push(j, v[j]).unwrap();
+        }
+        assert_eq!(committer.finalize().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_streaming_rejects_bad_input() {
+        let mut rng = thread_rng();
+        let mut committer = StreamingCommitter::new(Fr::rand(&mut rng));
+        assert_eq!(committer.push(POINTS.len() - 1, Fr::from(1u64)), Err(Error::IndexOutOfBounds));
+        committer.push(3, Fr::from(1u64)).unwrap();
+        assert_eq!(committer.push(3, Fr::from(2u64)), Err(Error::DuplicateIndices));
+        assert_eq!(
+            committer.finalize(),
+            Err(Error::LengthMismatch { expected: POINTS.len() - 1, got: 1 })
+        );
+    }
+
+    #[cfg(not(feature = "verify-only"))]
+    #[test]
+    fn test_builder_matches_commit() {
+        let mut rng = thread_rng();
+        let v: Vec<Fr> = (0..300).map(|_| Fr::rand(&mut rng)).collect();
+        let r = Fr::rand(&mut rng);
+
+        let mut builder = CommitmentBuilder::new();
+        assert!(builder.is_empty());
+        let mut start = 0;
+        while start < v.len() {
+            let end = (start + rng.gen_range(1..=40)).min(v.len());
+            if end - start == 1 {
+                builder.push(v[start]).unwrap();
+            } else {
+                builder.push_many(&v[start..end]).unwrap();
+            }
+            start = end;
+            assert_eq!(builder.len(), end);
+            assert_eq!(builder.finalize(r).unwrap(), commit(&v[..end], r).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_builder_resumes_from_checkpoint() {
+        let mut rng = thread_rng();
+        let v: Vec<Fr> = (0..POINTS.len() - 1).map(|_| Fr::rand(&mut rng)).collect();
+        let r = Fr::rand(&mut rng);
+
+        let mut builder = CommitmentBuilder::new();
+        builder.push_many(&v[..7]).unwrap();
+        let mut checkpoint = Vec::new();
+        builder.serialize_compressed(&mut checkpoint).unwrap();
+
+        let mut resumed = CommitmentBuilder::deserialize_compressed(&checkpoint[..]).unwrap();
+        assert_eq!(resumed.len(), 7);
+        for x in &v[7..] {
+            resumed.push(*x).unwrap();
+        }
+        assert_eq!(resumed.finalize(r).unwrap(), commit(&v, r).unwrap());
+        assert!(CommitmentBuilder::deserialize_compressed(&checkpoint[..checkpoint.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_builder_capacity() {
+        let mut full = CommitmentBuilder { acc: G1Projective::zero(), len: max_vector_len() - 1 };
+        assert_eq!(full.push_many(&[Fr::from(1u64), Fr::from(2u64)]), Err(Error::IndexOutOfBounds));
+        assert_eq!(full.len(), max_vector_len() - 1);
+        full.push(Fr::from(1u64)).unwrap();
+        assert_eq!(full.push(Fr::from(2u64)), Err(Error::IndexOutOfBounds));
+        assert_eq!(full.push_many(&[Fr::from(2u64)]), Err(Error::IndexOutOfBounds));
+
+        // A checkpoint claiming more values than fit errors instead of panicking
+        let mut checkpoint = Vec::new();
+        CommitmentBuilder { acc: G1Projective::zero(), len: max_vector_len() + 5 }.serialize_compressed(&mut checkpoint).unwrap();
+        let mut over = CommitmentBuilder::deserialize_compressed(&checkpoint[..]).unwrap();
+        assert_eq!(over.push_many(&[]), Err(Error::IndexOutOfBounds));
+        assert_eq!(over.push_many(&[Fr::from(1u64)]), Err(Error::IndexOutOfBounds));
+        assert_eq!(over.push(Fr::from(1u64)), Err(Error::IndexOutOfBounds));
+    }
+
+    #[cfg(not(feature = "verify-only"))]
+    #[test]
+    fn test_commit_iter() {
+        let mut rng = thread_rng();
+        let r = Fr::rand(&mut rng);
+        for n in [0, 1, POINTS.len() - 1, ITER_CHUNK, 2 * ITER_CHUNK + 3] {
+            let v: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+            assert_eq!(commit_iter(v.iter().copied(), r).unwrap(), (commit(&v, r).unwrap(), n));
+        }
+        assert_eq!(commit_iter(core::iter::empty(), r).unwrap(), ((POINTS[BLINDING_INDEX] * r).into_affine(), 0));
+
+        let too_many = core::iter::repeat_n(Fr::from(1u64), max_vector_len() + 1);
+        assert_eq!(commit_iter(too_many, r), Err(Error::IndexOutOfBounds));
+    }
+}
@@ -0,0 +1,135 @@
+//! Known-answer vectors pinning the byte-exact output of the scheme, so other
+//! implementations can check they derive the same generators and commitments.
+//!
+//! `generate` evaluates fixed inputs and `check` compares against a vectors
+//! file in the `name = hex` format written by `render`. The checked-in file is
+//! `test_vectors.txt` at the crate root.
+use std::fmt::Write;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use ark_bls12_377::{Fr, G1Affine};
+use ark_serialize::CanonicalSerialize;
+
+use crate::accumulator::{check_non_membership, insert, insert_at_epoch};
+use crate::pcs::{batch_open, commit, hash_to_scalar, open, POINTS};
+
+/// Number of generators, blinding base first, included as golden values.
+pub const GOLDEN_GENERATORS: usize = 4;
+
+pub const GOLDEN: &str = include_str!("../test_vectors.txt");
+
+fn to_hex(x: &impl CanonicalSerialize) -> String {
+    let mut buf = Vec::new();
+    x.serialize_compressed(&mut buf).unwrap();
+    buf.iter().fold(String::new(), |mut s, b| {
+        write!(s, "{b:02x}").unwrap();
+        s
+    })
+}
+
+/// Fixed inputs: `v = (1, 2, ..., 20)`, `r = 42`, roots `1..=5` inserted from
+/// the identity with blinding 7 (also at epoch 7), non-membership of 100 from
+/// the identity, and `hash_to_scalar(b"abc")`.
+pub fn generate() -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    for (i, p) in POINTS.iter().take(GOLDEN_GENERATORS).enumerate() {
+        out.push((format!("generator_{i}"), to_hex(p)));
+    }
+
+    let v: Vec<Fr> = (1..POINTS.len() as u64).map(Fr::from).collect();
+    let r = Fr::from(42u64);
+    out.push(("commitment".into(), to_hex(&commit(&v, r).unwrap())));
+    out.push(("open_3_witness".into(), to_hex(&open(&v, r, 3).unwrap().witness)));
+    out.push(("batch_open_1_4_9_witness".into(), to_hex(&batch_open(&v, r, [1, 4, 9]).unwrap().witness)));
+
+    let roots: Vec<Fr> = (1..=5u64).map(Fr::from).collect();
+    let a_0 = G1Affine::default();
+    let state = insert(&roots, a_0, Fr::from(7u64)).unwrap();
+    out.push(("insert_commitment".into(), to_hex(&state.Commitment)));
+    out.push(("insert_accumulator".into(), to_hex(&state.Accumulator)));
+    let proof = check_non_membership(&roots, Fr::from(100u64), Fr::from(7u64), a_0).unwrap();
+    out.push(("non_membership_accumulator".into(), to_hex(&proof.state.Accumulator)));
+    let epoched = insert_at_epoch(7, &roots, a_0, Fr::from(7u64)).unwrap();
+    out.push(("insert_at_epoch_7_accumulator".into(), to_hex(&epoched.state.Accumulator)));
+    out.push(("hash_to_scalar_abc".into(), to_hex(&hash_to_scalar(b"abc"))));
+    out
+}
+
+pub fn render(vectors: &[(String, String)]) -> String {
+    vectors.iter().map(|(k, v)| format!("{k} = {v}\n")).collect()
+}
+
+/// Parses `name = hex` lines, skipping blank lines and `#` comments.
+pub fn parse(text: &str) -> Result<Vec<(String, String)>> {
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(|l| {
+            let (k, v) = l.split_once('=').ok_or_else(|| anyhow!("malformed vector line: {l}"))?;
+            Ok((k.trim().to_string(), v.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Checks this build against a vectors file, naming the first mismatch.
+pub fn check(text: &str) -> Result<()> {
+    let expected = parse(text)?;
+    let actual = generate();
+    for (name, value) in &expected {
+        match actual.iter().find(|(k, _)| k == name) {
+            Some((_, v)) if v == value => {}
+            Some((_, v)) => return Err(anyhow!("{name}: expected {value}, got {v}")),
+            None => return Err(anyhow!("unknown vector {name}")),
+        }
+    }
+    if expected.len() != actual.len() {
+        return Err(anyhow!("expected {} vectors, got {}", actual.len(), expected.len()));
+    }
+    Ok(())
+}
+
+/// Rewrites a vectors file from the current build, keeping its `#` header.
+pub fn regenerate(path: &Path) -> Result<()> {
+    let header: String = std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .take_while(|l| l.starts_with('#'))
+        .map(|l| format!("{l}\n"))
+        .collect();
+    std::fs::write(path, header + &render(&generate()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vectors_golden() {
+        if std::env::var_os("REGENERATE_TEST_VECTORS").is_some() {
+            regenerate(&Path::new(env!("CARGO_MANIFEST_DIR")).join("test_vectors.txt")).unwrap();
+            return;
+        }
+        assert!(!parse(GOLDEN).unwrap().is_empty(), "test_vectors.txt has no vectors; run with REGENERATE_TEST_VECTORS=1");
+        check(GOLDEN).unwrap();
+    }
+
+    #[test]
+    fn test_vectors_self_consistent() {
+        let vectors = generate();
+        assert_eq!(vectors, generate());
+        assert_eq!(parse(&render(&vectors)).unwrap(), vectors);
+        assert!(check(&render(&vectors)).is_ok());
+
+        // A flipped byte is caught and reported by name
+        let mut tampered = vectors.clone();
+        let value = &mut tampered[GOLDEN_GENERATORS].1;
+        let flipped = if value.starts_with('0') { "1" } else { "0" };
+        value.replace_range(..1, flipped);
+        let err = check(&render(&tampered)).unwrap_err().to_string();
+        assert!(err.starts_with("commitment:"), "{err}");
+
+        assert!(check(&render(&vectors[1..])).is_err());
+    }
+}
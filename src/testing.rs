@@ -0,0 +1,46 @@
+//! Random valid inputs for downstream tests, built with the real `commit` and
+//! `open` under the embedded generators.
+use ark_bls12_377::{Fr, G1Affine};
+use ark_ff::UniformRand;
+use ark_std::rand::Rng;
+use ark_std::vec::Vec;
+
+use crate::error::Result;
+use crate::pcs::{commit, default_vector_len, open, Opening};
+
+/// A random full-length vector, a random blinding and their commitment.
+pub fn random_commitment<R: Rng + ?Sized>(rng: &mut R) -> (Vec<Fr>, Fr, G1Affine) {
+    let v: Vec<Fr> = (0..default_vector_len()).map(|_| Fr::rand(rng)).collect();
+    let r = Fr::rand(rng);
+    let c = commit(&v, r).expect("full-length vector");
+    (v, r, c)
+}
+
+/// A random commitment opened at `index`: `(c, v_j, witness)`, which passes
+/// `check(c, index, v_j, witness).unwrap()`.
+pub fn random_opening<R: Rng + ?Sized>(rng: &mut R, index: usize) -> Result<(G1Affine, Fr, G1Affine)> {
+    let (v, r, c) = random_commitment(rng);
+    let Opening { value: v_j, witness, .. } = open(&v, r, index)?;
+    Ok((c, v_j, witness))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::pcs::check;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_random_inputs_verify() {
+        let mut rng = thread_rng();
+        let (v, r, c) = random_commitment(&mut rng);
+        assert_eq!(commit(&v, r).unwrap(), c);
+
+        for index in [0, 9, default_vector_len() - 1] {
+            let (c, v_j, witness) = random_opening(&mut rng, index).unwrap();
+            assert!(check(c, index, v_j, witness).unwrap());
+        }
+        assert_eq!(random_opening(&mut rng, default_vector_len()), Err(Error::IndexOutOfBounds));
+    }
+}
@@ -0,0 +1,88 @@
+//! Fiat-Shamir transcripts over merlin (STROBE-128).
+//!
+//! Every challenge in the crate comes from a `Transcript`: it is labelled with
+//! the crate and proof type, and bound to every message absorbed before it, so
+//! challenges from different proofs or positions cannot collide.
+use ark_bls12_377::{Fr, G1Affine};
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+
+/// Label of every transcript this crate creates.
+const PROTOCOL_LABEL: &[u8] = b"oblivious-syncing-service v1";
+
+pub struct Transcript(merlin::Transcript);
+
+impl Transcript {
+    /// A fresh transcript for one proof type; `protocol` separates proof types.
+    pub fn new(protocol: &[u8]) -> Self {
+        let mut transcript = merlin::Transcript::new(PROTOCOL_LABEL);
+        transcript.append_message(b"protocol", protocol);
+        Transcript(transcript)
+    }
+
+    pub fn append_point(&mut self, label: &'static [u8], p: &G1Affine) {
+        let mut buf = [0u8; 48];
+        p.serialize_compressed(&mut buf[..]).unwrap();
+        self.0.append_message(label, &buf);
+    }
+
+    pub fn append_scalar(&mut self, label: &'static [u8], x: &Fr) {
+        let mut buf = [0u8; 32];
+        x.serialize_compressed(&mut buf[..]).unwrap();
+        self.0.append_message(label, &buf);
+    }
+
+    pub fn append_u64(&mut self, label: &'static [u8], x: u64) {
+        self.0.append_u64(label, x);
+    }
+
+    /// 64 challenge bytes reduced mod r, so the bias is negligible.
+    pub fn challenge_scalar(&mut self, label: &'static [u8]) -> Fr {
+        let mut buf = [0u8; 64];
+        self.0.challenge_bytes(label, &mut buf);
+        Fr::from_le_bytes_mod_order(&buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcs::POINTS;
+
+    fn challenge(protocol: &[u8], points: &[G1Affine]) -> Fr {
+        let mut transcript = Transcript::new(protocol);
+        for p in points {
+            transcript.append_point(b"point", p);
+        }
+        transcript.challenge_scalar(b"challenge")
+    }
+
+    #[test]
+    fn test_transcript_deterministic() {
+        let points = [POINTS[1], POINTS[2]];
+        assert_eq!(challenge(b"test", &points), challenge(b"test", &points));
+
+        let mut transcript = Transcript::new(b"test");
+        transcript.append_u64(b"n", 7);
+        let first = transcript.challenge_scalar(b"c");
+        // Later challenges depend on earlier ones
+        assert_ne!(first, transcript.challenge_scalar(b"c"));
+    }
+
+    #[test]
+    fn test_transcript_input_sensitive() {
+        let base = challenge(b"test", &[POINTS[1], POINTS[2]]);
+        assert_ne!(base, challenge(b"other", &[POINTS[1], POINTS[2]]));
+        assert_ne!(base, challenge(b"test", &[POINTS[2], POINTS[1]]));
+        assert_ne!(base, challenge(b"test", &[POINTS[1], POINTS[3]]));
+        assert_ne!(base, challenge(b"test", &[POINTS[1]]));
+
+        let scalar = |label: &'static [u8], x: u64| {
+            let mut transcript = Transcript::new(b"test");
+            transcript.append_scalar(label, &Fr::from(x));
+            transcript.challenge_scalar(b"challenge")
+        };
+        assert_ne!(scalar(b"x", 1), scalar(b"x", 2));
+        assert_ne!(scalar(b"x", 1), scalar(b"y", 1));
+    }
+}
@@ -0,0 +1,74 @@
+//! `wasm-bindgen` wrappers for browser clients. Scalars and points cross the
+//! JS boundary as hex strings of their compressed canonical encoding.
+use ark_bls12_377::{Fr, G1Affine};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use wasm_bindgen::prelude::*;
+
+use crate::pcs;
+
+fn scalar_from_hex(s: &str) -> Result<Fr, JsError> {
+    Ok(Fr::deserialize_compressed(&hex::decode(s)?[..])?)
+}
+
+fn point_from_hex(s: &str) -> Result<G1Affine, JsError> {
+    Ok(pcs::deserialize_checked(&hex::decode(s)?)?)
+}
+
+fn to_hex(x: &impl CanonicalSerialize) -> String {
+    let mut buf = Vec::new();
+    x.serialize_compressed(&mut buf).unwrap();
+    hex::encode(buf)
+}
+
+fn scalars_from_hex(values: &[String]) -> Result<Vec<Fr>, JsError> {
+    values.iter().map(|s| scalar_from_hex(s)).collect()
+}
+
+#[wasm_bindgen]
+pub struct Opening {
+    value: String,
+    witness: String,
+}
+
+#[wasm_bindgen]
+impl Opening {
+    #[wasm_bindgen(getter)]
+    pub fn value(&self) -> String {
+        self.value.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn witness(&self) -> String {
+        self.witness.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub fn commit(values: Vec<String>, blinding: &str) -> Result<String, JsError> {
+    let c = pcs::commit(&scalars_from_hex(&values)?, scalar_from_hex(blinding)?)?;
+    Ok(to_hex(&c))
+}
+
+#[wasm_bindgen]
+pub fn open(values: Vec<String>, blinding: &str, index: u32) -> Result<Opening, JsError> {
+    let opening = pcs::open(&scalars_from_hex(&values)?, scalar_from_hex(blinding)?, index as usize)?;
+    Ok(Opening { value: to_hex(&opening.value), witness: to_hex(&opening.witness) })
+}
+
+#[wasm_bindgen]
+pub fn check(commitment: &str, value: &str, witness: &str, index: u32) -> Result<bool, JsError> {
+    pcs::check(point_from_hex(commitment)?, index as usize, scalar_from_hex(value)?, point_from_hex(witness)?)
+        .map_err(|_| JsError::new("Index out of bounds"))
+}
+
+#[wasm_bindgen]
+pub fn batch_check(commitment: &str, values: Vec<String>, witness: &str, indices: Vec<u32>, len: u32) -> Result<bool, JsError> {
+    let indices: Vec<usize> = indices.into_iter().map(|i| i as usize).collect();
+    Ok(pcs::batch_check(
+        point_from_hex(commitment)?,
+        &scalars_from_hex(&values)?,
+        point_from_hex(witness)?,
+        &indices,
+        len as usize,
+    )?)
+}
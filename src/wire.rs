@@ -0,0 +1,225 @@
+//! Stable, versioned byte encoding for batch proofs.
+//!
+//! Layout (version 2):
+//!
+//! ```text
+//! version: u8
+//! field*5: len: u32 LE || bytes
+//!   key_id      32-byte `CommitmentKey::id` of the key the proof was made under
+//!   commitment  compressed G1 point
+//!   indices     u64 LE each
+//!   values      32-byte compressed scalars each
+//!   witness     compressed G1 point
+//! ```
+//!
+//! Every field is length-prefixed so a decoder never guesses where one ends.
+//! Unknown versions are rejected before any field is read, so a future layout
+//! cannot be misparsed as the current one. Version 1 is version 2 without the
+//! `key_id` field; it predates key ids and decodes as the default key's.
+use ark_bls12_377::{Fr, G1Affine};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::vec;
+use ark_std::vec::Vec;
+
+use crate::error::{Error, Result};
+use crate::key::{CommitmentKey, KeyId};
+use crate::pcs::deserialize_checked;
+
+pub const WIRE_VERSION: u8 = 2;
+
+/// A batch opening together with the commitment it opens and the id of the
+/// key both were made under.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WireProof {
+    pub key_id: KeyId,
+    pub commitment: G1Affine,
+    pub indices: Vec<usize>,
+    pub values: Vec<Fr>,
+    pub witness: G1Affine,
+}
+
+fn put_field(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    out.extend_from_slice(bytes);
+}
+
+fn take_field<'a>(input: &mut &'a [u8]) -> Result<&'a [u8]> {
+    if input.len() < 4 {
+        return Err(Error::MalformedEncoding);
+    }
+    let (len, rest) = input.split_at(4);
+    let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+    if rest.len() < len {
+        return Err(Error::MalformedEncoding);
+    }
+    let (field, rest) = rest.split_at(len);
+    *input = rest;
+    Ok(field)
+}
+
+fn serialize<T: CanonicalSerialize>(x: &T) -> Vec<u8> {
+    let mut buf = Vec::new();
+    x.serialize_compressed(&mut buf).unwrap();
+    buf
+}
+
+fn decode_point(bytes: &[u8]) -> Result<G1Affine> {
+    if bytes.len() != G1Affine::default().compressed_size() {
+        return Err(Error::MalformedEncoding);
+    }
+    deserialize_checked(bytes)
+}
+
+impl WireProof {
+    pub fn to_wire(&self) -> Vec<u8> {
+        let mut out = vec![WIRE_VERSION];
+        put_field(&mut out, &self.key_id);
+        put_field(&mut out, &serialize(&self.commitment));
+        let indices: Vec<u8> = self.indices.iter().flat_map(|&j| (j as u64).to_le_bytes()).collect();
+        put_field(&mut out, &indices);
+        let values: Vec<u8> = self.values.iter().flat_map(serialize).collect();
+        put_field(&mut out, &values);
+        put_field(&mut out, &serialize(&self.witness));
+        out
+    }
+
+    pub fn from_wire(bytes: &[u8]) -> Result<Self> {
+        let (&version, mut rest) = bytes.split_first().ok_or(Error::MalformedEncoding)?;
+        let key_id = match version {
+            1 => CommitmentKey::default().id(),
+            WIRE_VERSION => take_field(&mut rest)?.try_into().map_err(|_| Error::MalformedEncoding)?,
+            _ => return Err(Error::UnsupportedVersion(version)),
+        };
+
+        let commitment = decode_point(take_field(&mut rest)?)?;
+
+        let indices = take_field(&mut rest)?;
+        if indices.len() % 8 != 0 {
+            return Err(Error::MalformedEncoding);
+        }
+        let indices = indices
+            .chunks_exact(8)
+            .map(|b| usize::try_from(u64::from_le_bytes(b.try_into().unwrap())).map_err(|_| Error::MalformedEncoding))
+            .collect::<Result<Vec<_>>>()?;
+
+        let values = take_field(&mut rest)?;
+        let scalar_len = Fr::default().compressed_size();
+        if values.len() % scalar_len != 0 {
+            return Err(Error::MalformedEncoding);
+        }
+        let values = values
+            .chunks_exact(scalar_len)
+            .map(|b| Fr::deserialize_compressed(b).map_err(|_| Error::MalformedEncoding))
+            .collect::<Result<Vec<_>>>()?;
+
+        let witness = decode_point(take_field(&mut rest)?)?;
+        if !rest.is_empty() {
+            return Err(Error::MalformedEncoding);
+        }
+
+        Ok(WireProof { key_id, commitment, indices, values, witness })
+    }
+
+    /// Verifies the decoded proof under the default key.
+    pub fn verify(&self) -> Result<bool> {
+        self.verify_with(&CommitmentKey::default())
+    }
+
+    /// Verifies the decoded proof with `key.batch_check`, after checking it
+    /// was made under `key`.
+    pub fn verify_with(&self, key: &CommitmentKey) -> Result<bool> {
+        if self.key_id != key.id() {
+            return Err(Error::KeyMismatch);
+        }
+        key.batch_check(self.commitment, &self.values, self.witness, &self.indices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcs::{batch_open, commit, derive_points, BatchOpening, POINTS, TRUSTED_SETUP};
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    fn proof() -> WireProof {
+        let mut rng = thread_rng();
+        let v: Vec<Fr> = (0..POINTS.len() - 1).map(|_| Fr::rand(&mut rng)).collect();
+        let r = Fr::rand(&mut rng);
+        let indices = vec![2, 5, 11];
+        let BatchOpening { values, witness, .. } = batch_open(&v, r, indices.iter().copied()).unwrap();
+        WireProof { key_id: CommitmentKey::default().id(), commitment: commit(&v, r).unwrap(), indices, values, witness }
+    }
+
+    /// `bytes` re-encoded in the version 1 layout, without the key id.
+    fn to_v1(bytes: &[u8]) -> Vec<u8> {
+        let mut v1 = vec![1];
+        v1.extend_from_slice(&bytes[1 + 4 + 32..]);
+        v1
+    }
+
+    #[test]
+    fn test_wire_roundtrip() {
+        let proof = proof();
+        let bytes = proof.to_wire();
+        assert_eq!(bytes[0], WIRE_VERSION);
+        let decoded = WireProof::from_wire(&bytes).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(decoded.verify().unwrap());
+    }
+
+    #[test]
+    fn test_wire_rejects_bad_input() {
+        let bytes = proof().to_wire();
+
+        for version in [0, WIRE_VERSION + 1, u8::MAX] {
+            let mut tagged = bytes.clone();
+            tagged[0] = version;
+            assert_eq!(WireProof::from_wire(&tagged), Err(Error::UnsupportedVersion(version)));
+        }
+
+        assert_eq!(WireProof::from_wire(&[]), Err(Error::MalformedEncoding));
+        assert_eq!(WireProof::from_wire(&bytes[..bytes.len() - 1]), Err(Error::MalformedEncoding));
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(WireProof::from_wire(&trailing), Err(Error::MalformedEncoding));
+
+        // A key id of the wrong length
+        let mut short_id = bytes.clone();
+        short_id[1] = 31;
+        assert_eq!(WireProof::from_wire(&short_id), Err(Error::MalformedEncoding));
+
+        // A field length running past the end of the input
+        let mut overlong = bytes.clone();
+        overlong[1] = 0xff;
+        assert_eq!(WireProof::from_wire(&overlong), Err(Error::MalformedEncoding));
+    }
+
+    #[test]
+    fn test_wire_key_id() {
+        let mut rng = thread_rng();
+        let custom = CommitmentKey::from_points(derive_points(&TRUSTED_SETUP, b"CUSTOM-KEY", POINTS.len()).unwrap());
+        let v: Vec<Fr> = (0..custom.vector_len()).map(|_| Fr::rand(&mut rng)).collect();
+        let r = Fr::rand(&mut rng);
+        let indices = vec![1, 3];
+        let (values, _, witness) = custom.batch_open(&v, r, indices.iter().copied()).unwrap();
+        let proof = WireProof { key_id: custom.id(), commitment: custom.commit(&v, r).unwrap(), indices, values, witness };
+
+        let decoded = WireProof::from_wire(&proof.to_wire()).unwrap();
+        assert!(decoded.verify_with(&custom).unwrap());
+        assert_eq!(decoded.verify(), Err(Error::KeyMismatch));
+
+        // Relabelling the proof with the default key's id does not make it verify
+        let relabelled = WireProof { key_id: CommitmentKey::default().id(), ..decoded };
+        assert!(!relabelled.verify().unwrap());
+        assert_eq!(relabelled.verify_with(&custom), Err(Error::KeyMismatch));
+    }
+
+    #[test]
+    fn test_wire_v1_uses_default_key() {
+        let proof = proof();
+        let decoded = WireProof::from_wire(&to_v1(&proof.to_wire())).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(decoded.verify().unwrap());
+    }
+}
@@ -0,0 +1,90 @@
+//! Property tests for the ordering contract between `batch_open` and
+//! `batch_check`: values come back in the order the indices were given, and
+//! the verifier accepts any order as long as each value sits at its index.
+
+use ark_bls12_377::Fr;
+use oblivious_syncing_service::error::Error;
+use oblivious_syncing_service::pcs::{batch_check, batch_open, commit, BatchOpening, POINTS};
+use proptest::prelude::*;
+use proptest::sample::subsequence;
+
+fn vector_len() -> usize {
+    POINTS.len() - 1
+}
+
+/// A committed vector with its blinding.
+fn committed() -> impl Strategy<Value = (Vec<Fr>, Fr)> {
+    (prop::collection::vec(any::<u64>(), vector_len()), any::<u64>())
+        .prop_map(|(v, r)| (v.into_iter().map(Fr::from).collect(), Fr::from(r)))
+}
+
+/// A non-empty set of valid indices, in arbitrary order.
+fn index_set(min: usize) -> impl Strategy<Value = Vec<usize>> {
+    subsequence((0..vector_len()).collect::<Vec<_>>(), min..=vector_len()).prop_shuffle()
+}
+
+/// An index set together with a permutation of its positions.
+fn index_set_and_permutation() -> impl Strategy<Value = (Vec<usize>, Vec<usize>)> {
+    index_set(2).prop_flat_map(|indices| {
+        let positions: Vec<usize> = (0..indices.len()).collect();
+        (Just(indices), Just(positions).prop_shuffle())
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn open_then_check_succeeds((v, r) in committed(), indices in index_set(1)) {
+        let c = commit(&v, r).unwrap();
+        let BatchOpening { values, witness, .. } = batch_open(&v, r, indices.iter().copied()).unwrap();
+
+        prop_assert_eq!(&values, &indices.iter().map(|&j| v[j]).collect::<Vec<_>>());
+        prop_assert_eq!(batch_check(c, &values, witness, &indices, vector_len()), Ok(true));
+    }
+
+    #[test]
+    fn reshuffled_pairs_still_verify(
+        (v, r) in committed(),
+        (indices, permutation) in index_set_and_permutation(),
+    ) {
+        let c = commit(&v, r).unwrap();
+        let BatchOpening { values, witness, .. } = batch_open(&v, r, indices.iter().copied()).unwrap();
+
+        // Move the (index, value) pairs around together; the witness doesn't
+        // depend on their order
+        let shuffled: Vec<usize> = permutation.iter().map(|&k| indices[k]).collect();
+        let shuffled_values: Vec<Fr> = permutation.iter().map(|&k| values[k]).collect();
+        prop_assert_eq!(batch_check(c, &shuffled_values, witness, &shuffled, vector_len()), Ok(true));
+    }
+
+    #[test]
+    fn duplicated_indices_are_rejected(
+        (v, r) in committed(),
+        indices in index_set(1),
+        dup in any::<prop::sample::Index>(),
+        at in any::<prop::sample::Index>(),
+    ) {
+        let c = commit(&v, r).unwrap();
+        let BatchOpening { values, witness, .. } = batch_open(&v, r, indices.iter().copied()).unwrap();
+
+        let i = dup.index(indices.len());
+        let k = at.index(indices.len() + 1);
+        let mut dup_indices = indices.clone();
+        dup_indices.insert(k, indices[i]);
+        let mut dup_values = values.clone();
+        dup_values.insert(k, values[i]);
+        prop_assert_eq!(batch_check(c, &dup_values, witness, &dup_indices, vector_len()), Err(Error::DuplicateIndices));
+        prop_assert_eq!(batch_open(&v, r, dup_indices).unwrap_err(), Error::DuplicateIndices);
+    }
+
+    #[test]
+    fn misaligned_values_fail((v, r) in committed(), indices in index_set(2)) {
+        let c = commit(&v, r).unwrap();
+        let BatchOpening { mut values, witness, .. } = batch_open(&v, r, indices.iter().copied()).unwrap();
+        prop_assume!(values[0] != values[1]);
+
+        values.swap(0, 1);
+        prop_assert_eq!(batch_check(c, &values, witness, &indices, vector_len()), Ok(false));
+    }
+}
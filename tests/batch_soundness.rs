@@ -0,0 +1,60 @@
+//! Property tests that a batch opening of one commitment never verifies
+//! against another, and that a witness folded over opened slots is caught by
+//! the opening proof even where `batch_check` accepts it.
+
+use ark_bls12_377::Fr;
+use ark_ec::CurveGroup;
+use oblivious_syncing_service::pcs::{batch_check, batch_open, commit, POINTS};
+use oblivious_syncing_service::sigma::{prove_batch_opening, verify_batch_opening};
+use proptest::prelude::*;
+use proptest::sample::subsequence;
+
+fn vector_len() -> usize {
+    POINTS.len() - 1
+}
+
+/// A committed vector with its blinding.
+fn committed() -> impl Strategy<Value = (Vec<Fr>, Fr)> {
+    (prop::collection::vec(any::<u64>(), vector_len()), any::<u64>())
+        .prop_map(|(v, r)| (v.into_iter().map(Fr::from).collect(), Fr::from(r)))
+}
+
+/// A non-empty set of valid indices in ascending order.
+fn index_set() -> impl Strategy<Value = Vec<usize>> {
+    subsequence((0..vector_len()).collect::<Vec<_>>(), 1..=vector_len())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn witness_does_not_transfer((v1, r1) in committed(), (v2, r2) in committed(), indices in index_set()) {
+        let c1 = commit(&v1, r1).unwrap();
+        let c2 = commit(&v2, r2).unwrap();
+        prop_assume!(c1 != c2);
+
+        let (values, witness, proof) = prove_batch_opening(&v1, r1, &indices).unwrap();
+        prop_assert_eq!(batch_check(c1, &values, witness, &indices, vector_len()), Ok(true));
+        prop_assert_eq!(batch_check(c2, &values, witness, &indices, vector_len()), Ok(false));
+        prop_assert!(verify_batch_opening(c1, &values, witness, &indices, &proof).unwrap());
+        prop_assert!(!verify_batch_opening(c2, &values, witness, &indices, &proof).unwrap());
+
+        // Values opened from c2 at the same indices don't fit c1's witness either
+        let other = batch_open(&v2, r2, indices.iter().copied()).unwrap().values;
+        prop_assume!(other != values);
+        prop_assert_eq!(batch_check(c1, &other, witness, &indices, vector_len()), Ok(false));
+    }
+
+    #[test]
+    fn folded_witness_fails_opening_proof((v, r) in committed(), indices in index_set(), shift in 1u64..) {
+        let c = commit(&v, r).unwrap();
+        let (mut values, witness, proof) = prove_batch_opening(&v, r, &indices).unwrap();
+
+        // Move `shift` out of the first opened value and into the witness
+        let delta = Fr::from(shift);
+        values[0] -= delta;
+        let forged = (witness + POINTS[indices[0] + 1] * delta).into_affine();
+        prop_assert_eq!(batch_check(c, &values, forged, &indices, vector_len()), Ok(true));
+        prop_assert!(!verify_batch_opening(c, &values, forged, &indices, &proof).unwrap());
+    }
+}
@@ -0,0 +1,47 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+fn bin() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_oblivious-syncing-service"))
+}
+
+fn temp_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("oss-cli-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_commit_open_verify() {
+    let dir = temp_dir();
+    let input = dir.join("values.json");
+    let commitment = dir.join("commitment.bin");
+    let proof = dir.join("proof.bin");
+
+    let values: Vec<String> = (1..=20).map(|i| format!("\"{}\"", i * 7)).collect();
+    fs::write(&input, format!("{{\"values\": [{}], \"blinding\": \"12345\"}}", values.join(", "))).unwrap();
+
+    let status = bin().args(["commit", "--input"]).arg(&input).arg("--out").arg(&commitment).status().unwrap();
+    assert!(status.success());
+
+    let status = bin().args(["open", "--index", "3", "--input"]).arg(&input).arg("--out").arg(&proof).status().unwrap();
+    assert!(status.success());
+
+    let output = bin().arg("verify").arg("--commitment").arg(&commitment).arg("--proof").arg(&proof).output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "valid");
+
+    // Opening against a commitment to different values must fail
+    let other_input = dir.join("other.json");
+    let other_commitment = dir.join("other.bin");
+    fs::write(&other_input, format!("{{\"values\": [{}], \"blinding\": \"1\"}}", values.join(", "))).unwrap();
+    let status = bin().args(["commit", "--input"]).arg(&other_input).arg("--out").arg(&other_commitment).status().unwrap();
+    assert!(status.success());
+
+    let output = bin().arg("verify").arg("--commitment").arg(&other_commitment).arg("--proof").arg(&proof).output().unwrap();
+    assert!(!output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "invalid");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "server")]
+
+use ark_bls12_377::Fr;
+use ark_serialize::CanonicalSerialize;
+use oblivious_syncing_service::server::proto::syncing_client::SyncingClient;
+use oblivious_syncing_service::server::proto::{CommitRequest, InsertRequest, OpenRequest, VerifyRequest};
+use oblivious_syncing_service::server::{SyncService, SyncingServer};
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::Server;
+
+fn encode(x: u64) -> Vec<u8> {
+    let mut buf = Vec::new();
+    Fr::from(x).serialize_compressed(&mut buf).unwrap();
+    buf
+}
+
+#[tokio::test]
+async fn test_commit_then_verify() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(
+        Server::builder()
+            .add_service(SyncingServer::new(SyncService::default()))
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+
+    let mut client = SyncingClient::connect(format!("http://{addr}")).await.unwrap();
+    let values: Vec<Vec<u8>> = (1..=20).map(encode).collect();
+    let blinding = encode(99);
+
+    let commitment = client
+        .commit(CommitRequest { values: values.clone(), blinding: blinding.clone() })
+        .await
+        .unwrap()
+        .into_inner()
+        .commitment;
+    let opening = client
+        .open(OpenRequest { values, blinding, index: 5 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let verify = |index, value: Vec<u8>| VerifyRequest {
+        commitment: commitment.clone(),
+        index,
+        value,
+        witness: opening.witness.clone(),
+    };
+    assert!(client.verify(verify(5, opening.value.clone())).await.unwrap().into_inner().valid);
+    assert!(!client.verify(verify(5, encode(0))).await.unwrap().into_inner().valid);
+    assert!(!client.verify(verify(6, opening.value.clone())).await.unwrap().into_inner().valid);
+
+    // Inserts advance the server-held accumulator
+    let roots: Vec<Vec<u8>> = (100..119).map(encode).collect();
+    let first = client.insert(InsertRequest { roots: roots.clone(), blinding: encode(1) }).await.unwrap().into_inner();
+    let second = client.insert(InsertRequest { roots, blinding: encode(1) }).await.unwrap().into_inner();
+    assert_eq!(first.commitment, second.commitment);
+    assert_ne!(first.accumulator, second.accumulator);
+}
@@ -0,0 +1,35 @@
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use ark_bls12_377::Fr;
+use ark_serialize::CanonicalSerialize;
+use oblivious_syncing_service::wasm::{batch_check, check, commit, open};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn scalar_hex(x: u64) -> String {
+    let mut buf = Vec::new();
+    Fr::from(x).serialize_compressed(&mut buf).unwrap();
+    hex::encode(buf)
+}
+
+#[wasm_bindgen_test]
+fn test_js_round_trip() {
+    let values: Vec<String> = (1..=20).map(scalar_hex).collect();
+    let blinding = scalar_hex(12345);
+
+    let c = commit(values.clone(), &blinding).unwrap();
+    let opening = open(values.clone(), &blinding, 3).unwrap();
+    assert!(check(&c, &opening.value(), &opening.witness(), 3).unwrap());
+    assert!(!check(&c, &scalar_hex(0), &opening.witness(), 3).unwrap());
+    assert!(!check(&c, &opening.value(), &opening.witness(), 100).unwrap());
+    assert!(check(&c, &opening.value(), &opening.witness(), 10_000).is_err());
+
+    // Batch-opening every index leaves only the blinding term as witness
+    let all: Vec<u32> = (0..20).collect();
+    let witness = {
+        let mut buf = Vec::new();
+        let blind = oblivious_syncing_service::pcs::POINTS[0] * Fr::from(12345u64);
+        ark_ec::CurveGroup::into_affine(blind).serialize_compressed(&mut buf).unwrap();
+        hex::encode(buf)
+    };
+    assert!(batch_check(&c, values, &witness, all, 20).unwrap());
+}